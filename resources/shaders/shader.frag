@@ -1,9 +1,19 @@
 #version 450
 
 layout(location = 0) in vec3 fragColor;
+//Red, green and blue block light and sky light, from 0 to 1.
+layout(location = 1) in vec4 fragLight;
+layout(location = 2) in float fragAo;
 
 layout(location = 0) out vec4 outColor;
 
+//Each light level below full is 20% darker, with a floor so unlit caves are not pitch black.
+vec3 brightness(vec3 light) {
+    return max(pow(vec3(0.8), (1.0 - light) * 15.0), vec3(0.05));
+}
+
 void main() {
-    outColor = vec4(fragColor, 1.0);
+    //Sky light is white, coloured block light tints where it is brighter.
+    vec3 light = max(brightness(fragLight.rgb), brightness(vec3(fragLight.a)));
+    outColor = vec4(fragColor * light * fragAo, 1.0);
 }
\ No newline at end of file
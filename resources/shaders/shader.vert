@@ -6,12 +6,21 @@ layout(binding = 0) uniform UniformBufferObject {
     mat4 proj;
 } ubo;
 
-layout(location = 0) in vec2 inPosition;
+layout(location = 0) in vec3 inPosition;
 layout(location = 1) in vec3 inColor;
+layout(location = 2) in vec4 inLight;
+layout(location = 3) in uint inAo;
 
 layout(location = 0) out vec3 fragColor;
+layout(location = 1) out vec4 fragLight;
+layout(location = 2) out float fragAo;
+
+//Brightness for each ambient occlusion level, from fully occluded to open.
+const float AO_BRIGHTNESS[4] = float[](0.4, 0.6, 0.8, 1.0);
 
 void main() {
-    gl_Position = ubo.proj * ubo.view * ubo.model * vec4(inPosition, 0.0, 1.0);
+    gl_Position = ubo.proj * ubo.view * ubo.model * vec4(inPosition, 1.0);
     fragColor = inColor;
+    fragLight = inLight;
+    fragAo = AO_BRIGHTNESS[inAo];
 }
\ No newline at end of file
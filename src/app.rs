@@ -1,16 +1,35 @@
+use crate::camera::Camera;
+use crate::game::Game;
+use crate::physics::{EYE_HEIGHT, Player, PlayerInput};
 use crate::renderer::Renderer;
+use crate::world::World;
+use crate::world::mesher::mesh_chunk;
+use crate::world::raycast::RaycastHit;
 use ash::vk;
 use ash::vk::{CommandBufferResetFlags, Fence, PipelineStageFlags, PresentInfoKHR, SubmitInfo};
+use glam::{IVec3, Vec2, Vec3};
+use std::collections::HashSet;
+use std::time::Instant;
 use winit::application::ApplicationHandler;
-use winit::event::WindowEvent;
+use winit::event::{ElementState, MouseButton, WindowEvent};
 use winit::event_loop::ActiveEventLoop;
+use winit::keyboard::{KeyCode, ModifiersState, PhysicalKey};
 use winit::window::{Window, WindowAttributes, WindowId};
 
-#[derive(Default)]
+//Blocks further away than this many voxels cannot be picked.
+const REACH_DISTANCE: f32 = 6.0;
+
 pub struct App {
     pub window: Option<Window>,
     pub renderer: Option<Renderer>,
     pub close_requested: bool,
+    pub game: Game,
+    pub camera: Camera,
+    cursor_position: Option<Vec2>,
+    selected_block: u8,
+    modifiers: ModifiersState,
+    held_keys: HashSet<KeyCode>,
+    last_frame: Option<Instant>,
 }
 
 impl ApplicationHandler for App {
@@ -57,12 +76,59 @@ impl ApplicationHandler for App {
             WindowEvent::Resized(_) => {
                 self.recreate_swap_chain();
             }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor_position = Some(Vec2::new(position.x as f32, position.y as f32));
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.cursor_position = None;
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button,
+                ..
+            } => {
+                self.edit_block(button);
+            }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers.state();
+            }
+            WindowEvent::KeyboardInput { event, .. } => {
+                let PhysicalKey::Code(code) = event.physical_key else {
+                    return;
+                };
+                if event.state == ElementState::Released {
+                    self.held_keys.remove(&code);
+                } else if self.modifiers.control_key() {
+                    self.edit_history(code);
+                } else {
+                    self.held_keys.insert(code);
+                    self.select_block(code);
+                }
+            }
             _ => (),
         }
     }
 }
 
 impl App {
+    pub fn new(world: World) -> Self {
+        let camera = Camera::looking_at(Vec3::new(-24.0, -24.0, 48.0), Vec3::new(16.0, 16.0, 16.0));
+        let player = Player::new(camera.position - Vec3::Z * EYE_HEIGHT);
+        let game = Game::new(world, player);
+        Self {
+            window: None,
+            renderer: None,
+            close_requested: false,
+            game,
+            camera,
+            cursor_position: None,
+            selected_block: 1,
+            modifiers: ModifiersState::empty(),
+            held_keys: HashSet::new(),
+            last_frame: None,
+        }
+    }
+
     fn renderer_mut(&mut self) -> &mut Renderer {
         self.renderer.as_mut().unwrap()
     }
@@ -102,6 +168,10 @@ impl App {
             }
         }
         let image_index = image_index.unwrap();
+        self.advance_game();
+        self.update_chunk_meshes();
+        let selection = self.pick_block().map(|hit| hit.position);
+        self.renderer_mut().set_outline(selection);
         unsafe {
             self.renderer().device.logical.reset_command_buffer(
                 self.renderer().command_buffer,
@@ -114,9 +184,13 @@ impl App {
         let signal_semaphores = [self.renderer().sync.render_finished_semaphore];
         let wait_semaphores = [self.renderer().sync.image_available_semaphore];
         let queue = self.renderer().device.queues.graphics.1;
-        self.renderer()
-            .buffers
-            .update_uniform_buffer(image_index, self.renderer().swapchain.extent);
+        let extent = self.renderer().swapchain.extent;
+        let aspect_ratio = extent.width as f32 / extent.height as f32;
+        self.renderer().buffers.update_uniform_buffer(
+            image_index,
+            self.camera.view_matrix(),
+            self.camera.projection_matrix(aspect_ratio),
+        );
         let submit_info = SubmitInfo::default()
             .command_buffers(&command_buffers)
             .signal_semaphores(&signal_semaphores)
@@ -182,4 +256,95 @@ impl App {
         self.renderer_mut().recreate_swap_chain();
         true
     }
+
+    //Runs the simulation ticks due since the last frame. The player walks with WASD relative to
+    //where the camera looks and jumps with space, and the camera follows the player's eyes.
+    fn advance_game(&mut self) {
+        let now = Instant::now();
+        let elapsed = self
+            .last_frame
+            .map_or(0.0, |last_frame| (now - last_frame).as_secs_f32());
+        self.last_frame = Some(now);
+        let forward = Vec2::from_angle(self.camera.yaw);
+        let right = Vec2::new(forward.y, -forward.x);
+        let held = |code: KeyCode| self.held_keys.contains(&code) as i32 as f32;
+        let input = PlayerInput {
+            movement: forward * (held(KeyCode::KeyW) - held(KeyCode::KeyS))
+                + right * (held(KeyCode::KeyD) - held(KeyCode::KeyA)),
+            jump: self.held_keys.contains(&KeyCode::Space),
+        };
+        self.game.advance(input, elapsed);
+        self.camera.position = self.game.interpolated_eye_position();
+    }
+
+    //Remeshes the chunks the world marked dirty and uploads them before the frame is recorded, so
+    //edits show up in the same frame. Chunks that were not remeshed keep their GPU meshes.
+    fn update_chunk_meshes(&mut self) {
+        let dirty_chunks = self.game.world.take_dirty_chunks();
+        if dirty_chunks.is_empty() {
+            return;
+        }
+        let renderer = self.renderer.as_mut().unwrap();
+        for chunk_pos in dirty_chunks {
+            renderer.upload_chunk_mesh(chunk_pos, &mesh_chunk(&self.game.world, chunk_pos));
+        }
+        renderer
+            .retain_chunk_meshes(|chunk_pos| self.game.world.loaded_chunks.contains_key(chunk_pos));
+    }
+
+    fn pick_block(&self) -> Option<RaycastHit> {
+        let cursor_position = self.cursor_position?;
+        let size = self.window().inner_size();
+        if size.width == 0 || size.height == 0 {
+            return None;
+        }
+        let size = Vec2::new(size.width as f32, size.height as f32);
+        let ndc = cursor_position / size * 2.0 - 1.0;
+        let (origin, direction) = self.camera.ray_through(ndc, size.x / size.y);
+        self.game.world.raycast(origin, direction, REACH_DISTANCE)
+    }
+
+    fn edit_block(&mut self, button: MouseButton) {
+        let Some(hit) = self.pick_block() else {
+            return;
+        };
+        match button {
+            MouseButton::Left => {
+                self.game.world.set_block(hit.position, 0);
+            }
+            MouseButton::Right if hit.normal != IVec3::ZERO => {
+                let target = hit.position + hit.normal;
+                if self.game.world.get_block(target) == Some(0) {
+                    self.game.world.set_block(target, self.selected_block);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn select_block(&mut self, code: KeyCode) {
+        let block = match code {
+            KeyCode::Digit1 => 1,
+            KeyCode::Digit2 => 2,
+            KeyCode::Digit3 => 3,
+            KeyCode::Digit4 => 4,
+            KeyCode::Digit5 => 5,
+            KeyCode::Digit6 => 6,
+            KeyCode::Digit7 => 7,
+            KeyCode::Digit8 => 8,
+            KeyCode::Digit9 => 9,
+            KeyCode::Digit0 => 10,
+            _ => return,
+        };
+        self.selected_block = block;
+    }
+
+    fn edit_history(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::KeyZ if self.modifiers.shift_key() => self.game.world.redo(),
+            KeyCode::KeyZ => self.game.world.undo(),
+            KeyCode::KeyY => self.game.world.redo(),
+            _ => false,
+        };
+    }
 }
@@ -0,0 +1,59 @@
+mod tests;
+
+use glam::{Mat4, Vec2, Vec3};
+
+const FIELD_OF_VIEW_DEGREES: f32 = 60.0;
+const NEAR_PLANE: f32 = 0.05;
+const FAR_PLANE: f32 = 1000.0;
+
+//Free camera in world voxel units. The world is z up; yaw is measured from the x axis towards the
+//y axis and pitch from the horizontal plane.
+pub struct Camera {
+    pub position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+impl Camera {
+    pub fn looking_at(position: Vec3, target: Vec3) -> Self {
+        let direction = (target - position).normalize();
+        Self {
+            position,
+            yaw: direction.y.atan2(direction.x),
+            pitch: direction.z.asin(),
+        }
+    }
+
+    pub fn forward(&self) -> Vec3 {
+        Vec3::new(
+            self.pitch.cos() * self.yaw.cos(),
+            self.pitch.cos() * self.yaw.sin(),
+            self.pitch.sin(),
+        )
+    }
+
+    pub fn view_matrix(&self) -> Mat4 {
+        Mat4::look_to_rh(self.position, self.forward(), Vec3::Z)
+    }
+
+    pub fn projection_matrix(&self, aspect_ratio: f32) -> Mat4 {
+        let mut projection = Mat4::perspective_rh(
+            FIELD_OF_VIEW_DEGREES.to_radians(),
+            aspect_ratio,
+            NEAR_PLANE,
+            FAR_PLANE,
+        );
+        //Vulkan clip space has y pointing down.
+        projection.y_axis.y *= -1.0;
+        projection
+    }
+
+    //Origin and direction of the ray through a point in normalized device coordinates, where
+    //(-1, -1) is the top left corner of the screen.
+    pub fn ray_through(&self, ndc: Vec2, aspect_ratio: f32) -> (Vec3, Vec3) {
+        let inverse = (self.projection_matrix(aspect_ratio) * self.view_matrix()).inverse();
+        let near = inverse.project_point3(Vec3::new(ndc.x, ndc.y, 0.0));
+        let far = inverse.project_point3(Vec3::new(ndc.x, ndc.y, 1.0));
+        (near, (far - near).normalize())
+    }
+}
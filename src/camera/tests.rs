@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod tests {
+    use crate::camera::Camera;
+    use glam::{Vec2, Vec3};
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!(a.distance(b) < 1e-4, "{a} != {b}");
+    }
+
+    #[test]
+    fn test_looking_at() {
+        let camera = Camera::looking_at(Vec3::ZERO, Vec3::new(3.0, 3.0, 0.0));
+        assert_close(camera.forward(), Vec3::new(1.0, 1.0, 0.0).normalize());
+
+        let camera = Camera::looking_at(Vec3::ONE, Vec3::new(1.0, 1.0, -4.0) + Vec3::X * 0.001);
+        assert!(camera.forward().z < -0.99);
+    }
+
+    #[test]
+    fn test_center_ray_is_forward() {
+        let camera = Camera::looking_at(Vec3::new(-5.0, 2.0, 10.0), Vec3::new(4.0, 0.0, 1.0));
+        let (origin, direction) = camera.ray_through(Vec2::ZERO, 1.5);
+        assert_close(direction, camera.forward());
+        assert!(origin.distance(camera.position) < 0.1);
+    }
+
+    #[test]
+    fn test_screen_orientation() {
+        //Looking along +x with z up, the top of the screen is +z and the right side is -y, so
+        //the world is not mirrored.
+        let camera = Camera::looking_at(Vec3::ZERO, Vec3::X);
+        let (_, top) = camera.ray_through(Vec2::new(0.0, -1.0), 1.0);
+        let (_, right) = camera.ray_through(Vec2::new(1.0, 0.0), 1.0);
+        assert!(top.z > 0.1);
+        assert!(right.y < -0.1);
+        assert!(top.x > 0.0 && right.x > 0.0);
+    }
+
+    #[test]
+    fn test_projection_depth_range() {
+        let camera = Camera::looking_at(Vec3::ZERO, Vec3::X);
+        let view_projection = camera.projection_matrix(1.0) * camera.view_matrix();
+        let near = view_projection.project_point3(Vec3::new(0.05, 0.0, 0.0));
+        let far = view_projection.project_point3(Vec3::new(1000.0, 0.0, 0.0));
+        assert!(near.z.abs() < 1e-4);
+        assert!((far.z - 1.0).abs() < 1e-4);
+    }
+}
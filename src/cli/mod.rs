@@ -0,0 +1,309 @@
+mod tests;
+
+use crate::game::{Game, run_headless};
+use crate::physics::Player;
+use crate::utility::sparse_spatial_octree::RadiusError;
+use crate::world::World;
+use crate::world::block_registry::BlockRegistry;
+use crate::world::chunk::MAX_HEIGHT;
+use crate::world::edit::Region;
+use crate::world::heightmap::{Heightmap, HeightmapOptions};
+use crate::world::seed::{DEFAULT_SEED, WorldSeed};
+use glam::{IVec3, Vec3};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+pub const USAGE: &str = "Usage:
+  vxl [<world options>]
+      Open the world in a window.
+  vxl export <file.obj|file.glb> [--radius <chunks>] [--region <x1> <y1> <z1> <x2> <y2> <z2>]
+             [<world options>]
+      Mesh the generated world without opening a window and write it to an OBJ (with an MTL next
+      to it) or a binary glTF file. Only chunks overlapping --region are written if it is given.
+  vxl simulate <ticks> [--radius <chunks>] [--paced] [<world options>]
+      Run the simulation without a window, back to back or at the tick rate with --paced.
+
+World options:
+  --seed <seed>
+      The world is generated from the seed, an integer or any text, the same seed always giving
+      the same world.
+  --heightmap <png> [--materials <png>]
+      Take the terrain heights from a grayscale image, brighter is higher, and optionally the
+      surface blocks from an image of the same size painted in block colors.";
+
+pub const DEFAULT_RADIUS: i32 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Obj,
+    Glb,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeightmapFiles {
+    pub heights: PathBuf,
+    pub materials: Option<PathBuf>,
+}
+
+//What the world of every command is generated from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorldSource {
+    pub seed: WorldSeed,
+    pub heightmap: Option<HeightmapFiles>,
+}
+
+impl Default for WorldSource {
+    fn default() -> Self {
+        Self {
+            seed: DEFAULT_SEED,
+            heightmap: None,
+        }
+    }
+}
+
+impl WorldSource {
+    pub fn create_world(&self, radius: i32) -> Result<World, CliError> {
+        let Some(files) = &self.heightmap else {
+            return World::with_seed(radius, self.seed).map_err(CliError::Radius);
+        };
+        let heightmap = Heightmap::load(
+            &files.heights,
+            files.materials.as_deref(),
+            &BlockRegistry::default(),
+            HeightmapOptions::default(),
+        )
+        .map_err(CliError::Io)?;
+        World::with_heightmap(radius, self.seed, heightmap).map_err(CliError::Radius)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Window {
+        world: WorldSource,
+    },
+    Export {
+        path: PathBuf,
+        format: ExportFormat,
+        radius: i32,
+        region: Option<Region>,
+        world: WorldSource,
+    },
+    Simulate {
+        ticks: u64,
+        radius: i32,
+        paced: bool,
+        world: WorldSource,
+    },
+}
+
+#[derive(Debug)]
+pub enum CliError {
+    UnknownCommand(String),
+    UnknownOption(String),
+    MissingArgument(&'static str),
+    InvalidNumber(String),
+    UnsupportedFormat(PathBuf),
+    Radius(RadiusError),
+    Io(std::io::Error),
+}
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::UnknownCommand(command) => write!(f, "Unknown command {}", command),
+            CliError::UnknownOption(option) => write!(f, "Unknown option {}", option),
+            CliError::MissingArgument(argument) => write!(f, "Missing {}", argument),
+            CliError::InvalidNumber(value) => write!(f, "{} is not a valid integer", value),
+            CliError::UnsupportedFormat(path) => write!(
+                f,
+                "Cannot tell the export format of {}, use a .obj or .glb extension",
+                path.display()
+            ),
+            CliError::Radius(err) => write!(f, "{}", err),
+            CliError::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+//`args` excludes the program name.
+pub fn parse(args: &[String]) -> Result<Command, CliError> {
+    let Some(command) = args.first() else {
+        return Ok(Command::Window {
+            world: WorldSource::default(),
+        });
+    };
+    match command.as_str() {
+        "export" => parse_export(args[1..].iter()),
+        "simulate" => parse_simulate(args[1..].iter()),
+        _ if command.starts_with("--") => parse_window(args.iter()),
+        _ => Err(CliError::UnknownCommand(command.clone())),
+    }
+}
+
+fn parse_window<'a>(mut args: impl Iterator<Item = &'a String>) -> Result<Command, CliError> {
+    let mut world = WorldOptions::default();
+    while let Some(option) = args.next() {
+        if !world.parse_option(option, &mut args)? {
+            return Err(CliError::UnknownOption(option.clone()));
+        }
+    }
+    Ok(Command::Window {
+        world: world.finish()?,
+    })
+}
+
+fn parse_export<'a>(mut args: impl Iterator<Item = &'a String>) -> Result<Command, CliError> {
+    let path = PathBuf::from(
+        args.next()
+            .ok_or(CliError::MissingArgument("output file"))?,
+    );
+    let format = match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) if extension.eq_ignore_ascii_case("obj") => ExportFormat::Obj,
+        Some(extension) if extension.eq_ignore_ascii_case("glb") => ExportFormat::Glb,
+        _ => return Err(CliError::UnsupportedFormat(path)),
+    };
+    let mut radius = DEFAULT_RADIUS;
+    let mut region = None;
+    let mut world = WorldOptions::default();
+    while let Some(option) = args.next() {
+        match option.as_str() {
+            "--radius" => radius = parse_number(args.next(), "radius")?,
+            "--region" => {
+                let mut corners = [0; 6];
+                for value in &mut corners {
+                    *value = parse_number(args.next(), "region corner")?;
+                }
+                region = Some(Region::new(
+                    IVec3::from_slice(&corners[0..3]),
+                    IVec3::from_slice(&corners[3..6]),
+                ));
+            }
+            _ if world.parse_option(option, &mut args)? => {}
+            _ => return Err(CliError::UnknownOption(option.clone())),
+        }
+    }
+    Ok(Command::Export {
+        path,
+        format,
+        radius,
+        region,
+        world: world.finish()?,
+    })
+}
+
+fn parse_simulate<'a>(mut args: impl Iterator<Item = &'a String>) -> Result<Command, CliError> {
+    let ticks = args.next().ok_or(CliError::MissingArgument("tick count"))?;
+    let ticks = ticks
+        .parse()
+        .map_err(|_| CliError::InvalidNumber(ticks.clone()))?;
+    let mut radius = DEFAULT_RADIUS;
+    let mut paced = false;
+    let mut world = WorldOptions::default();
+    while let Some(option) = args.next() {
+        match option.as_str() {
+            "--radius" => radius = parse_number(args.next(), "radius")?,
+            "--paced" => paced = true,
+            _ if world.parse_option(option, &mut args)? => {}
+            _ => return Err(CliError::UnknownOption(option.clone())),
+        }
+    }
+    Ok(Command::Simulate {
+        ticks,
+        radius,
+        paced,
+        world: world.finish()?,
+    })
+}
+
+//The world options shared by every command, as they are parsed.
+#[derive(Default)]
+struct WorldOptions {
+    seed: Option<WorldSeed>,
+    heightmap: Option<PathBuf>,
+    materials: Option<PathBuf>,
+}
+
+impl WorldOptions {
+    //Returns false if `option` is not a world option.
+    fn parse_option<'a>(
+        &mut self,
+        option: &str,
+        args: &mut impl Iterator<Item = &'a String>,
+    ) -> Result<bool, CliError> {
+        match option {
+            "--seed" => self.seed = Some(parse_seed(args.next())?),
+            "--heightmap" => self.heightmap = Some(parse_path(args.next(), "heightmap image")?),
+            "--materials" => self.materials = Some(parse_path(args.next(), "material image")?),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    fn finish(self) -> Result<WorldSource, CliError> {
+        let heightmap = match (self.heightmap, self.materials) {
+            (Some(heights), materials) => Some(HeightmapFiles { heights, materials }),
+            (None, Some(_)) => return Err(CliError::MissingArgument("--heightmap")),
+            (None, None) => None,
+        };
+        Ok(WorldSource {
+            seed: self.seed.unwrap_or(DEFAULT_SEED),
+            heightmap,
+        })
+    }
+}
+
+fn parse_number(value: Option<&String>, name: &'static str) -> Result<i32, CliError> {
+    let value = value.ok_or(CliError::MissingArgument(name))?;
+    value
+        .parse()
+        .map_err(|_| CliError::InvalidNumber(value.clone()))
+}
+
+fn parse_seed(value: Option<&String>) -> Result<WorldSeed, CliError> {
+    let value = value.ok_or(CliError::MissingArgument("seed"))?;
+    Ok(WorldSeed::from_text(value))
+}
+
+fn parse_path(value: Option<&String>, name: &'static str) -> Result<PathBuf, CliError> {
+    value
+        .map(PathBuf::from)
+        .ok_or(CliError::MissingArgument(name))
+}
+
+//Generates a world without a window and exports its meshes. Returns the number of chunks written.
+pub fn export(
+    path: &Path,
+    format: ExportFormat,
+    radius: i32,
+    region: Option<&Region>,
+    world: &WorldSource,
+) -> Result<usize, CliError> {
+    let world = world.create_world(radius)?;
+    let export = match region {
+        Some(region) => world.export_region(region),
+        None => world.export_loaded_chunks(),
+    };
+    match format {
+        ExportFormat::Obj => export.save_obj(path),
+        ExportFormat::Glb => export.save_glb(path),
+    }
+    .map_err(CliError::Io)?;
+    Ok(export.chunks.len())
+}
+
+//Runs the simulation without a window, with the player dropped above the middle of the world.
+pub fn simulate(
+    ticks: u64,
+    radius: i32,
+    paced: bool,
+    world: &WorldSource,
+) -> Result<Game, CliError> {
+    let world = world.create_world(radius)?;
+    let spawn = Vec3::new(0.5, 0.5, (MAX_HEIGHT + 2) as f32);
+    let mut game = Game::new(world, Player::new(spawn));
+    run_headless(&mut game, ticks, paced);
+    Ok(game)
+}
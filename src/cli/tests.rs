@@ -0,0 +1,211 @@
+#[cfg(test)]
+mod tests {
+    use crate::cli::{
+        CliError, Command, DEFAULT_RADIUS, ExportFormat, HeightmapFiles, WorldSource, export, parse,
+    };
+    use crate::world::edit::Region;
+    use crate::world::seed::{DEFAULT_SEED, WorldSeed};
+    use glam::ivec3;
+    use std::path::PathBuf;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse(&[]).unwrap(),
+            Command::Window {
+                world: WorldSource::default()
+            }
+        );
+        assert_eq!(
+            parse(&args(&["--seed", "island"])).unwrap(),
+            Command::Window {
+                world: WorldSource {
+                    seed: WorldSeed::from_text("island"),
+                    heightmap: None,
+                }
+            }
+        );
+        assert_eq!(
+            parse(&args(&["export", "world.OBJ"])).unwrap(),
+            Command::Export {
+                path: PathBuf::from("world.OBJ"),
+                format: ExportFormat::Obj,
+                radius: DEFAULT_RADIUS,
+                region: None,
+                world: WorldSource::default(),
+            }
+        );
+        assert_eq!(
+            parse(&args(&[
+                "export",
+                "out/world.glb",
+                "--region",
+                "5",
+                "-3",
+                "0",
+                "-5",
+                "3",
+                "10",
+                "--radius",
+                "2",
+                "--seed",
+                "-7"
+            ]))
+            .unwrap(),
+            Command::Export {
+                path: PathBuf::from("out/world.glb"),
+                format: ExportFormat::Glb,
+                radius: 2,
+                region: Some(Region::new(ivec3(-5, -3, 0), ivec3(5, 3, 10))),
+                world: WorldSource {
+                    seed: WorldSeed(-7i64 as u64),
+                    heightmap: None,
+                },
+            }
+        );
+        assert_eq!(
+            parse(&args(&["simulate", "600", "--paced", "--radius", "3"])).unwrap(),
+            Command::Simulate {
+                ticks: 600,
+                radius: 3,
+                paced: true,
+                world: WorldSource::default(),
+            }
+        );
+        assert_eq!(
+            parse(&args(&[
+                "simulate",
+                "10",
+                "--materials",
+                "paint.png",
+                "--heightmap",
+                "island.png"
+            ]))
+            .unwrap(),
+            Command::Simulate {
+                ticks: 10,
+                radius: DEFAULT_RADIUS,
+                paced: false,
+                world: WorldSource {
+                    seed: DEFAULT_SEED,
+                    heightmap: Some(HeightmapFiles {
+                        heights: PathBuf::from("island.png"),
+                        materials: Some(PathBuf::from("paint.png")),
+                    }),
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            parse(&args(&["import"])),
+            Err(CliError::UnknownCommand(_))
+        ));
+        assert!(matches!(
+            parse(&args(&["export"])),
+            Err(CliError::MissingArgument(_))
+        ));
+        assert!(matches!(
+            parse(&args(&["export", "world.fbx"])),
+            Err(CliError::UnsupportedFormat(_))
+        ));
+        assert!(matches!(
+            parse(&args(&["export", "world.obj", "--radius", "two"])),
+            Err(CliError::InvalidNumber(_))
+        ));
+        assert!(matches!(
+            parse(&args(&["export", "world.obj", "--region", "1", "2", "3"])),
+            Err(CliError::MissingArgument(_))
+        ));
+        assert!(matches!(
+            parse(&args(&["export", "world.obj", "--fast"])),
+            Err(CliError::UnknownOption(_))
+        ));
+        assert!(matches!(
+            parse(&args(&["simulate"])),
+            Err(CliError::MissingArgument(_))
+        ));
+        assert!(matches!(
+            parse(&args(&["simulate", "-5"])),
+            Err(CliError::InvalidNumber(_))
+        ));
+        assert!(matches!(
+            parse(&args(&["simulate", "5", "--seed"])),
+            Err(CliError::MissingArgument(_))
+        ));
+        assert!(matches!(
+            parse(&args(&["--radius", "2"])),
+            Err(CliError::UnknownOption(_))
+        ));
+        assert!(matches!(
+            parse(&args(&["--heightmap"])),
+            Err(CliError::MissingArgument(_))
+        ));
+        //Materials only paint a heightmap.
+        assert!(matches!(
+            parse(&args(&["export", "world.obj", "--materials", "paint.png"])),
+            Err(CliError::MissingArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_export() {
+        let path = std::env::temp_dir().join(format!("vxl_cli_{}.glb", std::process::id()));
+        let region = Region::new(ivec3(0, 0, 0), ivec3(40, 10, 10));
+        let world = WorldSource::default();
+        let chunks = export(&path, ExportFormat::Glb, 2, Some(&region), &world);
+        let bytes = std::fs::read(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        //Terrain fills the bottom of both chunks the region overlaps.
+        assert_eq!(chunks.unwrap(), 2);
+        assert_eq!(&bytes.unwrap()[0..4], b"glTF");
+        assert!(matches!(
+            export(&path, ExportFormat::Obj, 0, None, &world),
+            Err(CliError::Radius(_))
+        ));
+    }
+
+    #[test]
+    fn test_heightmap_world() {
+        let dir = std::env::temp_dir();
+        let heights = dir.join(format!("vxl_cli_heightmap_{}.png", std::process::id()));
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, 1, 1);
+        encoder.set_color(png::ColorType::Grayscale);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&[255]).unwrap();
+        writer.finish().unwrap();
+        std::fs::write(&heights, &png).unwrap();
+
+        let mut source = WorldSource {
+            seed: DEFAULT_SEED,
+            heightmap: Some(HeightmapFiles {
+                heights: heights.clone(),
+                materials: None,
+            }),
+        };
+        let world = source.create_world(1);
+        source.heightmap = Some(HeightmapFiles {
+            heights: heights.clone(),
+            materials: Some(dir.join("vxl_cli_missing_materials.png")),
+        });
+        let missing = source.create_world(1);
+        std::fs::remove_file(&heights).unwrap();
+
+        //A white pixel is the highest point of the default heightmap scale.
+        let world = world.unwrap();
+        assert!(world.get_block(ivec3(0, 0, 30)).unwrap() != 0);
+        assert_eq!(world.get_block(ivec3(0, 0, 31)), Some(0));
+        assert!(matches!(
+            missing,
+            Err(CliError::Io(err)) if err.kind() == std::io::ErrorKind::NotFound
+        ));
+    }
+}
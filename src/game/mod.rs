@@ -0,0 +1,85 @@
+mod tests;
+
+use crate::physics::{EYE_HEIGHT, Player, PlayerInput};
+use crate::world::World;
+use glam::Vec3;
+use std::time::{Duration, Instant};
+
+pub const TICKS_PER_SECOND: u32 = 60;
+pub const TIME_STEP: f32 = 1.0 / TICKS_PER_SECOND as f32;
+//Ticks run by one advance at most, so a long frame does not freeze the game catching up.
+const MAX_TICKS_PER_ADVANCE: u32 = 10;
+
+//The simulated state: the world and the player in it. The simulation only moves in fixed
+//TIME_STEP ticks, whatever the frame rate, and frames are drawn between the last two ticks.
+pub struct Game {
+    pub world: World,
+    pub player: Player,
+    previous_player_position: Vec3,
+    //Time not simulated yet, less than one tick after an advance.
+    pending_time: f32,
+}
+
+impl Game {
+    pub fn new(world: World, player: Player) -> Self {
+        Self {
+            world,
+            previous_player_position: player.position,
+            player,
+            pending_time: 0.0,
+        }
+    }
+
+    //Advances the simulation by one TIME_STEP.
+    pub fn tick(&mut self, input: PlayerInput) {
+        self.previous_player_position = self.player.position;
+        self.player.tick(&self.world, input);
+        self.world.on_player_moved(self.player.position);
+        self.world.tick();
+    }
+
+    //Runs as many ticks as fit in `elapsed` seconds plus the time left over by the last advance.
+    //Returns the number of ticks run.
+    pub fn advance(&mut self, input: PlayerInput, elapsed: f32) -> u32 {
+        self.pending_time =
+            (self.pending_time + elapsed).min(TIME_STEP * MAX_TICKS_PER_ADVANCE as f32);
+        let mut ticks = 0;
+        while self.pending_time >= TIME_STEP {
+            self.pending_time -= TIME_STEP;
+            self.tick(input);
+            ticks += 1;
+        }
+        ticks
+    }
+
+    //Ticks run since the game started, counted by the world.
+    pub fn tick_count(&self) -> u64 {
+        self.world.tick_count()
+    }
+
+    //How far the time since the last tick is towards the next one, from 0 to 1.
+    pub fn interpolation(&self) -> f32 {
+        (self.pending_time / TIME_STEP).clamp(0.0, 1.0)
+    }
+
+    //Where to draw the player's eyes, between its positions after the last two ticks. This lags
+    //up to a tick behind the simulation but moves smoothly at any frame rate.
+    pub fn interpolated_eye_position(&self) -> Vec3 {
+        self.previous_player_position
+            .lerp(self.player.position, self.interpolation())
+            + Vec3::Z * EYE_HEIGHT
+    }
+}
+
+//Runs `ticks` ticks without a window and without input. Paced ticks wait for their time like a
+//server would, others run back to back.
+pub fn run_headless(game: &mut Game, ticks: u64, paced: bool) {
+    let start = Instant::now();
+    for tick in 1..=ticks {
+        game.tick(PlayerInput::default());
+        if paced {
+            let due = start + Duration::from_secs_f64(tick as f64 / TICKS_PER_SECOND as f64);
+            std::thread::sleep(due.saturating_duration_since(Instant::now()));
+        }
+    }
+}
@@ -0,0 +1,71 @@
+#[cfg(test)]
+mod tests {
+    use crate::game::{Game, TICKS_PER_SECOND, TIME_STEP, run_headless};
+    use crate::physics::{EYE_HEIGHT, Player, PlayerInput};
+    use crate::world::World;
+    use glam::{Vec2, Vec3};
+
+    fn create_test_game() -> Game {
+        let world = World::new(4).unwrap();
+        Game::new(world, Player::new(Vec3::new(5.5, 5.5, 50.0)))
+    }
+
+    fn walk() -> PlayerInput {
+        PlayerInput {
+            movement: Vec2::X,
+            jump: false,
+        }
+    }
+
+    #[test]
+    fn test_advance_runs_whole_ticks() {
+        let mut game = create_test_game();
+        assert_eq!(game.advance(walk(), TIME_STEP * 0.5), 0);
+        assert!((game.interpolation() - 0.5).abs() < 1e-4);
+        assert_eq!(game.advance(walk(), TIME_STEP), 1);
+        assert!((game.interpolation() - 0.5).abs() < 1e-4);
+        assert_eq!(game.advance(walk(), TIME_STEP * 2.5), 3);
+        assert_eq!(game.tick_count(), 4);
+        //A long stall does not make the game catch up for seconds.
+        assert_eq!(game.advance(walk(), 5.0), 10);
+    }
+
+    #[test]
+    fn test_uneven_frames_match_fixed_ticks() {
+        let mut fixed = create_test_game();
+        let mut framed = create_test_game();
+        for frame in 0..200 {
+            let input = PlayerInput {
+                movement: Vec2::from_angle(frame as f32 * 0.1),
+                jump: frame % 50 == 49,
+            };
+            let elapsed = TIME_STEP * [0.5, 1.0, 2.5][frame % 3];
+            for _ in 0..framed.advance(input, elapsed) {
+                fixed.tick(input);
+            }
+        }
+        assert!(fixed.tick_count() > 100);
+        assert_eq!(fixed.tick_count(), framed.tick_count());
+        assert_eq!(fixed.player, framed.player);
+    }
+
+    #[test]
+    fn test_interpolated_eye_position() {
+        let mut game = create_test_game();
+        game.advance(walk(), TIME_STEP * 5.25);
+        let previous = game.previous_player_position;
+        let eye = game.interpolated_eye_position() - Vec3::Z * EYE_HEIGHT;
+        assert!((eye - previous.lerp(game.player.position, 0.25)).length() < 1e-4);
+        assert!(eye.x > previous.x && eye.x < game.player.position.x);
+    }
+
+    #[test]
+    fn test_headless_runner() {
+        let mut game = create_test_game();
+        run_headless(&mut game, 3 * TICKS_PER_SECOND as u64, false);
+        assert_eq!(game.tick_count(), 180);
+        //The player fell onto the generated terrain.
+        assert!(game.player.on_ground);
+        assert!(game.player.position.z < 32.0);
+    }
+}
@@ -1,17 +1,70 @@
 mod app;
+mod camera;
+mod cli;
+mod game;
+mod physics;
 mod renderer;
 mod utility;
 mod world;
 
-use crate::world::World;
+use crate::cli::{Command, WorldSource};
 use app::App;
 use winit::event_loop::{ControlFlow, EventLoop};
 
 fn main() {
-    let _world = World::new(4);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command = cli::parse(&args).unwrap_or_else(|err| {
+        eprintln!("{}\n\n{}", err, cli::USAGE);
+        std::process::exit(2);
+    });
+    match command {
+        Command::Window { world } => run_window(&world),
+        Command::Export {
+            path,
+            format,
+            radius,
+            region,
+            world,
+        } => match cli::export(&path, format, radius, region.as_ref(), &world) {
+            Ok(chunks) => println!("Exported {} chunks to {}", chunks, path.display()),
+            Err(err) => {
+                eprintln!("Export failed: {}", err);
+                std::process::exit(1);
+            }
+        },
+        Command::Simulate {
+            ticks,
+            radius,
+            paced,
+            world,
+        } => {
+            let start = std::time::Instant::now();
+            match cli::simulate(ticks, radius, paced, &world) {
+                Ok(game) => println!(
+                    "Simulated {} ticks in {:.2?}, player at {}",
+                    game.tick_count(),
+                    start.elapsed(),
+                    game.player.position
+                ),
+                Err(err) => {
+                    eprintln!("Simulation failed: {}", err);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+}
+
+fn run_window(source: &WorldSource) {
+    let world = source
+        .create_world(cli::DEFAULT_RADIUS)
+        .unwrap_or_else(|err| {
+            eprintln!("Could not create world: {}", err);
+            std::process::exit(1);
+        });
     let event_loop = EventLoop::new().unwrap();
     event_loop.set_control_flow(ControlFlow::Poll);
-    let mut app = App::default();
+    let mut app = App::new(world);
     event_loop
         .run_app(&mut app)
         .expect("Could not run event loop");
@@ -0,0 +1,173 @@
+mod tests;
+
+use crate::game::TIME_STEP;
+use crate::world::World;
+use crate::world::block_registry::AIR;
+use glam::{IVec3, Vec2, Vec3};
+
+pub const PLAYER_WIDTH: f32 = 0.6;
+pub const PLAYER_HEIGHT: f32 = 1.8;
+pub const EYE_HEIGHT: f32 = 1.62;
+const WALK_SPEED: f32 = 4.3;
+const GRAVITY: f32 = 28.0;
+//Enough to jump about 1.4 blocks high.
+const JUMP_SPEED: f32 = 9.0;
+const TERMINAL_VELOCITY: f32 = 60.0;
+//Walking into a ledge at most this high climbs it without jumping.
+const STEP_HEIGHT: f32 = 1.0;
+//Boxes closer than this to a block face count as touching it, so rounding errors do not let them
+//sink into blocks.
+const EPSILON: f32 = 1e-4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    //Moves the box `distance` along `axis` (0, 1 or 2 for x, y and z) until it touches a solid
+    //voxel and returns how far it got. Voxels the box already overlaps do not block it, so a box
+    //stuck inside terrain can still get out.
+    fn sweep(&mut self, world: &World, axis: usize, distance: f32) -> f32 {
+        if distance == 0.0 {
+            return 0.0;
+        }
+        let mut swept = *self;
+        if distance > 0.0 {
+            swept.max[axis] += distance;
+        } else {
+            swept.min[axis] += distance;
+        }
+        let min = swept.min.floor().as_ivec3();
+        let max = swept.max.ceil().as_ivec3() - 1;
+        let mut allowed = distance;
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                for z in min.z..=max.z {
+                    let voxel = IVec3::new(x, y, z);
+                    if !is_solid(world, voxel) {
+                        continue;
+                    }
+                    let voxel_min = voxel.as_vec3();
+                    let voxel_max = voxel_min + 1.0;
+                    let overlaps_sideways = (0..3).filter(|other| *other != axis).all(|other| {
+                        self.min[other] < voxel_max[other] - EPSILON
+                            && self.max[other] > voxel_min[other] + EPSILON
+                    });
+                    if !overlaps_sideways {
+                        continue;
+                    }
+                    if distance > 0.0 && voxel_min[axis] >= self.max[axis] - EPSILON {
+                        allowed = allowed.min(voxel_min[axis] - self.max[axis]);
+                    } else if distance < 0.0 && voxel_max[axis] <= self.min[axis] + EPSILON {
+                        allowed = allowed.max(voxel_max[axis] - self.min[axis]);
+                    }
+                }
+            }
+        }
+        self.min[axis] += allowed;
+        self.max[axis] += allowed;
+        allowed
+    }
+
+    //Sweeps along z first, then x and y, and returns the motion that was possible.
+    fn sweep_all(&mut self, world: &World, motion: Vec3) -> Vec3 {
+        let mut moved = Vec3::ZERO;
+        for axis in [2, 0, 1] {
+            moved[axis] = self.sweep(world, axis, motion[axis]);
+        }
+        moved
+    }
+}
+
+//Fluids are not solid. Blocks in unloaded chunks are, so the player cannot walk or fall out of the
+//loaded world.
+fn is_solid(world: &World, world_pos: IVec3) -> bool {
+    world.get_block(world_pos).is_none_or(|block| {
+        block != AIR
+            && world
+                .registry()
+                .get(block)
+                .is_none_or(|block| block.fluid.is_none())
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PlayerInput {
+    //Wished walking direction in the world xy plane. Longer vectors are shortened to length 1.
+    pub movement: Vec2,
+    pub jump: bool,
+}
+
+//A walking player, simulated in fixed TIME_STEP ticks so that the same inputs always give the same
+//path. The position is the center of the bottom of its bounding box.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Player {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub on_ground: bool,
+}
+
+impl Player {
+    pub fn new(position: Vec3) -> Self {
+        Self {
+            position,
+            velocity: Vec3::ZERO,
+            on_ground: false,
+        }
+    }
+
+    pub fn aabb(&self) -> Aabb {
+        let half_width = Vec3::new(PLAYER_WIDTH / 2.0, PLAYER_WIDTH / 2.0, 0.0);
+        Aabb::new(
+            self.position - half_width,
+            self.position + half_width + Vec3::Z * PLAYER_HEIGHT,
+        )
+    }
+
+    //Advances the player by one TIME_STEP.
+    pub fn tick(&mut self, world: &World, input: PlayerInput) {
+        let walk = input.movement.clamp_length_max(1.0) * WALK_SPEED;
+        self.velocity.x = walk.x;
+        self.velocity.y = walk.y;
+        if input.jump && self.on_ground {
+            self.velocity.z = JUMP_SPEED;
+        }
+        self.velocity.z = (self.velocity.z - GRAVITY * TIME_STEP).max(-TERMINAL_VELOCITY);
+
+        let motion = self.velocity * TIME_STEP;
+        let start = self.aabb();
+        let mut aabb = start;
+        let mut moved = aabb.sweep_all(world, motion);
+        let blocked_sideways = moved.x != motion.x || moved.y != motion.y;
+        if blocked_sideways && self.on_ground {
+            //Tries again from up to STEP_HEIGHT higher and keeps that if it gets further, dropping
+            //back down onto whatever is below.
+            let mut stepped = start;
+            let lift = stepped.sweep(world, 2, STEP_HEIGHT);
+            let mut stepped_moved = stepped.sweep_all(world, motion.with_z(0.0));
+            stepped_moved.z = lift + stepped.sweep(world, 2, -lift + motion.z.min(0.0));
+            if stepped_moved.truncate().length_squared() > moved.truncate().length_squared() {
+                aabb = stepped;
+                moved = stepped_moved;
+            }
+        }
+
+        self.on_ground = motion.z < 0.0 && moved.z > motion.z;
+        for axis in 0..3 {
+            if moved[axis] != motion[axis] {
+                self.velocity[axis] = 0.0;
+            }
+        }
+        self.position = Vec3::new(
+            (aabb.min.x + aabb.max.x) / 2.0,
+            (aabb.min.y + aabb.max.y) / 2.0,
+            aabb.min.z,
+        );
+    }
+}
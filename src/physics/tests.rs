@@ -0,0 +1,126 @@
+#[cfg(test)]
+mod tests {
+    use crate::game::TICKS_PER_SECOND;
+    use crate::physics::{Player, PlayerInput};
+    use crate::world::World;
+    use crate::world::block_registry::STONE;
+    use crate::world::edit::Region;
+    use crate::world::tests::tests::create_test_world_with_floor;
+    use glam::{Vec2, Vec3, ivec3};
+
+    fn run(world: &World, player: &mut Player, input: PlayerInput, ticks: u32) {
+        for _ in 0..ticks {
+            player.tick(world, input);
+        }
+    }
+
+    fn walk(x: f32, y: f32) -> PlayerInput {
+        PlayerInput {
+            movement: Vec2::new(x, y),
+            jump: false,
+        }
+    }
+
+    #[test]
+    fn test_falls_onto_floor() {
+        let world = create_test_world_with_floor();
+        let mut player = Player::new(Vec3::new(5.5, 5.5, 75.0));
+        run(&world, &mut player, PlayerInput::default(), 10);
+        assert!(!player.on_ground);
+        assert!(player.velocity.z < 0.0);
+
+        run(
+            &world,
+            &mut player,
+            PlayerInput::default(),
+            TICKS_PER_SECOND,
+        );
+        assert!(player.on_ground);
+        assert!((player.position.z - 65.0).abs() < 1e-3);
+        assert_eq!(player.velocity.z, 0.0);
+    }
+
+    #[test]
+    fn test_wall_stops_walking() {
+        let mut world = create_test_world_with_floor();
+        world
+            .fill(&Region::new(ivec3(10, 0, 65), ivec3(10, 20, 66)), STONE)
+            .unwrap();
+        let mut player = Player::new(Vec3::new(5.5, 5.5, 65.0));
+        run(&world, &mut player, walk(1.0, 0.0), 2 * TICKS_PER_SECOND);
+        assert!((player.position.x - 9.7).abs() < 1e-3);
+        assert!((player.position.z - 65.0).abs() < 1e-3);
+
+        //Walking diagonally slides along the wall.
+        let y = player.position.y;
+        run(&world, &mut player, walk(1.0, 1.0), TICKS_PER_SECOND);
+        assert!((player.position.x - 9.7).abs() < 1e-3);
+        assert!(player.position.y > y + 2.0);
+    }
+
+    #[test]
+    fn test_steps_onto_single_block() {
+        let mut world = create_test_world_with_floor();
+        world
+            .fill(&Region::new(ivec3(8, 0, 65), ivec3(20, 20, 65)), STONE)
+            .unwrap();
+        let mut player = Player::new(Vec3::new(5.5, 5.5, 65.0));
+        run(&world, &mut player, PlayerInput::default(), 1);
+        run(&world, &mut player, walk(1.0, 0.0), TICKS_PER_SECOND);
+        assert!((player.position.z - 66.0).abs() < 1e-3);
+        assert!(player.position.x > 8.5);
+        assert!(player.on_ground);
+    }
+
+    #[test]
+    fn test_jump() {
+        let mut world = create_test_world_with_floor();
+        let mut player = Player::new(Vec3::new(5.5, 5.5, 65.0));
+        run(&world, &mut player, PlayerInput::default(), 1);
+        let jump = PlayerInput {
+            movement: Vec2::ZERO,
+            jump: true,
+        };
+        let mut highest: f32 = 0.0;
+        for _ in 0..TICKS_PER_SECOND {
+            player.tick(&world, jump);
+            highest = highest.max(player.position.z);
+        }
+        assert!(highest > 66.2 && highest < 66.6, "{}", highest);
+
+        //A ceiling two blocks above the floor stops the jump.
+        world
+            .fill(&Region::new(ivec3(0, 0, 67), ivec3(20, 20, 67)), STONE)
+            .unwrap();
+        let mut player = Player::new(Vec3::new(5.5, 5.5, 65.0));
+        run(&world, &mut player, PlayerInput::default(), 1);
+        player.tick(&world, jump);
+        highest = 0.0;
+        for _ in 0..TICKS_PER_SECOND {
+            player.tick(&world, PlayerInput::default());
+            highest = highest.max(player.aabb().max.z);
+        }
+        assert!(highest > 66.9);
+        assert!(highest <= 67.0);
+        assert!(player.on_ground);
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let mut world = create_test_world_with_floor();
+        world
+            .fill(&Region::new(ivec3(8, 3, 65), ivec3(9, 9, 65)), STONE)
+            .unwrap();
+        let script = |tick: u32| PlayerInput {
+            movement: Vec2::new((tick as f32 * 0.1).cos(), (tick as f32 * 0.07).sin()),
+            jump: tick.is_multiple_of(45),
+        };
+        let mut first = Player::new(Vec3::new(5.5, 5.5, 70.0));
+        let mut second = first.clone();
+        for tick in 0..300 {
+            first.tick(&world, script(tick));
+            second.tick(&world, script(tick));
+        }
+        assert_eq!(first, second);
+    }
+}
@@ -1,34 +1,73 @@
 use crate::renderer::device::Device;
-use crate::renderer::vertex::{Vertex, get_indices, get_vertices};
+use crate::renderer::vertex::Vertex;
 use ash::Instance;
 use ash::vk::*;
-use glam::{Mat4, vec3, vec4};
+use glam::{IVec3, Mat4, Vec3};
+use std::collections::HashMap;
 use std::ffi::c_void;
 use std::ptr;
-use std::time::Instant;
 
 pub const UNIFORM_BUFFER_COUNT: u8 = 3;
+//Twelve edges of the selection box.
+const OUTLINE_VERTEX_COUNT: usize = 24;
+//Keeps the outline from z-fighting with the faces of the selected block.
+const OUTLINE_PADDING: f32 = 0.002;
 #[allow(dead_code)]
 pub struct UniformBufferObject {
     model: Mat4,
     view: Mat4,
     proj: Mat4,
 }
+
+pub struct MeshBuffer {
+    pub buffer: Buffer,
+    pub memory: DeviceMemory,
+    pub indices_offset: usize,
+    pub index_count: u32,
+}
+
+impl MeshBuffer {
+    fn destroy(&self, logical_device: &ash::Device) {
+        unsafe {
+            logical_device.destroy_buffer(self.buffer, None);
+            logical_device.free_memory(self.memory, None);
+        }
+    }
+}
+
 pub struct Buffers {
-    pub start_time: Instant,
-    pub combined_buffer: Buffer,
-    pub combined_buffer_memory: DeviceMemory,
-    pub combined_buffer_indices_offset: usize,
+    //One buffer per chunk, so an edit only uploads the chunks it remeshed.
+    pub chunk_meshes: HashMap<IVec3, MeshBuffer>,
+    pub outline_buffer: Buffer,
+    pub outline_buffer_memory: DeviceMemory,
+    pub outline_buffer_mapped: *mut c_void,
+    pub outline_vertex_count: u32,
     pub uniform_buffers: Vec<Buffer>,
     pub uniform_buffers_memory: Vec<DeviceMemory>,
     pub uniform_buffers_mapped: Vec<*mut c_void>,
 }
 
 impl Buffers {
-    pub fn new(instance: &Instance, device: &Device, transfer_command_pool: &CommandPool) -> Self {
-        let start_time = Instant::now();
-        let (combined_buffer, combined_buffer_memory, combined_buffer_indices_offset) =
-            Self::create_combined_buffer(device, instance, transfer_command_pool);
+    pub fn new(instance: &Instance, device: &Device) -> Self {
+        let outline_buffer_size = (size_of::<Vertex>() * OUTLINE_VERTEX_COUNT) as DeviceSize;
+        let (outline_buffer, outline_buffer_memory) = Self::create_buffer(
+            device,
+            instance,
+            BufferUsageFlags::VERTEX_BUFFER,
+            MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            outline_buffer_size,
+            SharingMode::EXCLUSIVE,
+            &[],
+        );
+        let outline_buffer_mapped = unsafe {
+            device.logical.map_memory(
+                outline_buffer_memory,
+                0,
+                outline_buffer_size,
+                MemoryMapFlags::empty(),
+            )
+        }
+        .expect("Could not map memory");
         let buffer_size = size_of::<UniformBufferObject>() as DeviceSize;
         let mut uniform_buffers: Vec<Buffer> = Vec::from([]);
         let mut uniform_buffers_memory: Vec<DeviceMemory> = Vec::from([]);
@@ -59,40 +98,23 @@ impl Buffers {
         }
 
         Self {
-            start_time,
-            combined_buffer,
-            combined_buffer_memory,
-            combined_buffer_indices_offset,
+            chunk_meshes: HashMap::new(),
+            outline_buffer,
+            outline_buffer_memory,
+            outline_buffer_mapped,
+            outline_vertex_count: 0,
             uniform_buffers,
             uniform_buffers_memory,
             uniform_buffers_mapped,
         }
     }
 
-    pub fn update_uniform_buffer(&self, image_index: u32, extent: Extent2D) {
-        let identity_matrix = Mat4 {
-            x_axis: vec4(1.0, 0.0, 0.0, 0.0),
-            y_axis: vec4(0.0, -1.0, 0.0, 0.0),
-            z_axis: vec4(0.0, 0.0, 1.0, 0.0),
-            w_axis: vec4(0.0, 0.0, 0.0, 1.0),
+    pub fn update_uniform_buffer(&self, image_index: u32, view: Mat4, proj: Mat4) {
+        let ubo = UniformBufferObject {
+            model: Mat4::IDENTITY,
+            view,
+            proj,
         };
-        let current_time = Instant::now();
-        let elapsed = current_time.duration_since(self.start_time).as_secs_f32();
-        let model = Mat4::from_rotation_z(elapsed * 90.0_f32.to_radians()) * identity_matrix;
-        // let view = Mat4::IDENTITY;
-        // let proj = Mat4::IDENTITY;
-        let view = Mat4::look_at_lh(
-            vec3(1.0, 1.0, 2.0),
-            vec3(0.0, 0.0, 0.0),
-            vec3(0.0, 0.0, 1.0),
-        ) * identity_matrix;
-        let proj = Mat4::perspective_lh(
-            45.0_f32.to_radians(),
-            extent.width as f32 / extent.height as f32,
-            0.01,
-            100.0,
-        ) * identity_matrix;
-        let ubo = UniformBufferObject { model, view, proj };
         unsafe {
             ptr::copy_nonoverlapping(
                 &ubo as *const UniformBufferObject as *const c_void,
@@ -101,21 +123,96 @@ impl Buffers {
             );
         }
     }
+
+    //Replaces the mesh of a chunk, an empty mesh removes it. The caller has to make sure the
+    //previous mesh is no longer in use.
+    pub fn upload_chunk_mesh(
+        &mut self,
+        device: &Device,
+        instance: &Instance,
+        command_pool: &CommandPool,
+        chunk_pos: IVec3,
+        vertices: &[Vertex],
+        indices: &[u32],
+    ) {
+        if let Some(mesh) = self.chunk_meshes.remove(&chunk_pos) {
+            mesh.destroy(&device.logical);
+        }
+        if indices.is_empty() {
+            return;
+        }
+        let (buffer, memory, indices_offset) =
+            Self::create_combined_buffer(device, instance, command_pool, vertices, indices);
+        self.chunk_meshes.insert(
+            chunk_pos,
+            MeshBuffer {
+                buffer,
+                memory,
+                indices_offset,
+                index_count: indices.len() as u32,
+            },
+        );
+    }
+
+    //Frees the meshes of the chunks `keep` returns false for.
+    pub fn retain_chunk_meshes(&mut self, device: &Device, mut keep: impl FnMut(&IVec3) -> bool) {
+        self.chunk_meshes.retain(|chunk_pos, mesh| {
+            let kept = keep(chunk_pos);
+            if !kept {
+                mesh.destroy(&device.logical);
+            }
+            kept
+        });
+    }
+
+    pub fn update_outline(&mut self, block: Option<IVec3>) {
+        let Some(block) = block else {
+            self.outline_vertex_count = 0;
+            return;
+        };
+        let min = block.as_vec3() - OUTLINE_PADDING;
+        let size = 1.0 + OUTLINE_PADDING * 2.0;
+        let corner = |index: usize| {
+            min + Vec3::new(
+                (index & 1) as f32,
+                (index >> 1 & 1) as f32,
+                (index >> 2 & 1) as f32,
+            ) * size
+        };
+        let mut vertices = Vec::with_capacity(OUTLINE_VERTEX_COUNT);
+        //Edges connect corners whose indices differ in exactly one bit.
+        for start in 0..8 {
+            for bit in [1, 2, 4] {
+                if start & bit == 0 {
+                    vertices.push(Vertex::new(corner(start), Vec3::ONE));
+                    vertices.push(Vertex::new(corner(start | bit), Vec3::ONE));
+                }
+            }
+        }
+        unsafe {
+            ptr::copy_nonoverlapping(
+                vertices.as_ptr() as *const c_void,
+                self.outline_buffer_mapped,
+                size_of::<Vertex>() * vertices.len(),
+            );
+        }
+        self.outline_vertex_count = vertices.len() as u32;
+    }
+
     fn create_combined_buffer(
         device: &Device,
         instance: &Instance,
         command_pool: &CommandPool,
+        vertices: &[Vertex],
+        indices: &[u32],
     ) -> (Buffer, DeviceMemory, usize) {
         //vertices
-        let vertices = get_vertices();
-        let vertices_size = size_of::<Vertex>() * vertices.len();
+        let vertices_size = size_of_val(vertices);
         let buffer_size = vertices_size;
         let aligned_buffer_size =
             (buffer_size + device.min_buffer_alignment - 1) & !(device.min_buffer_alignment - 1);
 
-        let indices = get_indices();
-        let index_size = size_of::<u16>();
-        let indices_size = index_size * indices.len();
+        let indices_size = size_of_val(indices);
         let buffer_size = aligned_buffer_size + indices_size;
 
         let (staging_buffer, staging_buffer_memory) = Self::create_buffer(
@@ -295,7 +392,7 @@ impl Buffers {
         Self::end_command_buffer(device, command_buffer, *command_pool);
     }
 
-    fn find_memory_type_index(
+    pub fn find_memory_type_index(
         physical_device: &PhysicalDevice,
         instance: &Instance,
         type_filter: u32,
@@ -317,8 +414,11 @@ impl Buffers {
 
     pub fn cleanup(&self, logical_device: &ash::Device) {
         unsafe {
-            logical_device.destroy_buffer(self.combined_buffer, None);
-            logical_device.free_memory(self.combined_buffer_memory, None);
+            for mesh in self.chunk_meshes.values() {
+                mesh.destroy(logical_device);
+            }
+            logical_device.destroy_buffer(self.outline_buffer, None);
+            logical_device.free_memory(self.outline_buffer_memory, None);
             for buffer in &self.uniform_buffers {
                 logical_device.destroy_buffer(*buffer, None);
             }
@@ -0,0 +1,79 @@
+use crate::renderer::buffers::Buffers;
+use crate::renderer::device::Device;
+use ash::Instance;
+use ash::vk::{
+    DeviceMemory, Extent2D, Extent3D, Format, Image, ImageAspectFlags, ImageCreateInfo,
+    ImageLayout, ImageSubresourceRange, ImageTiling, ImageType, ImageUsageFlags, ImageView,
+    ImageViewCreateInfo, ImageViewType, MemoryAllocateInfo, MemoryPropertyFlags, SampleCountFlags,
+    SharingMode,
+};
+
+pub const DEPTH_FORMAT: Format = Format::D32_SFLOAT;
+
+pub struct DepthBuffer {
+    pub image: Image,
+    pub memory: DeviceMemory,
+    pub view: ImageView,
+}
+
+impl DepthBuffer {
+    pub fn new(instance: &Instance, device: &Device, extent: Extent2D) -> Self {
+        let image_create_info = ImageCreateInfo::default()
+            .image_type(ImageType::TYPE_2D)
+            .extent(Extent3D {
+                width: extent.width,
+                height: extent.height,
+                depth: 1,
+            })
+            .mip_levels(1)
+            .array_layers(1)
+            .format(DEPTH_FORMAT)
+            .tiling(ImageTiling::OPTIMAL)
+            .initial_layout(ImageLayout::UNDEFINED)
+            .usage(ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT)
+            .sharing_mode(SharingMode::EXCLUSIVE)
+            .samples(SampleCountFlags::TYPE_1);
+        let image = unsafe { device.logical.create_image(&image_create_info, None) }
+            .expect("Could not create depth image");
+        let mem_requirements = unsafe { device.logical.get_image_memory_requirements(image) };
+        let memory_type_index = Buffers::find_memory_type_index(
+            &device.physical,
+            instance,
+            mem_requirements.memory_type_bits,
+            MemoryPropertyFlags::DEVICE_LOCAL,
+        );
+        let memory_allocate_info = MemoryAllocateInfo::default()
+            .memory_type_index(memory_type_index)
+            .allocation_size(mem_requirements.size);
+        let memory = unsafe { device.logical.allocate_memory(&memory_allocate_info, None) }
+            .expect("Could not allocate memory for depth image");
+        unsafe { device.logical.bind_image_memory(image, memory, 0) }
+            .expect("Could not bind depth image memory");
+        let subresource_range = ImageSubresourceRange::default()
+            .aspect_mask(ImageAspectFlags::DEPTH)
+            .base_mip_level(0)
+            .level_count(1)
+            .base_array_layer(0)
+            .layer_count(1);
+        let view_create_info = ImageViewCreateInfo::default()
+            .subresource_range(subresource_range)
+            .image(image)
+            .view_type(ImageViewType::TYPE_2D)
+            .format(DEPTH_FORMAT);
+        let view = unsafe { device.logical.create_image_view(&view_create_info, None) }
+            .expect("Could not create depth image view");
+        Self {
+            image,
+            memory,
+            view,
+        }
+    }
+
+    pub fn cleanup(&self, logical_device: &ash::Device) {
+        unsafe {
+            logical_device.destroy_image_view(self.view, None);
+            logical_device.destroy_image(self.image, None);
+            logical_device.free_memory(self.memory, None);
+        }
+    }
+}
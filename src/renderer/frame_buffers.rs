@@ -1,9 +1,10 @@
 use crate::renderer::swapchain::Swapchain;
 use ash::Device;
-use ash::vk::{Framebuffer, FramebufferCreateInfo, RenderPass};
+use ash::vk::{Framebuffer, FramebufferCreateInfo, ImageView, RenderPass};
 
 pub fn create_frame_buffers(
     swapchain: &Swapchain,
+    depth_view: ImageView,
     render_pass: RenderPass,
     logical_device: &Device,
 ) -> Vec<Framebuffer> {
@@ -11,7 +12,7 @@ pub fn create_frame_buffers(
         .image_views
         .iter()
         .map(|&image_view| {
-            let image_view_array = [image_view];
+            let image_view_array = [image_view, depth_view];
             let frame_buffer_create_info = FramebufferCreateInfo::default()
                 .render_pass(render_pass)
                 .attachments(&image_view_array)
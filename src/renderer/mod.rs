@@ -1,6 +1,7 @@
 mod buffers;
 mod command_buffers;
 mod command_pools;
+mod depth;
 mod descriptor;
 mod device;
 mod frame_buffers;
@@ -13,11 +14,14 @@ mod vertex;
 
 use crate::renderer::buffers::Buffers;
 use crate::renderer::command_pools::CommandPools;
+use crate::renderer::depth::DepthBuffer;
 use crate::renderer::descriptor::Descriptor;
 use crate::renderer::swapchain::*;
 use crate::renderer::vertex::*;
+use crate::world::mesher::ChunkMesh;
 use ash::Entry;
 use ash::vk::*;
+use glam::IVec3;
 use std::ffi::c_void;
 use std::fs::File;
 use std::ptr;
@@ -28,6 +32,7 @@ pub struct Renderer {
     pub surface: surface::Surface,
     pub device: device::Device,
     pub swapchain: Swapchain,
+    pub depth_buffer: DepthBuffer,
     pub descriptor: Descriptor,
     pub pipeline: pipeline::Pipeline,
     pub command_pools: CommandPools,
@@ -44,6 +49,7 @@ impl Renderer {
         let surface = surface::Surface::new(window, &entry, &instance.handle);
         let device = device::Device::new(&instance.handle, &surface);
         let swapchain = Swapchain::new(&instance.handle, &device, &surface);
+        let depth_buffer = DepthBuffer::new(&instance.handle, &device, swapchain.extent);
         let mut descriptor = Descriptor::new(&device);
         let pipeline = pipeline::Pipeline::new(&device, &swapchain, &descriptor);
         let command_pools = CommandPools::new(&device);
@@ -107,9 +113,13 @@ impl Renderer {
         Buffers::end_command_buffer(&device, command_buffer, command_pools.transfer);
 
         //
-        let frame_buffers =
-            frame_buffers::create_frame_buffers(&swapchain, pipeline.render_pass, &device.logical);
-        let buffers = Buffers::new(&instance.handle, &device, &command_pools.transfer);
+        let frame_buffers = frame_buffers::create_frame_buffers(
+            &swapchain,
+            depth_buffer.view,
+            pipeline.render_pass,
+            &device.logical,
+        );
+        let buffers = Buffers::new(&instance.handle, &device);
         descriptor.create_descriptor_sets(&device, &buffers);
         let command_buffer =
             command_buffers::create_command_buffer(&command_pools.graphics, &device.logical);
@@ -119,6 +129,7 @@ impl Renderer {
             surface,
             device,
             swapchain,
+            depth_buffer,
             descriptor,
             pipeline,
             command_pools,
@@ -138,11 +149,19 @@ impl Renderer {
         }
         .expect("Could not begin recording the command buffer");
 
-        let clear_values = [ClearValue {
-            color: ClearColorValue {
-                float32: [0.1, 0.1, 0.1, 1.0],
+        let clear_values = [
+            ClearValue {
+                color: ClearColorValue {
+                    float32: [0.1, 0.1, 0.1, 1.0],
+                },
+            },
+            ClearValue {
+                depth_stencil: ClearDepthStencilValue {
+                    depth: 1.0,
+                    stencil: 0,
+                },
             },
-        }];
+        ];
         let render_pass_begin_info = RenderPassBeginInfo::default()
             .render_pass(self.pipeline.render_pass)
             .clear_values(&clear_values)
@@ -151,8 +170,6 @@ impl Renderer {
                 offset: Offset2D { x: 0, y: 0 },
                 extent: self.swapchain.extent,
             });
-        let buffers = &[self.buffers.combined_buffer];
-        let vertex_offsets = &[0];
 
         unsafe {
             self.device.logical.cmd_begin_render_pass(
@@ -160,30 +177,13 @@ impl Renderer {
                 &render_pass_begin_info,
                 SubpassContents::INLINE,
             );
-            self.device.logical.cmd_bind_pipeline(
-                self.command_buffer,
-                PipelineBindPoint::GRAPHICS,
-                self.pipeline.handle,
-            );
-            self.device.logical.cmd_bind_vertex_buffers(
-                self.command_buffer,
-                0,
-                buffers,
-                vertex_offsets,
-            );
-            self.device.logical.cmd_bind_index_buffer(
-                self.command_buffer,
-                self.buffers.combined_buffer,
-                self.buffers.combined_buffer_indices_offset as DeviceSize,
-                IndexType::UINT16,
-            )
         }
 
         let viewport = Viewport::default()
             .x(0.0)
             .y(0.0)
             .min_depth(0.0)
-            .max_depth(0.0)
+            .max_depth(1.0)
             .width(self.swapchain.extent.width as f32)
             .height(self.swapchain.extent.height as f32);
 
@@ -195,7 +195,13 @@ impl Renderer {
         let scissors = [scissor];
 
         let descriptor_sets = [self.descriptor.sets.as_ref().unwrap()[image_index]];
+        let vertex_offsets = &[0];
         unsafe {
+            self.device.logical.cmd_bind_pipeline(
+                self.command_buffer,
+                PipelineBindPoint::GRAPHICS,
+                self.pipeline.handle,
+            );
             self.device
                 .logical
                 .cmd_set_viewport(self.command_buffer, 0, &viewports);
@@ -210,14 +216,48 @@ impl Renderer {
                 &descriptor_sets,
                 &[],
             );
-            self.device.logical.cmd_draw_indexed(
-                self.command_buffer,
-                get_indices().len() as u32,
-                1,
-                0,
-                0,
-                0,
-            );
+            for mesh in self.buffers.chunk_meshes.values() {
+                self.device.logical.cmd_bind_vertex_buffers(
+                    self.command_buffer,
+                    0,
+                    &[mesh.buffer],
+                    vertex_offsets,
+                );
+                self.device.logical.cmd_bind_index_buffer(
+                    self.command_buffer,
+                    mesh.buffer,
+                    mesh.indices_offset as DeviceSize,
+                    IndexType::UINT32,
+                );
+                self.device.logical.cmd_draw_indexed(
+                    self.command_buffer,
+                    mesh.index_count,
+                    1,
+                    0,
+                    0,
+                    0,
+                );
+            }
+            if self.buffers.outline_vertex_count > 0 {
+                self.device.logical.cmd_bind_pipeline(
+                    self.command_buffer,
+                    PipelineBindPoint::GRAPHICS,
+                    self.pipeline.outline_handle,
+                );
+                self.device.logical.cmd_bind_vertex_buffers(
+                    self.command_buffer,
+                    0,
+                    &[self.buffers.outline_buffer],
+                    vertex_offsets,
+                );
+                self.device.logical.cmd_draw(
+                    self.command_buffer,
+                    self.buffers.outline_vertex_count,
+                    1,
+                    0,
+                    0,
+                );
+            }
             self.device.logical.cmd_end_render_pass(self.command_buffer);
             self.device
                 .logical
@@ -226,6 +266,30 @@ impl Renderer {
         }
     }
 
+    //Replaces the GPU mesh of one chunk. Must be called while no frame that uses the previous
+    //mesh is in flight.
+    pub fn upload_chunk_mesh(&mut self, chunk_pos: IVec3, mesh: &ChunkMesh) {
+        let vertices: Vec<Vertex> = mesh.vertices.iter().map(Vertex::from_mesh_vertex).collect();
+        self.buffers.upload_chunk_mesh(
+            &self.device,
+            &self.instance.handle,
+            &self.command_pools.transfer,
+            chunk_pos,
+            &vertices,
+            &mesh.indices,
+        );
+    }
+
+    //Frees the GPU meshes of the chunks `keep` returns false for, under the same condition as
+    //upload_chunk_mesh.
+    pub fn retain_chunk_meshes(&mut self, keep: impl FnMut(&IVec3) -> bool) {
+        self.buffers.retain_chunk_meshes(&self.device, keep);
+    }
+
+    pub fn set_outline(&mut self, block: Option<IVec3>) {
+        self.buffers.update_outline(block);
+    }
+
     pub fn recreate_swap_chain(&mut self) {
         unsafe {
             self.device
@@ -237,9 +301,13 @@ impl Renderer {
             }
         }
         self.swapchain.cleanup(&self.device.logical);
+        self.depth_buffer.cleanup(&self.device.logical);
         self.swapchain = Swapchain::new(&self.instance.handle, &self.device, &self.surface);
+        self.depth_buffer =
+            DepthBuffer::new(&self.instance.handle, &self.device, self.swapchain.extent);
         self.frame_buffers = frame_buffers::create_frame_buffers(
             &self.swapchain,
+            self.depth_buffer.view,
             self.pipeline.render_pass,
             &self.device.logical,
         );
@@ -256,6 +324,7 @@ impl Renderer {
             }
         }
         self.swapchain.cleanup(&self.device.logical);
+        self.depth_buffer.cleanup(&self.device.logical);
         self.descriptor.cleanup(&self.device.logical);
         self.buffers.cleanup(&self.device.logical);
         self.pipeline.cleanup(&self.device.logical);
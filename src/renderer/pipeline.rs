@@ -1,18 +1,20 @@
+use crate::renderer::depth::DEPTH_FORMAT;
 use crate::renderer::descriptor::Descriptor;
 use crate::renderer::device::Device;
 use crate::renderer::swapchain::Swapchain;
 use crate::renderer::vertex::Vertex;
 use ash::vk::{
     AccessFlags, AttachmentDescription, AttachmentLoadOp, AttachmentReference, AttachmentStoreOp,
-    ColorComponentFlags, CullModeFlags, DynamicState, FrontFace, GraphicsPipelineCreateInfo,
-    ImageLayout, PipelineBindPoint, PipelineCache, PipelineColorBlendAttachmentState,
-    PipelineColorBlendStateCreateInfo, PipelineDepthStencilStateCreateInfo,
-    PipelineDynamicStateCreateInfo, PipelineInputAssemblyStateCreateInfo, PipelineLayout,
-    PipelineLayoutCreateInfo, PipelineMultisampleStateCreateInfo,
-    PipelineRasterizationStateCreateInfo, PipelineShaderStageCreateInfo, PipelineStageFlags,
-    PipelineVertexInputStateCreateInfo, PipelineViewportStateCreateInfo, PolygonMode,
-    PrimitiveTopology, RenderPass, RenderPassCreateInfo, SUBPASS_EXTERNAL, SampleCountFlags,
-    ShaderModule, ShaderModuleCreateInfo, ShaderStageFlags, SubpassDependency, SubpassDescription,
+    ColorComponentFlags, CompareOp, CullModeFlags, DynamicState, FrontFace,
+    GraphicsPipelineCreateInfo, ImageLayout, PipelineBindPoint, PipelineCache,
+    PipelineColorBlendAttachmentState, PipelineColorBlendStateCreateInfo,
+    PipelineDepthStencilStateCreateInfo, PipelineDynamicStateCreateInfo,
+    PipelineInputAssemblyStateCreateInfo, PipelineLayout, PipelineLayoutCreateInfo,
+    PipelineMultisampleStateCreateInfo, PipelineRasterizationStateCreateInfo,
+    PipelineShaderStageCreateInfo, PipelineStageFlags, PipelineVertexInputStateCreateInfo,
+    PipelineViewportStateCreateInfo, PolygonMode, PrimitiveTopology, RenderPass,
+    RenderPassCreateInfo, SUBPASS_EXTERNAL, SampleCountFlags, ShaderModule, ShaderModuleCreateInfo,
+    ShaderStageFlags, SubpassDependency, SubpassDescription,
 };
 use std::ffi::CString;
 use vk_shader_macros::include_glsl;
@@ -22,6 +24,7 @@ const FRAG: &[u32] = include_glsl!("resources/shaders/shader.frag");
 
 pub struct Pipeline {
     pub handle: ash::vk::Pipeline,
+    pub outline_handle: ash::vk::Pipeline,
     pub layout: PipelineLayout,
     pub render_pass: RenderPass,
 }
@@ -31,57 +34,6 @@ impl Pipeline {
         let vert_module = Self::create_shader_module(device, VERT);
         let frag_module = Self::create_shader_module(device, FRAG);
 
-        let name = CString::new("main").expect("Could not convert to CStr");
-        let vertex_info = PipelineShaderStageCreateInfo::default()
-            .stage(ShaderStageFlags::VERTEX)
-            .module(vert_module)
-            .name(&name);
-        let frag_info = PipelineShaderStageCreateInfo::default()
-            .stage(ShaderStageFlags::FRAGMENT)
-            .module(frag_module)
-            .name(&name);
-        let stages = [vertex_info, frag_info];
-
-        let dynamic_states = [DynamicState::VIEWPORT, DynamicState::SCISSOR];
-        let dynamic_state_create_info =
-            PipelineDynamicStateCreateInfo::default().dynamic_states(&dynamic_states);
-
-        let binding_descriptions = Vertex::get_binding_descriptions();
-        let binding_attributes = Vertex::get_attribute_descriptions();
-        let vertex_input_create_info = PipelineVertexInputStateCreateInfo::default()
-            .vertex_binding_descriptions(&binding_descriptions)
-            .vertex_attribute_descriptions(&binding_attributes);
-        let input_assembly_state_create_info = PipelineInputAssemblyStateCreateInfo::default()
-            .primitive_restart_enable(false)
-            .topology(PrimitiveTopology::TRIANGLE_LIST);
-        let pipeline_viewport_state_create_info = PipelineViewportStateCreateInfo::default()
-            .viewport_count(1)
-            .scissor_count(1);
-        let pipeline_rasterization_state_create_info =
-            PipelineRasterizationStateCreateInfo::default()
-                .depth_clamp_enable(false)
-                .rasterizer_discard_enable(false)
-                .polygon_mode(PolygonMode::FILL)
-                .line_width(1.0)
-                .cull_mode(CullModeFlags::BACK)
-                .front_face(FrontFace::CLOCKWISE)
-                .depth_bias_enable(false);
-
-        let pipeline_multisample_state_create_info = PipelineMultisampleStateCreateInfo::default()
-            .sample_shading_enable(false)
-            .rasterization_samples(SampleCountFlags::TYPE_1);
-
-        let pipeline_stencil_state_create_info = PipelineDepthStencilStateCreateInfo::default();
-
-        let pipeline_color_blend_attachment_state = PipelineColorBlendAttachmentState::default()
-            .color_write_mask(ColorComponentFlags::RGBA)
-            .blend_enable(false);
-
-        let attachments = [pipeline_color_blend_attachment_state];
-        let pipeline_color_blend_state_create_info = PipelineColorBlendStateCreateInfo::default()
-            .logic_op_enable(false)
-            .attachments(&attachments);
-
         let descriptor_set_layouts = &[descriptor.layout];
         let pipeline_layout_create_info =
             PipelineLayoutCreateInfo::default().set_layouts(descriptor_set_layouts);
@@ -103,29 +55,50 @@ impl Pipeline {
             .stencil_store_op(AttachmentStoreOp::DONT_CARE)
             .initial_layout(ImageLayout::UNDEFINED)
             .final_layout(ImageLayout::PRESENT_SRC_KHR);
+        let depth_attachment = AttachmentDescription::default()
+            .samples(SampleCountFlags::TYPE_1)
+            .format(DEPTH_FORMAT)
+            .load_op(AttachmentLoadOp::CLEAR)
+            .store_op(AttachmentStoreOp::DONT_CARE)
+            .stencil_load_op(AttachmentLoadOp::DONT_CARE)
+            .stencil_store_op(AttachmentStoreOp::DONT_CARE)
+            .initial_layout(ImageLayout::UNDEFINED)
+            .final_layout(ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL);
 
         let color_attachment_ref = AttachmentReference::default()
             .attachment(0)
             .layout(ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
+        let depth_attachment_ref = AttachmentReference::default()
+            .attachment(1)
+            .layout(ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL);
 
-        let color_attachments = [color_attachment];
+        let attachments = [color_attachment, depth_attachment];
         let color_attachment_refs = [color_attachment_ref];
 
         let sub_pass_description = SubpassDescription::default()
             .pipeline_bind_point(PipelineBindPoint::GRAPHICS)
-            .color_attachments(&color_attachment_refs);
+            .color_attachments(&color_attachment_refs)
+            .depth_stencil_attachment(&depth_attachment_ref);
         let sub_pass_descriptions = [sub_pass_description];
 
         let dependencies = [SubpassDependency::default()
             .src_subpass(SUBPASS_EXTERNAL)
             .dst_subpass(0)
-            .src_stage_mask(PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
+            .src_stage_mask(
+                PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+                    | PipelineStageFlags::EARLY_FRAGMENT_TESTS,
+            )
             .src_access_mask(AccessFlags::NONE)
-            .dst_stage_mask(PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
-            .dst_access_mask(AccessFlags::COLOR_ATTACHMENT_WRITE)];
+            .dst_stage_mask(
+                PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+                    | PipelineStageFlags::EARLY_FRAGMENT_TESTS,
+            )
+            .dst_access_mask(
+                AccessFlags::COLOR_ATTACHMENT_WRITE | AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+            )];
 
         let render_pass_create_info = RenderPassCreateInfo::default()
-            .attachments(&color_attachments)
+            .attachments(&attachments)
             .subpasses(&sub_pass_descriptions)
             .dependencies(&dependencies);
 
@@ -136,6 +109,98 @@ impl Pipeline {
                 .expect("Could not create render pass")
         };
         //
+        let handle = Self::create_graphics_pipeline(
+            device,
+            layout,
+            render_pass,
+            [vert_module, frag_module],
+            PrimitiveTopology::TRIANGLE_LIST,
+        );
+        let outline_handle = Self::create_graphics_pipeline(
+            device,
+            layout,
+            render_pass,
+            [vert_module, frag_module],
+            PrimitiveTopology::LINE_LIST,
+        );
+
+        unsafe {
+            device.logical.destroy_shader_module(vert_module, None);
+            device.logical.destroy_shader_module(frag_module, None)
+        };
+
+        Self {
+            handle,
+            outline_handle,
+            layout,
+            render_pass,
+        }
+    }
+
+    fn create_graphics_pipeline(
+        device: &Device,
+        layout: PipelineLayout,
+        render_pass: RenderPass,
+        [vert_module, frag_module]: [ShaderModule; 2],
+        topology: PrimitiveTopology,
+    ) -> ash::vk::Pipeline {
+        let name = CString::new("main").expect("Could not convert to CStr");
+        let vertex_info = PipelineShaderStageCreateInfo::default()
+            .stage(ShaderStageFlags::VERTEX)
+            .module(vert_module)
+            .name(&name);
+        let frag_info = PipelineShaderStageCreateInfo::default()
+            .stage(ShaderStageFlags::FRAGMENT)
+            .module(frag_module)
+            .name(&name);
+        let stages = [vertex_info, frag_info];
+
+        let dynamic_states = [DynamicState::VIEWPORT, DynamicState::SCISSOR];
+        let dynamic_state_create_info =
+            PipelineDynamicStateCreateInfo::default().dynamic_states(&dynamic_states);
+
+        let binding_descriptions = Vertex::get_binding_descriptions();
+        let binding_attributes = Vertex::get_attribute_descriptions();
+        let vertex_input_create_info = PipelineVertexInputStateCreateInfo::default()
+            .vertex_binding_descriptions(&binding_descriptions)
+            .vertex_attribute_descriptions(&binding_attributes);
+        let input_assembly_state_create_info = PipelineInputAssemblyStateCreateInfo::default()
+            .primitive_restart_enable(false)
+            .topology(topology);
+        let pipeline_viewport_state_create_info = PipelineViewportStateCreateInfo::default()
+            .viewport_count(1)
+            .scissor_count(1);
+        //Meshes are wound counter-clockwise as seen from outside, see mesher::ChunkMesh.
+        let pipeline_rasterization_state_create_info =
+            PipelineRasterizationStateCreateInfo::default()
+                .depth_clamp_enable(false)
+                .rasterizer_discard_enable(false)
+                .polygon_mode(PolygonMode::FILL)
+                .line_width(1.0)
+                .cull_mode(CullModeFlags::BACK)
+                .front_face(FrontFace::COUNTER_CLOCKWISE)
+                .depth_bias_enable(false);
+
+        let pipeline_multisample_state_create_info = PipelineMultisampleStateCreateInfo::default()
+            .sample_shading_enable(false)
+            .rasterization_samples(SampleCountFlags::TYPE_1);
+
+        let pipeline_stencil_state_create_info = PipelineDepthStencilStateCreateInfo::default()
+            .depth_test_enable(true)
+            .depth_write_enable(true)
+            .depth_compare_op(CompareOp::LESS)
+            .depth_bounds_test_enable(false)
+            .stencil_test_enable(false);
+
+        let pipeline_color_blend_attachment_state = PipelineColorBlendAttachmentState::default()
+            .color_write_mask(ColorComponentFlags::RGBA)
+            .blend_enable(false);
+
+        let attachments = [pipeline_color_blend_attachment_state];
+        let pipeline_color_blend_state_create_info = PipelineColorBlendStateCreateInfo::default()
+            .logic_op_enable(false)
+            .attachments(&attachments);
+
         let graphics_pipeline_create_info = GraphicsPipelineCreateInfo::default()
             .stages(&stages)
             .vertex_input_state(&vertex_input_create_info)
@@ -150,7 +215,7 @@ impl Pipeline {
             .render_pass(render_pass)
             .subpass(0);
         let graphics_pipeline_create_infos = [graphics_pipeline_create_info];
-        let handle = unsafe {
+        let pipelines = unsafe {
             device
                 .logical
                 .create_graphics_pipelines(
@@ -159,19 +224,10 @@ impl Pipeline {
                     None,
                 )
                 .expect("Could not create graphics pipeline")
-        }[0];
-
-        unsafe {
-            device.logical.destroy_shader_module(vert_module, None);
-            device.logical.destroy_shader_module(frag_module, None)
         };
-
-        Self {
-            handle,
-            layout,
-            render_pass,
-        }
+        pipelines[0]
     }
+
     fn create_shader_module(device: &Device, code: &[u32]) -> ShaderModule {
         let shader_module_create_info = ShaderModuleCreateInfo::default().code(code);
         unsafe {
@@ -185,6 +241,7 @@ impl Pipeline {
     pub fn cleanup(&self, logical_device: &ash::Device) {
         unsafe {
             logical_device.destroy_pipeline(self.handle, None);
+            logical_device.destroy_pipeline(self.outline_handle, None);
             logical_device.destroy_pipeline_layout(self.layout, None);
             logical_device.destroy_render_pass(self.render_pass, None);
         }
@@ -1,37 +1,46 @@
+use crate::world::mesher::MeshVertex;
 use ash::vk::{
     Format, VertexInputAttributeDescription, VertexInputBindingDescription, VertexInputRate,
 };
+use glam::Vec3;
+
 #[allow(dead_code)]
+#[repr(C)]
 pub struct Vertex {
-    pos: (f32, f32),
+    pos: (f32, f32, f32),
     color: (f32, f32, f32),
-}
-
-pub fn get_vertices() -> Vec<Vertex> {
-    vec![
-        Vertex {
-            pos: (-0.5, -0.5),
-            color: (1.0, 0.0, 0.0),
-        },
-        Vertex {
-            pos: (0.5, -0.5),
-            color: (0.0, 1.0, 0.0),
-        },
-        Vertex {
-            pos: (0.5, 0.5),
-            color: (1.0, 0.0, 0.0),
-        },
-        Vertex {
-            pos: (-0.5, 0.5),
-            color: (0.0, 0.0, 1.0),
-        },
-    ]
-}
-pub fn get_indices() -> Vec<u16> {
-    vec![0, 1, 2, 2, 3, 0]
+    //Red, green and blue block light and sky light from 0 to 255, read as normalized floats. The
+    //fragment shader turns them into brightness.
+    light: [u8; 4],
+    //Ambient occlusion from 0 (fully occluded) to 3 (open).
+    ao: u32,
 }
 
 impl Vertex {
+    //Fully lit, for geometry that is not part of the world.
+    pub fn new(pos: Vec3, color: Vec3) -> Self {
+        Self {
+            pos: pos.into(),
+            color: color.into(),
+            light: [u8::MAX; 4],
+            ao: 3,
+        }
+    }
+
+    pub fn from_mesh_vertex(vertex: &MeshVertex) -> Self {
+        let to_byte = |level: f32| (level * u8::MAX as f32).round() as u8;
+        Self {
+            light: [
+                to_byte(vertex.block_light.x),
+                to_byte(vertex.block_light.y),
+                to_byte(vertex.block_light.z),
+                to_byte(vertex.sky_light),
+            ],
+            ao: vertex.ao as u32,
+            ..Self::new(vertex.position, vertex.color * face_shade(vertex.normal))
+        }
+    }
+
     pub fn get_binding_descriptions() -> [VertexInputBindingDescription; 1] {
         [VertexInputBindingDescription::default()
             .binding(0)
@@ -39,18 +48,41 @@ impl Vertex {
             .input_rate(VertexInputRate::VERTEX)]
     }
 
-    pub fn get_attribute_descriptions() -> [VertexInputAttributeDescription; 2] {
+    pub fn get_attribute_descriptions() -> [VertexInputAttributeDescription; 4] {
         [
             VertexInputAttributeDescription::default()
                 .binding(0)
                 .location(0)
-                .format(Format::R32G32_SFLOAT)
+                .format(Format::R32G32B32_SFLOAT)
                 .offset(0),
             VertexInputAttributeDescription::default()
                 .binding(0)
                 .location(1)
                 .format(Format::R32G32B32_SFLOAT)
-                .offset(8),
+                .offset(12),
+            VertexInputAttributeDescription::default()
+                .binding(0)
+                .location(2)
+                .format(Format::R8G8B8A8_UNORM)
+                .offset(24),
+            VertexInputAttributeDescription::default()
+                .binding(0)
+                .location(3)
+                .format(Format::R32_UINT)
+                .offset(28),
         ]
     }
 }
+
+//Fixed per-face brightness so neighbouring faces can be told apart without lighting.
+fn face_shade(normal: Vec3) -> f32 {
+    if normal.z > 0.0 {
+        1.0
+    } else if normal.z < 0.0 {
+        0.5
+    } else if normal.x != 0.0 {
+        0.8
+    } else {
+        0.65
+    }
+}
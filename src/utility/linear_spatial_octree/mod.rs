@@ -0,0 +1,185 @@
+mod tests;
+
+use crate::utility::SpatialOctree;
+use crate::utility::sparse_spatial_octree::{ROOT_CENTER, RadiusError, root_half_extent};
+use glam::{IVec3, vec3};
+use std::ops::Add;
+
+//Linear counterpart of SparseSpatialOctree. Leaves are stored as Morton (Z-order) codes in a
+//sorted array, so siblings are adjacent in memory and there is no per-node allocation.
+pub struct LinearSpatialOctree {
+    codes: Vec<u64>,
+    pub center: IVec3,
+    radius_sqr: f32,
+    half_extent: i32,
+}
+
+impl SpatialOctree for LinearSpatialOctree {
+    fn new(center: IVec3, radius: i32) -> Result<Self, RadiusError> {
+        let half_extent = root_half_extent(radius)?;
+        let radius_sqr = (radius * radius) as f32;
+        Ok(Self {
+            codes: Vec::new(),
+            center,
+            radius_sqr,
+            half_extent,
+        })
+    }
+
+    fn is_in_sphere(&self, pos: &IVec3) -> bool {
+        pos.as_vec3().add(vec3(-0.5, -0.5, -0.5)).length_squared() <= self.radius_sqr
+    }
+
+    fn add(&mut self, position: IVec3, is_local: bool) {
+        let position = if is_local {
+            position
+        } else {
+            position - self.center
+        };
+        if !self.is_in_sphere(&position) {
+            return;
+        }
+        let code = self.encode(position);
+        if let Err(index) = self.codes.binary_search(&code) {
+            self.codes.insert(index, code);
+        }
+    }
+
+    fn remove(&mut self, position: IVec3) {
+        let local_position = position - self.center;
+        if !self.is_in_sphere(&local_position) {
+            return;
+        }
+        let code = self.encode(local_position);
+        if let Ok(index) = self.codes.binary_search(&code) {
+            self.codes.remove(index);
+        }
+    }
+
+    fn exists(&self, position: IVec3) -> bool {
+        let local_position = position - self.center;
+        if !self.is_in_sphere(&local_position) {
+            return false;
+        }
+        self.codes
+            .binary_search(&self.encode(local_position))
+            .is_ok()
+    }
+
+    fn query_range(&self, min: IVec3, max: IVec3) -> Vec<IVec3> {
+        let mut result = Vec::new();
+        if min.cmpge(max).any() {
+            return result;
+        }
+        let local_min = min.saturating_sub(self.center);
+        let local_max = (max - IVec3::ONE).saturating_sub(self.center);
+        //Morton order is monotonic on every axis, so every code inside the box lies between the
+        //codes of its two extreme corners.
+        let min_code = self.encode(local_min);
+        let max_code = self.encode(local_max);
+        let mut index = self.codes.partition_point(|code| *code < min_code);
+        let end = self.codes.partition_point(|code| *code <= max_code);
+        while index < end {
+            let code = self.codes[index];
+            let position = self.decode(code) + self.center;
+            if position.cmpge(min).all() && position.cmplt(max).all() {
+                result.push(position);
+                index += 1;
+            } else {
+                let next = Self::next_code_in_box(code, min_code, max_code);
+                index += self.codes[index..end].partition_point(|code| *code < next);
+            }
+        }
+        result
+    }
+}
+
+impl LinearSpatialOctree {
+    pub fn len(&self) -> usize {
+        self.codes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.codes.is_empty()
+    }
+
+    //Bulk insertion, appends everything and sorts once instead of shifting the array per entry.
+    pub fn extend(&mut self, positions: impl IntoIterator<Item = IVec3>, is_local: bool) {
+        for position in positions {
+            let position = if is_local {
+                position
+            } else {
+                position - self.center
+            };
+            if self.is_in_sphere(&position) {
+                self.codes.push(self.encode(position));
+            }
+        }
+        self.codes.sort_unstable();
+        self.codes.dedup();
+    }
+
+    //Positions outside the root extent only come from range query corners and are clamped onto
+    //its boundary cells.
+    fn encode(&self, local_position: IVec3) -> u64 {
+        let unsigned = local_position
+            .saturating_sub(ROOT_CENTER)
+            .saturating_add(IVec3::splat(self.half_extent))
+            .clamp(IVec3::ZERO, IVec3::splat(self.half_extent * 2 - 1));
+        Self::spread_bits(unsigned.x as u64)
+            | Self::spread_bits(unsigned.y as u64) << 1
+            | Self::spread_bits(unsigned.z as u64) << 2
+    }
+
+    fn decode(&self, code: u64) -> IVec3 {
+        IVec3::new(
+            Self::compact_bits(code) as i32,
+            Self::compact_bits(code >> 1) as i32,
+            Self::compact_bits(code >> 2) as i32,
+        ) + ROOT_CENTER
+            - self.half_extent
+    }
+
+    //BIGMIN from Tropf and Herzog: the smallest code greater than `code` that lies inside the box
+    //spanned by `min_code` and `max_code`.
+    fn next_code_in_box(code: u64, mut min_code: u64, mut max_code: u64) -> u64 {
+        let mut next = 0;
+        for bit in (0..63).rev() {
+            let mask = 1u64 << bit;
+            let lower_same_axis = (0x1249_2492_4924_9249u64 << (bit % 3)) & (mask - 1);
+            match (code & mask != 0, min_code & mask != 0, max_code & mask != 0) {
+                (false, false, true) => {
+                    next = (min_code & !lower_same_axis) | mask;
+                    max_code = (max_code & !mask) | lower_same_axis;
+                }
+                (false, true, true) => return min_code,
+                (true, false, false) => return next,
+                (true, false, true) => {
+                    min_code = (min_code & !lower_same_axis) | mask;
+                }
+                _ => {}
+            }
+        }
+        next
+    }
+
+    fn spread_bits(value: u64) -> u64 {
+        let mut x = value & 0x1f_ffff;
+        x = (x | x << 32) & 0x001f_0000_0000_ffff;
+        x = (x | x << 16) & 0x001f_0000_ff00_00ff;
+        x = (x | x << 8) & 0x100f_00f0_0f00_f00f;
+        x = (x | x << 4) & 0x10c3_0c30_c30c_30c3;
+        x = (x | x << 2) & 0x1249_2492_4924_9249;
+        x
+    }
+
+    fn compact_bits(value: u64) -> u64 {
+        let mut x = value & 0x1249_2492_4924_9249;
+        x = (x | x >> 2) & 0x10c3_0c30_c30c_30c3;
+        x = (x | x >> 4) & 0x100f_00f0_0f00_f00f;
+        x = (x | x >> 8) & 0x001f_0000_ff00_00ff;
+        x = (x | x >> 16) & 0x001f_0000_0000_ffff;
+        x = (x | x >> 32) & 0x1f_ffff;
+        x
+    }
+}
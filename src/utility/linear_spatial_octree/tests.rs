@@ -0,0 +1,234 @@
+#[cfg(test)]
+mod tests {
+    use crate::utility::SpatialOctree;
+    use crate::utility::linear_spatial_octree::LinearSpatialOctree;
+    use crate::utility::sparse_spatial_octree::SparseSpatialOctree;
+    use glam::IVec3;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::time::{Duration, Instant};
+
+    fn create_test_octree() -> LinearSpatialOctree {
+        LinearSpatialOctree::new(IVec3::new(0, 0, 0), 8).unwrap()
+    }
+
+    fn random_positions(radius: i32, count: usize, seed: u64) -> Vec<IVec3> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..count)
+            .map(|_| {
+                IVec3::new(
+                    rng.random_range(-radius + 1..=radius),
+                    rng.random_range(-radius + 1..=radius),
+                    rng.random_range(-radius + 1..=radius),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_new_octree_invalid_capacity() {
+        assert!(LinearSpatialOctree::new(IVec3::ZERO, 0).is_err());
+        assert!(LinearSpatialOctree::new(IVec3::ZERO, 12).is_ok());
+    }
+
+    #[test]
+    fn test_morton_round_trip() {
+        let octree = LinearSpatialOctree::new(IVec3::ZERO, 512).unwrap();
+        for position in random_positions(512, 1000, 1) {
+            assert_eq!(octree.decode(octree.encode(position)), position);
+        }
+    }
+
+    #[test]
+    fn test_morton_child_index_order() {
+        //The lowest three bits of a code must match SparseSpatialOctree::get_child_index.
+        let octree = LinearSpatialOctree::new(IVec3::ZERO, 1).unwrap();
+        let positions = [
+            (IVec3::new(1, 1, 1), 7),
+            (IVec3::new(0, 0, 0), 0),
+            (IVec3::new(1, 0, 1), 5),
+            (IVec3::new(0, 1, 0), 2),
+        ];
+        for (pos, expected_index) in positions {
+            assert_eq!(octree.encode(pos), expected_index);
+        }
+    }
+
+    #[test]
+    fn test_get() {
+        let mut octree = create_test_octree();
+
+        assert!(!octree.exists(IVec3::new(1, 1, 1)));
+
+        octree.add(IVec3::new(1, 1, 1), true);
+        assert!(octree.exists(IVec3::new(1, 1, 1)));
+        assert!(!octree.exists(IVec3::new(0, 0, 0)));
+
+        octree.add(IVec3::new(-1, -1, -1), true);
+        assert!(octree.exists(IVec3::new(-1, -1, -1)));
+
+        octree.remove(IVec3::new(1, 1, 1));
+        assert!(!octree.exists(IVec3::new(1, 1, 1)));
+        assert!(octree.exists(IVec3::new(-1, -1, -1)));
+        assert_eq!(octree.len(), 1);
+    }
+
+    #[test]
+    fn test_add_duplicate() {
+        let mut octree = create_test_octree();
+        octree.add(IVec3::new(2, 2, 2), true);
+        octree.add(IVec3::new(2, 2, 2), true);
+        assert_eq!(octree.len(), 1);
+    }
+
+    #[test]
+    fn test_add_outside_sphere() {
+        let mut octree = create_test_octree();
+        octree.add(IVec3::new(8, 8, 8), true);
+        assert!(octree.is_empty());
+    }
+
+    #[test]
+    fn test_world_positions() {
+        let mut octree = LinearSpatialOctree::new(IVec3::new(100, -50, 3), 8).unwrap();
+        octree.add(IVec3::new(102, -50, 1), false);
+        assert!(octree.exists(IVec3::new(102, -50, 1)));
+        octree.remove(IVec3::new(102, -50, 1));
+        assert!(octree.is_empty());
+    }
+
+    //Makes the same edits through the shared interface.
+    fn build<T: SpatialOctree>(center: IVec3) -> T {
+        let mut octree = T::new(center, 14).unwrap();
+        let positions = random_positions(17, 2000, 2);
+        for position in &positions {
+            octree.add(*position + center, false);
+        }
+        for position in positions.iter().step_by(3) {
+            octree.remove(*position + center);
+        }
+        octree
+    }
+
+    #[test]
+    fn test_matches_sparse_octree() {
+        let center = IVec3::new(5, -3, 12);
+        let linear: LinearSpatialOctree = build(center);
+        let sparse: SparseSpatialOctree = build(center);
+        for position in random_positions(17, 4000, 3) {
+            assert_eq!(
+                linear.exists(position + center),
+                sparse.exists(position + center)
+            );
+        }
+        let min = center + IVec3::new(-5, -9, 0);
+        let max = center + IVec3::new(7, 3, 11);
+        let mut linear_range = linear.query_range(min, max);
+        let mut sparse_range = sparse.query_range(min, max);
+        linear_range.sort_by_key(|p| (p.x, p.y, p.z));
+        sparse_range.sort_by_key(|p| (p.x, p.y, p.z));
+        assert!(!linear_range.is_empty());
+        assert_eq!(linear_range, sparse_range);
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut octree = create_test_octree();
+        let mut expected = create_test_octree();
+        let positions = random_positions(9, 500, 7);
+        octree.extend(positions.iter().copied(), true);
+        for position in &positions {
+            expected.add(*position, true);
+        }
+        assert_eq!(octree.codes, expected.codes);
+    }
+
+    #[test]
+    fn test_query_range_unbounded() {
+        let mut octree = LinearSpatialOctree::new(IVec3::new(-4, 9, 0), 5).unwrap();
+        let positions = random_positions(5, 100, 8);
+        octree.extend(positions.iter().copied(), true);
+        assert_eq!(
+            octree.query_range(IVec3::MIN, IVec3::MAX).len(),
+            octree.len()
+        );
+    }
+
+    #[test]
+    fn test_query_range_empty_box() {
+        let mut octree = create_test_octree();
+        octree.add(IVec3::new(1, 1, 1), true);
+        assert!(
+            octree
+                .query_range(IVec3::new(2, 0, 0), IVec3::new(1, 4, 4))
+                .is_empty()
+        );
+    }
+
+    //Adds the positions one by one and returns the octree with the time it took.
+    fn timed_insert<T: SpatialOctree>(positions: &[IVec3]) -> (T, Duration) {
+        let mut octree = T::new(IVec3::ZERO, 256).unwrap();
+        let start = Instant::now();
+        for position in positions {
+            octree.add(*position, true);
+        }
+        (octree, start.elapsed())
+    }
+
+    fn timed_lookup<T: SpatialOctree>(octree: &T, queries: &[IVec3]) -> (usize, Duration) {
+        let start = Instant::now();
+        let hits = queries
+            .iter()
+            .filter(|query| octree.exists(**query))
+            .count();
+        (hits, start.elapsed())
+    }
+
+    fn timed_range<T: SpatialOctree>(octree: &T, boxes: &[(IVec3, IVec3)]) -> (usize, Duration) {
+        let start = Instant::now();
+        let found = boxes
+            .iter()
+            .map(|(min, max)| octree.query_range(*min, *max).len())
+            .sum();
+        (found, start.elapsed())
+    }
+
+    //Slow in debug builds, run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_sparse_and_linear_at_radius_256() {
+        let positions = random_positions(256, 200_000, 4);
+        let (sparse, sparse_insert) = timed_insert::<SparseSpatialOctree>(&positions);
+        let (linear, linear_insert) = timed_insert::<LinearSpatialOctree>(&positions);
+        let mut bulk = LinearSpatialOctree::new(IVec3::ZERO, 256).unwrap();
+        let start = Instant::now();
+        bulk.extend(positions.iter().copied(), true);
+        let bulk_insert = start.elapsed();
+        assert_eq!(bulk.codes, linear.codes);
+
+        let queries = random_positions(256, 200_000, 5);
+        let (sparse_hits, sparse_lookup) = timed_lookup(&sparse, &queries);
+        let (linear_hits, linear_lookup) = timed_lookup(&linear, &queries);
+        assert_eq!(sparse_hits, linear_hits);
+
+        let boxes: Vec<_> = random_positions(256 - 32, 200, 6)
+            .into_iter()
+            .map(|min| (min, min + IVec3::splat(32)))
+            .collect();
+        let (sparse_found, sparse_range) = timed_range(&sparse, &boxes);
+        let (linear_found, linear_range) = timed_range(&linear, &boxes);
+        assert_eq!(sparse_found, linear_found);
+
+        println!(
+            "insert: sparse {:?}, linear {:?}, bulk {:?}; lookup: sparse {:?}, linear {:?}; \
+             range: sparse {:?}, linear {:?}",
+            sparse_insert,
+            linear_insert,
+            bulk_insert,
+            sparse_lookup,
+            linear_lookup,
+            sparse_range,
+            linear_range
+        );
+    }
+}
@@ -1 +1,19 @@
+pub mod linear_spatial_octree;
 pub mod sparse_spatial_octree;
+
+use crate::utility::sparse_spatial_octree::RadiusError;
+use glam::IVec3;
+
+//Set of cells inside a sphere around `center`, implemented by both octree layouts. Positions are
+//in world coordinates unless a method says otherwise.
+pub trait SpatialOctree: Sized {
+    fn new(center: IVec3, radius: i32) -> Result<Self, RadiusError>;
+    //`pos` is relative to the center.
+    fn is_in_sphere(&self, pos: &IVec3) -> bool;
+    //Cells outside of the sphere are ignored.
+    fn add(&mut self, position: IVec3, is_local: bool);
+    fn remove(&mut self, position: IVec3);
+    fn exists(&self, position: IVec3) -> bool;
+    //Returns the stored world positions inside [min, max).
+    fn query_range(&self, min: IVec3, max: IVec3) -> Vec<IVec3>;
+}
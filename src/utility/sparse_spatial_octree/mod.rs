@@ -1,9 +1,50 @@
+pub(crate) mod serialization;
 mod tests;
 
+use crate::utility::SpatialOctree;
 use glam::{IVec3, vec3};
+use std::fmt::{Display, Formatter};
 use std::ops::Add;
 
-const MAX_RADIUS: i32 = 512;
+pub(crate) const MAX_RADIUS: i32 = 512;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RadiusError {
+    NotPositive(i32),
+    TooLarge(i32),
+}
+
+impl Display for RadiusError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RadiusError::NotPositive(radius) => {
+                write!(f, "Radius must be positive, got {}", radius)
+            }
+            RadiusError::TooLarge(radius) => write!(
+                f,
+                "Cannot create a tree with radius {} larger than {}",
+                radius, MAX_RADIUS
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RadiusError {}
+
+//The sphere test is centered on (0.5, 0.5, 0.5), so the covered cells span [-radius + 1, radius]
+//on every axis. A root centered on (1, 1, 1) with a power of two half extent covers exactly that
+//for power of two radii and rounds the extent up for any other radius.
+pub(crate) const ROOT_CENTER: IVec3 = IVec3::ONE;
+
+pub(crate) fn root_half_extent(radius: i32) -> Result<i32, RadiusError> {
+    if radius <= 0 {
+        return Err(RadiusError::NotPositive(radius));
+    }
+    if radius > MAX_RADIUS {
+        return Err(RadiusError::TooLarge(radius));
+    }
+    Ok((radius as u32).next_power_of_two() as i32)
+}
 
 pub struct SparseSpatialOctreeNode {
     center: IVec3,
@@ -23,75 +64,218 @@ impl SparseSpatialOctreeNode {
     }
 }
 
+pub struct Recentered {
+    pub dropped: Vec<IVec3>,
+    pub covered: Vec<IVec3>,
+}
+
+//Entries are stored at their world positions, so moving the sphere leaves the entries that stay
+//inside it untouched. The root starts out covering the sphere and gains levels above it when an
+//entry lands outside of it.
 pub struct SparseSpatialOctree {
     root: SparseSpatialOctreeNode,
     pub center: IVec3,
     pub radius: i32,
     radius_sqr: f32,
+    allocated_nodes: usize,
 }
 
-impl SparseSpatialOctree {
-    pub fn new(center: IVec3, radius: i32) -> Self {
-        //depth = log2(radius)+1
-        if radius > MAX_RADIUS {
-            panic!(
-                "Cannot create a tree with radius larger than {}",
-                MAX_RADIUS
-            );
-        }
-        if radius <= 0 || (radius & (radius - 1)) > 0 {
-            panic!("Radius must be a multiplier of 2");
-        }
+impl SpatialOctree for SparseSpatialOctree {
+    fn new(center: IVec3, radius: i32) -> Result<Self, RadiusError> {
+        //depth = log2(half_extent)+1
+        let half_extent = root_half_extent(radius)?;
         let radius_sqr = (radius * radius) as f32;
-        let root = SparseSpatialOctreeNode::new(IVec3::ZERO, radius);
-        Self {
+        let root = SparseSpatialOctreeNode::new(center + ROOT_CENTER, half_extent);
+        Ok(Self {
             root,
             center,
             radius,
             radius_sqr,
-        }
+            allocated_nodes: 0,
+        })
     }
 
-    pub fn copy_base(&self, center: IVec3) -> Self {
-        let root = SparseSpatialOctreeNode::new(IVec3::ZERO, self.radius);
-        Self {
-            root,
-            center,
-            radius: self.radius,
-            radius_sqr: self.radius_sqr,
-        }
-    }
-
-    pub fn is_in_sphere(&self, pos: &IVec3) -> bool {
+    fn is_in_sphere(&self, pos: &IVec3) -> bool {
         pos.as_vec3().add(vec3(-0.5, -0.5, -0.5)).length_squared() <= self.radius_sqr
     }
 
-    pub fn add(&mut self, position: IVec3, is_local: bool) {
-        let position = if is_local {
+    fn add(&mut self, position: IVec3, is_local: bool) {
+        let local_position = if is_local {
             position
         } else {
             position - self.center
         };
-        if !self.is_in_sphere(&position) {
+        if !self.is_in_sphere(&local_position) {
             return;
         }
-        Self::add_recursive(&mut self.root, position);
+        let position = local_position + self.center;
+        self.grow_root(position);
+        self.allocated_nodes += Self::add_recursive(&mut self.root, position);
     }
 
-    pub fn remove(&mut self, position: IVec3) {
-        let local_position = position - self.center;
-        if !self.is_in_sphere(&local_position) {
+    fn remove(&mut self, position: IVec3) {
+        if !self.is_in_sphere(&(position - self.center)) || !Self::contains(&self.root, position) {
             return;
         }
-        Self::remove_recursive(&mut self.root, local_position);
+        Self::remove_recursive(&mut self.root, position);
     }
 
-    pub fn exists(&self, position: IVec3) -> bool {
-        let local_position = position - self.center;
-        if !self.is_in_sphere(&local_position) {
+    fn exists(&self, position: IVec3) -> bool {
+        if !self.is_in_sphere(&(position - self.center)) || !Self::contains(&self.root, position) {
             return false;
         }
-        Self::exists_recursive(&self.root, local_position)
+        Self::exists_recursive(&self.root, position)
+    }
+
+    fn query_range(&self, min: IVec3, max: IVec3) -> Vec<IVec3> {
+        let mut result = Vec::new();
+        let node_min = self.root.center - self.root.half_extent;
+        Self::query_range_recursive(&self.root, node_min, min, max, &mut result);
+        result
+    }
+}
+
+impl SparseSpatialOctree {
+    //Moves the sphere to `center`. Only the cells that left the sphere are visited to remove their
+    //entries, the others stay where they are. Returns the world positions of the removed entries
+    //and of the cells that were not covered before.
+    pub fn recenter(&mut self, center: IVec3) -> Recentered {
+        let old_center = self.center;
+        self.center = center;
+        let mut dropped = Vec::new();
+        for position in self.sphere_difference(old_center, center) {
+            if Self::contains(&self.root, position) && Self::exists_recursive(&self.root, position)
+            {
+                Self::remove_recursive(&mut self.root, position);
+                dropped.push(position);
+            }
+        }
+        self.shrink_root();
+        let covered = self.sphere_difference(center, old_center);
+        Recentered { dropped, covered }
+    }
+
+    //World positions of the cells inside the sphere around `center` but not around `other_center`.
+    fn sphere_difference(&self, center: IVec3, other_center: IVec3) -> Vec<IVec3> {
+        let mut cells = Vec::new();
+        let offset = center - other_center;
+        for x in -self.radius..=self.radius {
+            for y in -self.radius..=self.radius {
+                let Some((z_min, z_max)) = self.sphere_z_range(x, y) else {
+                    continue;
+                };
+                let other_range = self.sphere_z_range(x + offset.x, y + offset.y);
+                for z in z_min..=z_max {
+                    let other_z = z + offset.z;
+                    if other_range.is_some_and(|(other_min, other_max)| {
+                        other_z >= other_min && other_z <= other_max
+                    }) {
+                        continue;
+                    }
+                    cells.push(IVec3::new(x, y, z) + center);
+                }
+            }
+        }
+        cells
+    }
+
+    //Inclusive range of local z values inside the sphere for the column at local (x, y).
+    fn sphere_z_range(&self, x: i32, y: i32) -> Option<(i32, i32)> {
+        let remainder = self.radius_sqr - (x as f32 - 0.5).powi(2) - (y as f32 - 0.5).powi(2);
+        if remainder < 0.0 {
+            return None;
+        }
+        let mut z_max = (0.5 + remainder.sqrt()).floor() as i32;
+        while z_max > 0 && !self.is_in_sphere(&IVec3::new(x, y, z_max)) {
+            z_max -= 1;
+        }
+        while self.is_in_sphere(&IVec3::new(x, y, z_max + 1)) {
+            z_max += 1;
+        }
+        if !self.is_in_sphere(&IVec3::new(x, y, z_max)) {
+            return None;
+        }
+        //The sphere is centered on z = 0.5, so the range is mirrored around it.
+        Some((1 - z_max, z_max))
+    }
+
+    //Root covering exactly the sphere around the current center.
+    fn sphere_root(&self) -> SparseSpatialOctreeNode {
+        let half_extent = root_half_extent(self.radius).expect("Radius was checked by new");
+        SparseSpatialOctreeNode::new(self.center + ROOT_CENTER, half_extent)
+    }
+
+    //Adds levels above the root until it covers `position`. The old root becomes a child of the new
+    //one, so none of the entries move.
+    fn grow_root(&mut self, position: IVec3) {
+        if self.root.children.is_none() && !Self::contains(&self.root, position) {
+            self.root = self.sphere_root();
+        }
+        while !Self::contains(&self.root, position) {
+            let half_extent = self.root.half_extent;
+            let below = position.cmplt(self.root.center - half_extent);
+            let center = IVec3::select(
+                below,
+                self.root.center - half_extent,
+                self.root.center + half_extent,
+            );
+            let old_root = std::mem::replace(
+                &mut self.root,
+                SparseSpatialOctreeNode::new(center, half_extent * 2),
+            );
+            let mut children: [Option<Box<SparseSpatialOctreeNode>>; 8] =
+                core::array::from_fn(|_| None);
+            let index = Self::get_child_index(old_root.center, center);
+            children[index] = Some(Box::from(old_root));
+            self.root.children = Some(children);
+            self.root.child_count = 1;
+            self.allocated_nodes += 1;
+        }
+    }
+
+    //Drops the levels above the root that only lead to a single child once entries are removed.
+    fn shrink_root(&mut self) {
+        let sphere_root = self.sphere_root();
+        while self.root.half_extent > sphere_root.half_extent && self.root.child_count == 1 {
+            let children = self.root.children.as_mut().expect("The root has a child");
+            self.root = *children.iter_mut().find_map(Option::take).unwrap();
+        }
+        if self.root.children.is_none() {
+            self.root = sphere_root;
+        }
+    }
+
+    fn contains(node: &SparseSpatialOctreeNode, position: IVec3) -> bool {
+        position.cmpge(node.center - node.half_extent).all()
+            && position.cmplt(node.center + node.half_extent).all()
+    }
+
+    fn query_range_recursive(
+        node: &SparseSpatialOctreeNode,
+        node_min: IVec3,
+        min: IVec3,
+        max: IVec3,
+        result: &mut Vec<IVec3>,
+    ) {
+        if node.half_extent < 1 {
+            if node_min.cmpge(min).all() && node_min.cmplt(max).all() {
+                result.push(node_min);
+            }
+            return;
+        }
+        let node_max = node_min + node.half_extent * 2;
+        if node_max.cmple(min).any() || node_min.cmpge(max).any() {
+            return;
+        }
+        let Some(children) = &node.children else {
+            return;
+        };
+        for (index, child) in children.iter().enumerate() {
+            if let Some(child) = child {
+                let child_min = node_min + Self::child_offset(index) * node.half_extent;
+                Self::query_range_recursive(child, child_min, min, max, result);
+            }
+        }
     }
 
     fn exists_recursive(node: &SparseSpatialOctreeNode, position: IVec3) -> bool {
@@ -133,28 +317,23 @@ impl SparseSpatialOctree {
         false
     }
 
-    fn add_recursive(node: &mut SparseSpatialOctreeNode, position: IVec3) {
+    //Returns the number of nodes created for the new entry.
+    fn add_recursive(node: &mut SparseSpatialOctreeNode, position: IVec3) -> usize {
         if node.half_extent < 1 {
-            return;
+            return 0;
         }
         let index = Self::get_child_index(position, node.center);
-        if let Some(children) = &mut node.children {
-            if let Some(next_node) = &mut children[index] {
-                Self::add_recursive(next_node, position);
-            } else {
-                let mut new_node = Self::create_new_node(index, &node.center, node.half_extent);
-                Self::add_recursive(&mut new_node, position);
-                node.child_count += 1;
-                children[index] = Some(Box::from(new_node));
-            }
+        let children = node
+            .children
+            .get_or_insert_with(|| core::array::from_fn(|_| None));
+        if let Some(next_node) = &mut children[index] {
+            Self::add_recursive(next_node, position)
         } else {
-            let mut children: [Option<Box<SparseSpatialOctreeNode>>; 8] =
-                core::array::from_fn(|_| None);
             let mut new_node = Self::create_new_node(index, &node.center, node.half_extent);
-            Self::add_recursive(&mut new_node, position);
+            let created = Self::add_recursive(&mut new_node, position);
             node.child_count += 1;
             children[index] = Some(Box::from(new_node));
-            node.children = Some(children);
+            created + 1
         }
     }
 
@@ -168,6 +347,14 @@ impl SparseSpatialOctree {
         SparseSpatialOctreeNode::new(center, offset)
     }
 
+    fn child_offset(index: usize) -> IVec3 {
+        IVec3::new(
+            (index & 1) as i32,
+            ((index >> 1) & 1) as i32,
+            ((index >> 2) & 1) as i32,
+        )
+    }
+
     fn get_child_index(pos: IVec3, center: IVec3) -> usize {
         ((pos.x >= center.x) as usize)
             | ((pos.y >= center.y) as usize) << 1
@@ -0,0 +1,231 @@
+use crate::utility::SpatialOctree;
+use crate::utility::sparse_spatial_octree::{
+    RadiusError, SparseSpatialOctree, SparseSpatialOctreeNode,
+};
+use glam::IVec3;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+
+const FORMAT_VERSION: u8 = 1;
+const HEADER_SIZE: usize = 1 + 4 * 4;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DeserializeError {
+    UnsupportedVersion(u8),
+    Truncated,
+    InvalidRadius(RadiusError),
+    EmptyNode,
+    LeafOutsideSphere(IVec3),
+    TrailingBytes(usize),
+}
+
+impl Display for DeserializeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeserializeError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported octree format version {}", version)
+            }
+            DeserializeError::Truncated => write!(f, "Octree data ended unexpectedly"),
+            DeserializeError::InvalidRadius(err) => write!(f, "{}", err),
+            DeserializeError::EmptyNode => write!(f, "Octree data contains an empty inner node"),
+            DeserializeError::LeafOutsideSphere(position) => {
+                write!(
+                    f,
+                    "Octree data has an entry at {} outside of its sphere",
+                    position
+                )
+            }
+            DeserializeError::TrailingBytes(count) => {
+                write!(f, "Octree data has {} trailing bytes", count)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DeserializeError {}
+
+impl From<RadiusError> for DeserializeError {
+    fn from(err: RadiusError) -> Self {
+        DeserializeError::InvalidRadius(err)
+    }
+}
+
+pub struct OctreeDiff {
+    pub added: Vec<IVec3>,
+    pub removed: Vec<IVec3>,
+}
+
+impl SparseSpatialOctree {
+    //Layout: version byte, center and radius as little endian i32s, then one child mask byte per
+    //inner node in depth-first order. Leaves are implied by their parent's mask.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + 1);
+        bytes.push(FORMAT_VERSION);
+        for value in [self.center.x, self.center.y, self.center.z, self.radius] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        let sphere_root = self.sphere_root();
+        if self.root.center == sphere_root.center
+            && self.root.half_extent == sphere_root.half_extent
+        {
+            Self::serialize_recursive(&self.root, &mut bytes);
+        } else {
+            //The root moved or grew while recentering, the data always starts from the root
+            //around the center.
+            let mut root = sphere_root;
+            for position in self.query_range(IVec3::MIN, IVec3::MAX) {
+                Self::add_recursive(&mut root, position);
+            }
+            Self::serialize_recursive(&root, &mut bytes);
+        }
+        bytes
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, DeserializeError> {
+        if bytes.len() < HEADER_SIZE {
+            return Err(DeserializeError::Truncated);
+        }
+        if bytes[0] != FORMAT_VERSION {
+            return Err(DeserializeError::UnsupportedVersion(bytes[0]));
+        }
+        let read_i32 =
+            |offset: usize| i32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+        let center = IVec3::new(read_i32(1), read_i32(5), read_i32(9));
+        let mut octree = SparseSpatialOctree::new(center, read_i32(13))?;
+        let mut root = octree.sphere_root();
+        let mut cursor = HEADER_SIZE;
+        Self::deserialize_recursive(&octree, &mut root, bytes, &mut cursor, true)?;
+        octree.root = root;
+        if cursor != bytes.len() {
+            return Err(DeserializeError::TrailingBytes(bytes.len() - cursor));
+        }
+        Ok(octree)
+    }
+
+    //Positions stored in `other` but not in `self` are added, the reverse are removed.
+    pub fn diff(&self, other: &SparseSpatialOctree) -> OctreeDiff {
+        let mut diff = OctreeDiff {
+            added: Vec::new(),
+            removed: Vec::new(),
+        };
+        if self.root.center == other.root.center && self.root.half_extent == other.root.half_extent
+        {
+            let node_min = self.root.center - self.root.half_extent;
+            Self::diff_recursive(Some(&self.root), Some(&other.root), node_min, &mut diff);
+        } else {
+            let own: HashSet<IVec3> = self
+                .query_range(IVec3::MIN, IVec3::MAX)
+                .into_iter()
+                .collect();
+            let others: HashSet<IVec3> = other
+                .query_range(IVec3::MIN, IVec3::MAX)
+                .into_iter()
+                .collect();
+            diff.added = others.difference(&own).copied().collect();
+            diff.removed = own.difference(&others).copied().collect();
+        }
+        diff
+    }
+
+    fn serialize_recursive(node: &SparseSpatialOctreeNode, bytes: &mut Vec<u8>) {
+        if node.half_extent < 1 {
+            return;
+        }
+        let Some(children) = &node.children else {
+            bytes.push(0);
+            return;
+        };
+        let mask = children
+            .iter()
+            .enumerate()
+            .filter(|(_, child)| child.is_some())
+            .fold(0u8, |mask, (index, _)| mask | 1 << index);
+        bytes.push(mask);
+        for child in children.iter().flatten() {
+            Self::serialize_recursive(child, bytes);
+        }
+    }
+
+    fn deserialize_recursive(
+        octree: &SparseSpatialOctree,
+        node: &mut SparseSpatialOctreeNode,
+        bytes: &[u8],
+        cursor: &mut usize,
+        is_root: bool,
+    ) -> Result<(), DeserializeError> {
+        if node.half_extent < 1 {
+            return Ok(());
+        }
+        let Some(&mask) = bytes.get(*cursor) else {
+            return Err(DeserializeError::Truncated);
+        };
+        *cursor += 1;
+        if mask == 0 {
+            return if is_root {
+                Ok(())
+            } else {
+                Err(DeserializeError::EmptyNode)
+            };
+        }
+        let mut children: [Option<Box<SparseSpatialOctreeNode>>; 8] =
+            core::array::from_fn(|_| None);
+        for (index, child) in children.iter_mut().enumerate() {
+            if mask & (1 << index) == 0 {
+                continue;
+            }
+            if node.half_extent == 1 {
+                let position = node.center - 1 + Self::child_offset(index);
+                if !octree.is_in_sphere(&(position - octree.center)) {
+                    return Err(DeserializeError::LeafOutsideSphere(position));
+                }
+            }
+            let mut new_node = Self::create_new_node(index, &node.center, node.half_extent);
+            Self::deserialize_recursive(octree, &mut new_node, bytes, cursor, false)?;
+            *child = Some(Box::from(new_node));
+        }
+        node.child_count = mask.count_ones() as usize;
+        node.children = Some(children);
+        Ok(())
+    }
+
+    fn diff_recursive(
+        own: Option<&SparseSpatialOctreeNode>,
+        other: Option<&SparseSpatialOctreeNode>,
+        node_min: IVec3,
+        diff: &mut OctreeDiff,
+    ) {
+        let (own, other) = match (own, other) {
+            (None, None) => return,
+            (Some(own), None) => {
+                Self::query_range_recursive(
+                    own,
+                    node_min,
+                    IVec3::MIN,
+                    IVec3::MAX,
+                    &mut diff.removed,
+                );
+                return;
+            }
+            (None, Some(other)) => {
+                Self::query_range_recursive(
+                    other,
+                    node_min,
+                    IVec3::MIN,
+                    IVec3::MAX,
+                    &mut diff.added,
+                );
+                return;
+            }
+            (Some(own), Some(other)) => (own, other),
+        };
+        if own.half_extent < 1 {
+            return;
+        }
+        for index in 0..8 {
+            let own_child = own.children.as_ref().and_then(|c| c[index].as_deref());
+            let other_child = other.children.as_ref().and_then(|c| c[index].as_deref());
+            let child_min = node_min + Self::child_offset(index) * own.half_extent;
+            Self::diff_recursive(own_child, other_child, child_min, diff);
+        }
+    }
+}
@@ -1,33 +1,66 @@
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::utility::sparse_spatial_octree::SparseSpatialOctree;
+    use crate::utility::SpatialOctree;
+    use crate::utility::sparse_spatial_octree::serialization::DeserializeError;
+    use crate::utility::sparse_spatial_octree::{RadiusError, SparseSpatialOctree};
     use glam::IVec3;
 
     // Helper function to create a default octree for tests
     fn create_test_octree() -> SparseSpatialOctree {
-        SparseSpatialOctree::new(IVec3::new(0, 0, 0), 8)
+        SparseSpatialOctree::new(IVec3::new(0, 0, 0), 8).unwrap()
     }
 
     #[test]
     fn test_new_octree_half_extent() {
-        let octree = SparseSpatialOctree::new(IVec3::ZERO, 1);
+        let octree = SparseSpatialOctree::new(IVec3::ZERO, 1).unwrap();
         assert_eq!(octree.root.half_extent, 1); // 8^1 -> half_extent = 2^0
 
-        let octree = SparseSpatialOctree::new(IVec3::ZERO, 2);
+        let octree = SparseSpatialOctree::new(IVec3::ZERO, 2).unwrap();
         assert_eq!(octree.root.half_extent, 2); // 8^2 -> half_extent = 2^1
     }
 
     #[test]
-    #[should_panic]
     fn test_new_octree_invalid_capacity() {
-        SparseSpatialOctree::new(IVec3::ZERO, 0);
+        assert_eq!(
+            SparseSpatialOctree::new(IVec3::ZERO, 0).err(),
+            Some(RadiusError::NotPositive(0))
+        );
+        assert_eq!(
+            SparseSpatialOctree::new(IVec3::ZERO, 513).err(),
+            Some(RadiusError::TooLarge(513))
+        );
+    }
+
+    #[test]
+    fn test_new_octree_rounds_extent_up() {
+        let octree = SparseSpatialOctree::new(IVec3::ZERO, 12).unwrap();
+        assert_eq!(octree.radius, 12);
+        assert_eq!(octree.root.half_extent, 16);
+
+        let octree = SparseSpatialOctree::new(IVec3::ZERO, 3).unwrap();
+        assert_eq!(octree.root.half_extent, 4);
+    }
+
+    #[test]
+    fn test_non_power_of_two_radius_keeps_sphere_exact() {
+        let mut octree = SparseSpatialOctree::new(IVec3::new(3, 0, -2), 12).unwrap();
+        let positions = sphere_positions(&octree);
+        for position in &positions {
+            octree.add(*position, false);
+        }
+        let mut stored = octree.query_range(IVec3::MIN, IVec3::MAX);
+        let mut expected = positions.clone();
+        stored.sort_by_key(|p| (p.x, p.y, p.z));
+        expected.sort_by_key(|p| (p.x, p.y, p.z));
+        assert_eq!(stored, expected);
+        assert!(!octree.exists(IVec3::new(3 + 13, 0, -2)));
     }
 
     #[test]
     fn test_new_octree() {
         let octree = create_test_octree();
-        assert_eq!(octree.root.center, IVec3::new(0, 0, 0));
+        assert_eq!(octree.root.center, IVec3::new(1, 1, 1));
         assert_eq!(octree.root.half_extent, 8);
         assert!(octree.root.children.is_none());
         assert_eq!(octree.root.child_count, 0);
@@ -49,7 +82,7 @@ mod tests {
         let child = children[child_index].as_ref().unwrap();
 
         // Check child node properties
-        assert_eq!(child.center, IVec3::new(4, 4, 4));
+        assert_eq!(child.center, IVec3::new(5, 5, 5));
         assert_eq!(child.half_extent, 4);
         assert!(child.children.is_some());
 
@@ -184,7 +217,7 @@ mod tests {
 
     #[test]
     fn test_add_at_minimum_half_extent() {
-        let mut octree = SparseSpatialOctree::new(IVec3::new(0, 0, 0), 1);
+        let mut octree = SparseSpatialOctree::new(IVec3::new(0, 0, 0), 1).unwrap();
         let position = IVec3::new(0, 0, 0);
         octree.add(position, true);
         assert!(octree.root.children.is_some());
@@ -193,7 +226,7 @@ mod tests {
 
     #[test]
     fn test_get() {
-        let mut octree: SparseSpatialOctree = SparseSpatialOctree::new(IVec3::ZERO, 8);
+        let mut octree: SparseSpatialOctree = SparseSpatialOctree::new(IVec3::ZERO, 8).unwrap();
 
         // Test empty octree
         assert_eq!(octree.exists(IVec3::new(1, 1, 1)), false);
@@ -214,10 +247,259 @@ mod tests {
     }
     #[test]
     fn test_add_child_count() {
-        let mut octree = SparseSpatialOctree::new(IVec3::ZERO, 8);
+        let mut octree = SparseSpatialOctree::new(IVec3::ZERO, 8).unwrap();
         octree.add(IVec3::new(1, 1, 1), true);
         octree.add(IVec3::new(-1, -1, -1), true);
         // Verify child_count is correct at root
         assert_eq!(octree.root.child_count, 2);
     }
+
+    fn sphere_positions(octree: &SparseSpatialOctree) -> Vec<IVec3> {
+        let mut positions = Vec::new();
+        let radius = octree.radius + 1;
+        for x in -radius..=radius {
+            for y in -radius..=radius {
+                for z in -radius..=radius {
+                    let local = IVec3::new(x, y, z);
+                    if octree.is_in_sphere(&local) {
+                        positions.push(local + octree.center);
+                    }
+                }
+            }
+        }
+        positions
+    }
+
+    #[test]
+    fn test_recenter_keeps_survivors() {
+        let mut octree = SparseSpatialOctree::new(IVec3::ZERO, 4).unwrap();
+        let positions = sphere_positions(&octree);
+        for position in &positions {
+            octree.add(*position, false);
+        }
+        let new_center = IVec3::new(2, -1, 3);
+        let recentered = octree.recenter(new_center);
+
+        assert_eq!(octree.center, new_center);
+        for position in &positions {
+            let survives = octree.is_in_sphere(&(position - new_center));
+            assert_eq!(octree.exists(*position), survives);
+            assert_eq!(recentered.dropped.contains(position), !survives);
+            assert!(!recentered.covered.contains(position));
+        }
+    }
+
+    #[test]
+    fn test_recenter_reports_covered() {
+        let mut octree = SparseSpatialOctree::new(IVec3::new(5, 5, 5), 8).unwrap();
+        let old_positions = sphere_positions(&octree);
+        let recentered = octree.recenter(IVec3::new(7, 4, 5));
+        let mut expected: Vec<IVec3> = sphere_positions(&octree)
+            .into_iter()
+            .filter(|position| !old_positions.contains(position))
+            .collect();
+        let mut covered = recentered.covered;
+        expected.sort_by_key(|p| (p.x, p.y, p.z));
+        covered.sort_by_key(|p| (p.x, p.y, p.z));
+        assert_eq!(covered, expected);
+        assert!(recentered.dropped.is_empty());
+    }
+
+    #[test]
+    fn test_recenter_reports_dropped() {
+        let mut octree = SparseSpatialOctree::new(IVec3::ZERO, 8).unwrap();
+        octree.add(IVec3::new(-7, 0, 0), false);
+        octree.add(IVec3::new(3, 0, 0), false);
+        let recentered = octree.recenter(IVec3::new(4, 0, 0));
+        assert_eq!(recentered.dropped, vec![IVec3::new(-7, 0, 0)]);
+        assert!(octree.exists(IVec3::new(3, 0, 0)));
+        assert!(!octree.exists(IVec3::new(-7, 0, 0)));
+    }
+
+    #[test]
+    fn test_recenter_far_move_covers_whole_sphere() {
+        let mut octree = SparseSpatialOctree::new(IVec3::ZERO, 2).unwrap();
+        octree.add(IVec3::new(1, 1, 1), false);
+        let recentered = octree.recenter(IVec3::new(100, 0, 0));
+        assert_eq!(recentered.dropped, vec![IVec3::new(1, 1, 1)]);
+        assert_eq!(recentered.covered.len(), sphere_positions(&octree).len());
+    }
+
+    #[test]
+    fn test_recenter_keeps_survivor_nodes() {
+        let mut octree = SparseSpatialOctree::new(IVec3::ZERO, 8).unwrap();
+        for position in sphere_positions(&octree) {
+            octree.add(position, false);
+        }
+        let filled = octree.allocated_nodes;
+        let mut center = IVec3::ZERO;
+        for step in [IVec3::X, IVec3::Y, -IVec3::Z, IVec3::X * 3] {
+            center += step;
+            let allocated = octree.allocated_nodes;
+            let recentered = octree.recenter(center);
+            //Survivors are never inserted again, only the newly covered cells allocate nodes.
+            assert_eq!(octree.allocated_nodes, allocated);
+            for position in &recentered.covered {
+                octree.add(*position, false);
+            }
+            assert!(octree.allocated_nodes - allocated < filled / 2);
+            assert_eq!(stored_positions(&octree), sorted(sphere_positions(&octree)));
+        }
+        //Far from where it started the tree still serializes like a fresh one.
+        let mut fresh = SparseSpatialOctree::new(center, 8).unwrap();
+        for position in sphere_positions(&fresh) {
+            fresh.add(position, false);
+        }
+        assert_eq!(octree.serialize(), fresh.serialize());
+        octree.recenter(IVec3::new(100, 0, 0));
+        assert_eq!(octree.root.half_extent, 8);
+        assert!(octree.root.children.is_none());
+    }
+
+    fn sorted(mut positions: Vec<IVec3>) -> Vec<IVec3> {
+        positions.sort_by_key(|p| (p.x, p.y, p.z));
+        positions
+    }
+
+    fn stored_positions(octree: &SparseSpatialOctree) -> Vec<IVec3> {
+        sorted(octree.query_range(IVec3::MIN, IVec3::MAX))
+    }
+
+    #[test]
+    fn test_serialize_round_trip() {
+        let mut octree = SparseSpatialOctree::new(IVec3::new(-3, 7, 100), 12).unwrap();
+        for (i, position) in sphere_positions(&octree).into_iter().enumerate() {
+            if i % 3 == 0 || i % 7 == 0 {
+                octree.add(position, false);
+            }
+        }
+        let bytes = octree.serialize();
+        let restored = SparseSpatialOctree::deserialize(&bytes).unwrap();
+        assert_eq!(restored.center, octree.center);
+        assert_eq!(restored.radius, octree.radius);
+        assert_eq!(stored_positions(&restored), stored_positions(&octree));
+        assert_eq!(restored.serialize(), bytes);
+    }
+
+    #[test]
+    fn test_serialize_empty_round_trip() {
+        let octree = create_test_octree();
+        let bytes = octree.serialize();
+        let restored = SparseSpatialOctree::deserialize(&bytes).unwrap();
+        assert!(restored.root.children.is_none());
+        assert_eq!(restored.root.child_count, 0);
+        assert_eq!(restored.serialize(), bytes);
+    }
+
+    #[test]
+    fn test_serialize_single_path_is_compact() {
+        let mut octree = create_test_octree();
+        octree.add(IVec3::new(2, 2, 2), true);
+        let bytes = octree.serialize();
+        //Header plus one mask per level above the leaf.
+        assert_eq!(bytes.len(), 17 + 4);
+        let restored = SparseSpatialOctree::deserialize(&bytes).unwrap();
+        assert!(restored.exists(IVec3::new(2, 2, 2)));
+        assert_eq!(restored.root.child_count, 1);
+    }
+
+    #[test]
+    fn test_deserialize_errors() {
+        let mut octree = create_test_octree();
+        octree.add(IVec3::new(2, 2, 2), true);
+        let bytes = octree.serialize();
+
+        assert_eq!(
+            SparseSpatialOctree::deserialize(&bytes[..bytes.len() - 1]).err(),
+            Some(DeserializeError::Truncated)
+        );
+        assert_eq!(
+            SparseSpatialOctree::deserialize(&bytes[..5]).err(),
+            Some(DeserializeError::Truncated)
+        );
+
+        let mut extended = bytes.clone();
+        extended.push(0);
+        assert_eq!(
+            SparseSpatialOctree::deserialize(&extended).err(),
+            Some(DeserializeError::TrailingBytes(1))
+        );
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[0] = 99;
+        assert_eq!(
+            SparseSpatialOctree::deserialize(&wrong_version).err(),
+            Some(DeserializeError::UnsupportedVersion(99))
+        );
+
+        let mut empty_node = bytes.clone();
+        empty_node[18] = 0;
+        assert_eq!(
+            SparseSpatialOctree::deserialize(&empty_node).err(),
+            Some(DeserializeError::EmptyNode)
+        );
+
+        let mut bad_radius = bytes.clone();
+        bad_radius[13..17].copy_from_slice(&0i32.to_le_bytes());
+        assert_eq!(
+            SparseSpatialOctree::deserialize(&bad_radius).err(),
+            Some(DeserializeError::InvalidRadius(RadiusError::NotPositive(0)))
+        );
+
+        //A path down the +x +y +z corner of the root, which is outside of the sphere.
+        let mut outside = create_test_octree().serialize();
+        outside.pop();
+        outside.extend([0x80; 4]);
+        assert_eq!(
+            SparseSpatialOctree::deserialize(&outside).err(),
+            Some(DeserializeError::LeafOutsideSphere(IVec3::new(8, 8, 8)))
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut before = create_test_octree();
+        let mut after = create_test_octree();
+        for position in [
+            IVec3::new(1, 1, 1),
+            IVec3::new(-3, 2, 0),
+            IVec3::new(4, 4, -2),
+        ] {
+            before.add(position, true);
+            after.add(position, true);
+        }
+        before.add(IVec3::new(-6, 0, 0), true);
+        before.add(IVec3::new(-5, 0, 0), true);
+        after.add(IVec3::new(6, 1, 1), true);
+        after.remove(IVec3::new(1, 1, 1));
+
+        let diff = before.diff(&after);
+        assert_eq!(sorted(diff.added), vec![IVec3::new(6, 1, 1)]);
+        assert_eq!(
+            sorted(diff.removed),
+            vec![
+                IVec3::new(-6, 0, 0),
+                IVec3::new(-5, 0, 0),
+                IVec3::new(1, 1, 1)
+            ]
+        );
+
+        let diff = before.diff(&before);
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+    }
+
+    #[test]
+    fn test_diff_different_centers() {
+        let mut before = SparseSpatialOctree::new(IVec3::ZERO, 8).unwrap();
+        let mut after = SparseSpatialOctree::new(IVec3::new(3, 0, 0), 5).unwrap();
+        before.add(IVec3::new(2, 0, 0), false);
+        before.add(IVec3::new(-6, 0, 0), false);
+        after.add(IVec3::new(2, 0, 0), false);
+        after.add(IVec3::new(7, 0, 0), false);
+
+        let diff = before.diff(&after);
+        assert_eq!(diff.added, vec![IVec3::new(7, 0, 0)]);
+        assert_eq!(diff.removed, vec![IVec3::new(-6, 0, 0)]);
+    }
 }
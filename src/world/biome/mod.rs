@@ -0,0 +1,182 @@
+mod tests;
+
+use crate::world::World;
+use crate::world::block_registry::{CACTUS, DIRT, FLOWER, GRASS, SAND, SNOW, STONE, TALL_GRASS};
+use crate::world::structures::StructureKind;
+use std::fmt::{Display, Formatter};
+
+//Biomes whose climates are closer than this to the nearest one mix into the terrain height, so
+//heights change smoothly across biome borders.
+const BLEND_DISTANCE: f32 = 0.08;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Biome {
+    Plains,
+    Forest,
+    Desert,
+    Tundra,
+    Mountains,
+}
+
+pub const BIOMES: [Biome; 5] = [
+    Biome::Plains,
+    Biome::Forest,
+    Biome::Desert,
+    Biome::Tundra,
+    Biome::Mountains,
+];
+
+//How likely a structure cell of the biome holds a structure, and the weights of the structures
+//picked for it.
+pub(crate) struct Decoration {
+    pub(crate) chance: f64,
+    pub(crate) structures: &'static [(StructureKind, u32)],
+}
+
+//How likely each candidate spot of the decoration pass gets a tree or a plant, and the plants
+//picked from.
+pub(crate) struct Vegetation {
+    pub(crate) tree_chance: f64,
+    pub(crate) plant_chance: f64,
+    pub(crate) plants: &'static [u8],
+}
+
+impl Biome {
+    pub fn name(self) -> &'static str {
+        match self {
+            Biome::Plains => "plains",
+            Biome::Forest => "forest",
+            Biome::Desert => "desert",
+            Biome::Tundra => "tundra",
+            Biome::Mountains => "mountains",
+        }
+    }
+
+    //sRGB color of the biome on a map.
+    pub fn map_color(self) -> [u8; 3] {
+        match self {
+            Biome::Plains => [141, 179, 96],
+            Biome::Forest => [5, 102, 33],
+            Biome::Desert => [250, 148, 24],
+            Biome::Tundra => [220, 230, 240],
+            Biome::Mountains => [96, 96, 96],
+        }
+    }
+
+    //Temperature and humidity, both from 0 to 1, the biome is picked for.
+    fn climate(self) -> (f32, f32) {
+        match self {
+            Biome::Plains => (0.5, 0.4),
+            Biome::Forest => (0.5, 0.65),
+            Biome::Desert => (0.7, 0.25),
+            Biome::Tundra => (0.3, 0.55),
+            Biome::Mountains => (0.3, 0.3),
+        }
+    }
+
+    //Base terrain height for a height noise value from 0 to 1.
+    pub(crate) fn height(self, noise: f32) -> f32 {
+        let (min, max, exponent) = match self {
+            Biome::Plains => (6.0, 13.0, 1.0),
+            Biome::Forest => (7.0, 18.0, 1.0),
+            Biome::Desert => (5.0, 11.0, 1.5),
+            Biome::Tundra => (6.0, 14.0, 1.0),
+            //Mostly foothills with the occasional steep peak.
+            Biome::Mountains => (9.0, 26.0, 2.0),
+        };
+        min + noise.powf(exponent) * (max - min)
+    }
+
+    //Block on top of the ground.
+    pub fn surface_block(self) -> u8 {
+        match self {
+            Biome::Plains | Biome::Forest => GRASS,
+            Biome::Desert => SAND,
+            Biome::Tundra => SNOW,
+            Biome::Mountains => STONE,
+        }
+    }
+
+    //Block of the few layers under the surface block, above the stone.
+    pub fn subsurface_block(self) -> u8 {
+        match self {
+            Biome::Plains | Biome::Forest | Biome::Tundra => DIRT,
+            Biome::Desert => SAND,
+            Biome::Mountains => STONE,
+        }
+    }
+
+    //Structures placed on the structure grid. Trees are not among them, they only come from the
+    //vegetation.
+    pub(crate) fn decoration(self) -> Decoration {
+        let (chance, structures): (f64, &'static [(StructureKind, u32)]) = match self {
+            Biome::Plains => (0.075, &[(StructureKind::Ruin, 1)]),
+            Biome::Forest | Biome::Tundra => (0.0, &[]),
+            Biome::Desert => (0.15, &[(StructureKind::Ruin, 1)]),
+            Biome::Mountains => (0.1, &[(StructureKind::Ruin, 1)]),
+        };
+        Decoration { chance, structures }
+    }
+
+    pub(crate) fn vegetation(self) -> Vegetation {
+        let (tree_chance, plant_chance, plants): (f64, f64, &'static [u8]) = match self {
+            Biome::Plains => (0.05, 0.6, &[TALL_GRASS, TALL_GRASS, TALL_GRASS, FLOWER]),
+            Biome::Forest => (0.8, 0.3, &[TALL_GRASS, FLOWER]),
+            Biome::Desert => (0.0, 0.08, &[CACTUS]),
+            Biome::Tundra => (0.1, 0.05, &[TALL_GRASS]),
+            Biome::Mountains => (0.0, 0.0, &[]),
+        };
+        Vegetation {
+            tree_chance,
+            plant_chance,
+            plants,
+        }
+    }
+
+    //The biome with the climate nearest to `temperature` and `humidity`.
+    pub(crate) fn from_climate(temperature: f32, humidity: f32) -> Biome {
+        let distances = climate_distances(temperature, humidity);
+        BIOMES
+            .into_iter()
+            .zip(distances)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .unwrap()
+            .0
+    }
+
+    //Base terrain height for a climate, mixed from the heights of the biomes whose climate is
+    //within BLEND_DISTANCE of the nearest one. The weights fall to 0 at that distance, so the
+    //height is continuous where the nearest biome changes.
+    pub(crate) fn blended_height(temperature: f32, humidity: f32, noise: f32) -> f32 {
+        let distances = climate_distances(temperature, humidity);
+        let nearest = distances.iter().copied().fold(f32::MAX, f32::min);
+        let mut height = 0.0;
+        let mut total_weight = 0.0;
+        for (biome, distance) in BIOMES.into_iter().zip(distances) {
+            let weight = (BLEND_DISTANCE - (distance - nearest)).max(0.0);
+            height += biome.height(noise) * weight;
+            total_weight += weight;
+        }
+        height / total_weight
+    }
+}
+
+impl Display for Biome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+fn climate_distances(temperature: f32, humidity: f32) -> [f32; BIOMES.len()] {
+    BIOMES.map(|biome| {
+        let (biome_temperature, biome_humidity) = biome.climate();
+        (temperature - biome_temperature).hypot(humidity - biome_humidity)
+    })
+}
+
+impl World {
+    //The biome of the world column (x, y), whether it is loaded or not.
+    pub fn biome(&self, x: i32, y: i32) -> Biome {
+        self.terrain.biome(x, y)
+    }
+}
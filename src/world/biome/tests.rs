@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod tests {
+    use crate::world::World;
+    use crate::world::biome::{BIOMES, Biome};
+    use crate::world::block_registry::{AIR, COAL_ORE, GOLD_ORE, IRON_ORE, STONE};
+    use crate::world::chunk::{CHUNK_SIDE_SIZE, CHUNK_SIZE, Chunk};
+    use crate::world::seed::DEFAULT_SEED;
+    use crate::world::terrain::Terrain;
+    use glam::{IVec3, ivec3};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_all_biomes_appear() {
+        let terrain = Terrain::new(DEFAULT_SEED);
+        let mut found = HashSet::new();
+        for x in (-4096..4096).step_by(64) {
+            for y in (-4096..4096).step_by(64) {
+                found.insert(terrain.biome(x, y));
+            }
+        }
+        assert_eq!(found.len(), BIOMES.len());
+    }
+
+    #[test]
+    fn test_biome_query() {
+        let world = World::new(1).unwrap();
+        let terrain = Terrain::new(DEFAULT_SEED);
+        for (x, y) in [(100, -20), (0, 0), (-3000, 1700), (2048, 4096)] {
+            assert_eq!(world.biome(x, y), terrain.biome(x, y));
+        }
+        //Biomes are wide, so a close column is almost always in the same one.
+        let same = (0..100)
+            .filter(|i| world.biome(i * 50, 0) == world.biome(i * 50 + 1, 0))
+            .count();
+        assert!(same > 95);
+        assert_eq!(Biome::Desert.to_string(), "desert");
+    }
+
+    #[test]
+    fn test_map_colors_are_distinct() {
+        let colors: HashSet<_> = BIOMES.iter().map(|biome| biome.map_color()).collect();
+        assert_eq!(colors.len(), BIOMES.len());
+    }
+
+    #[test]
+    fn test_surface_blocks_follow_biome() {
+        let terrain = Terrain::new(DEFAULT_SEED);
+        for chunk_pos in [ivec3(0, 0, 0), ivec3(7, -3, 0), ivec3(-20, 11, 0)] {
+            let mut texture = vec![AIR; CHUNK_SIZE as usize];
+            terrain.fill(chunk_pos, &mut texture);
+            let block = |pos: IVec3| texture[Chunk::texture_index(pos)];
+            let chunk_min = chunk_pos * CHUNK_SIDE_SIZE;
+            for y in 0..CHUNK_SIDE_SIZE {
+                for x in 0..CHUNK_SIDE_SIZE {
+                    let biome = terrain.biome(chunk_min.x + x, chunk_min.y + y);
+                    let Some(top) = (0..CHUNK_SIDE_SIZE)
+                        .rev()
+                        .find(|z| block(ivec3(x, y, *z)) != AIR)
+                    else {
+                        continue;
+                    };
+                    assert_eq!(block(ivec3(x, y, top)), biome.surface_block());
+                    if top > 3 && (top - 3..top).all(|z| block(ivec3(x, y, z)) != AIR) {
+                        assert_eq!(block(ivec3(x, y, top - 1)), biome.subsurface_block());
+                    }
+                }
+            }
+            //Deep down everything solid is stone, or ore the decoration put into it.
+            assert_eq!(
+                Chunk::new(chunk_pos - IVec3::Z * 2, &terrain)
+                    .unwrap()
+                    .texture
+                    .iter()
+                    .filter(|block| ![AIR, STONE, COAL_ORE, IRON_ORE, GOLD_ORE].contains(block))
+                    .count(),
+                0
+            );
+        }
+    }
+
+    #[test]
+    fn test_heights_blend_across_borders() {
+        let mut borders = 0;
+        let mut largest_gap: f32 = 0.0;
+        for step in 0..1000 {
+            let temperature = step as f32 / 1000.0;
+            let next_temperature = (step + 1) as f32 / 1000.0;
+            let (biome, next_biome) = (
+                Biome::from_climate(temperature, 0.4),
+                Biome::from_climate(next_temperature, 0.4),
+            );
+            let change = Biome::blended_height(next_temperature, 0.4, 0.8)
+                - Biome::blended_height(temperature, 0.4, 0.8);
+            assert!(change.abs() < 0.5, "temperature {}", temperature);
+            if biome != next_biome {
+                borders += 1;
+                largest_gap = largest_gap.max((biome.height(0.8) - next_biome.height(0.8)).abs());
+            }
+        }
+        assert!(borders >= 2);
+        //Without blending the height would jump at these borders.
+        assert!(largest_gap > 3.0);
+    }
+}
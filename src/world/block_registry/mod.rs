@@ -0,0 +1,226 @@
+mod tests;
+
+use crate::world::World;
+use crate::world::block_tick::{TickHandler, TickHandlers};
+use crate::world::fluid::Fluid;
+use crate::world::light::MAX_LIGHT;
+use glam::Vec3;
+use std::fmt::{Display, Formatter};
+
+pub const AIR: u8 = 0;
+pub const GRASS: u8 = 1;
+pub const STONE: u8 = 2;
+pub const WOOD: u8 = 3;
+pub const SAND: u8 = 4;
+pub const LEAVES: u8 = 5;
+pub const LAMP: u8 = 6;
+pub const RED_LAMP: u8 = 7;
+pub const BLUE_LAMP: u8 = 8;
+pub const WATER: u8 = 9;
+pub const LAVA: u8 = 10;
+pub const DIRT: u8 = 11;
+pub const SNOW: u8 = 12;
+pub const COAL_ORE: u8 = 13;
+pub const IRON_ORE: u8 = 14;
+pub const GOLD_ORE: u8 = 15;
+pub const TALL_GRASS: u8 = 16;
+pub const FLOWER: u8 = 17;
+pub const CACTUS: u8 = 18;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RegistryError {
+    ReservedId(u8),
+    IdTaken(u8),
+    NameTaken(String),
+}
+
+impl Display for RegistryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegistryError::ReservedId(id) => write!(f, "Block id {} is reserved for air", id),
+            RegistryError::IdTaken(id) => write!(f, "Block id {} is already registered", id),
+            RegistryError::NameTaken(name) => {
+                write!(f, "A block named {} is already registered", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockDefinition {
+    pub name: String,
+    //sRGB with alpha, the same layout as a MagicaVoxel palette entry.
+    pub color: [u8; 4],
+    //Red, green and blue block light levels the block emits, each up to MAX_LIGHT.
+    pub emission: [u8; 3],
+    //Set for blocks that flow.
+    pub fluid: Option<Fluid>,
+    //Set for blocks that fall when there is air or fluid below them.
+    pub falls: bool,
+}
+
+//Maps the u8 block ids stored in chunks to their definitions. Id 0 is always air.
+pub struct BlockRegistry {
+    blocks: Vec<Option<BlockDefinition>>,
+    tick_handlers: Vec<TickHandlers>,
+}
+
+impl Default for BlockRegistry {
+    fn default() -> Self {
+        let mut registry = Self::new();
+        let blocks = [
+            (GRASS, "grass", [92, 158, 71, 255]),
+            (STONE, "stone", [128, 128, 133, 255]),
+            (WOOD, "wood", [115, 82, 51, 255]),
+            (SAND, "sand", [219, 204, 143, 255]),
+            (LEAVES, "leaves", [51, 115, 41, 255]),
+            (LAMP, "lamp", [255, 221, 140, 255]),
+            (RED_LAMP, "red lamp", [230, 60, 50, 255]),
+            (BLUE_LAMP, "blue lamp", [70, 110, 240, 255]),
+            (WATER, "water", [52, 95, 218, 255]),
+            (LAVA, "lava", [207, 84, 18, 255]),
+            (DIRT, "dirt", [134, 96, 67, 255]),
+            (SNOW, "snow", [240, 244, 250, 255]),
+            (COAL_ORE, "coal ore", [54, 54, 58, 255]),
+            (IRON_ORE, "iron ore", [196, 160, 132, 255]),
+            (GOLD_ORE, "gold ore", [232, 200, 64, 255]),
+            (TALL_GRASS, "tall grass", [108, 178, 80, 255]),
+            (FLOWER, "flower", [214, 60, 140, 255]),
+            (CACTUS, "cactus", [88, 140, 60, 255]),
+        ];
+        for (id, name, color) in blocks {
+            registry
+                .register(id, name, color)
+                .expect("Built-in blocks must not collide");
+        }
+        let emissions = [
+            (LAMP, [MAX_LIGHT, 13, 9]),
+            (RED_LAMP, [MAX_LIGHT, 3, 2]),
+            (BLUE_LAMP, [4, 7, MAX_LIGHT]),
+            (LAVA, [MAX_LIGHT, 6, 1]),
+        ];
+        for (id, emission) in emissions {
+            registry.get_mut(id).unwrap().emission = emission;
+        }
+        registry.get_mut(WATER).unwrap().fluid = Some(Fluid { decay: 1, delay: 5 });
+        registry.get_mut(LAVA).unwrap().fluid = Some(Fluid {
+            decay: 2,
+            delay: 30,
+        });
+        for fluid in [WATER, LAVA] {
+            registry.set_scheduled_tick(fluid, World::update_fluid);
+        }
+        registry.get_mut(SAND).unwrap().falls = true;
+        registry.set_scheduled_tick(SAND, World::update_falling);
+        registry
+    }
+}
+
+impl BlockRegistry {
+    pub fn new() -> Self {
+        let mut blocks = vec![None; 256];
+        blocks[AIR as usize] = Some(BlockDefinition {
+            name: "air".to_string(),
+            color: [0, 0, 0, 0],
+            emission: [0; 3],
+            fluid: None,
+            falls: false,
+        });
+        Self {
+            blocks,
+            tick_handlers: vec![TickHandlers::default(); 256],
+        }
+    }
+
+    pub fn register(&mut self, id: u8, name: &str, color: [u8; 4]) -> Result<(), RegistryError> {
+        if id == AIR {
+            return Err(RegistryError::ReservedId(id));
+        }
+        if self.blocks[id as usize].is_some() {
+            return Err(RegistryError::IdTaken(id));
+        }
+        if self.id_by_name(name).is_some() {
+            return Err(RegistryError::NameTaken(name.to_string()));
+        }
+        self.blocks[id as usize] = Some(BlockDefinition {
+            name: name.to_string(),
+            color,
+            emission: [0; 3],
+            fluid: None,
+            falls: false,
+        });
+        Ok(())
+    }
+
+    pub fn get(&self, id: u8) -> Option<&BlockDefinition> {
+        self.blocks[id as usize].as_ref()
+    }
+
+    //Changes to emission only light blocks placed afterwards, and changes to fluid and falls only
+    //affect updates scheduled afterwards. Falling blocks also need World::update_falling as their
+    //scheduled tick.
+    pub fn get_mut(&mut self, id: u8) -> Option<&mut BlockDefinition> {
+        self.blocks[id as usize].as_mut()
+    }
+
+    pub fn tick_handlers(&self, id: u8) -> TickHandlers {
+        self.tick_handlers[id as usize]
+    }
+
+    pub fn set_scheduled_tick(&mut self, id: u8, handler: TickHandler) {
+        self.tick_handlers[id as usize].scheduled = Some(handler);
+    }
+
+    pub fn set_random_tick(&mut self, id: u8, handler: TickHandler) {
+        self.tick_handlers[id as usize].random = Some(handler);
+    }
+
+    pub fn id_by_name(&self, name: &str) -> Option<u8> {
+        self.iter()
+            .find(|(_, block)| block.name == name)
+            .map(|(id, _)| id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u8, &BlockDefinition)> {
+        self.blocks
+            .iter()
+            .enumerate()
+            .filter_map(|(id, block)| block.as_ref().map(|block| (id as u8, block)))
+    }
+
+    //sRGB in [0, 1], the registered color divided by 255. Unregistered ids get a stable color
+    //derived from the id so they can still be told apart.
+    pub fn color(&self, id: u8) -> Vec3 {
+        match self.get(id) {
+            Some(block) => {
+                Vec3::new(
+                    block.color[0] as f32,
+                    block.color[1] as f32,
+                    block.color[2] as f32,
+                ) / 255.0
+            }
+            None => {
+                let hash = (id as u32).wrapping_mul(2654435761);
+                Vec3::new(
+                    (hash & 0xff) as f32,
+                    (hash >> 8 & 0xff) as f32,
+                    (hash >> 16 & 0xff) as f32,
+                ) / 255.0
+            }
+        }
+    }
+
+    //The registered solid block with the color nearest to `color`, ignoring alpha.
+    pub fn closest_color(&self, color: [u8; 4]) -> Option<u8> {
+        self.iter()
+            .filter(|(id, _)| *id != AIR)
+            .min_by_key(|(_, block)| {
+                (0..3)
+                    .map(|channel| (block.color[channel] as i32 - color[channel] as i32).pow(2))
+                    .sum::<i32>()
+            })
+            .map(|(id, _)| id)
+    }
+}
@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use crate::world::block_registry::{
+        AIR, BlockRegistry, GRASS, LAMP, RED_LAMP, RegistryError, SAND, STONE,
+    };
+    use glam::Vec3;
+
+    #[test]
+    fn test_default_blocks() {
+        let registry = BlockRegistry::default();
+        assert_eq!(registry.get(AIR).unwrap().name, "air");
+        assert_eq!(registry.id_by_name("stone"), Some(STONE));
+        assert_eq!(registry.id_by_name("obsidian"), None);
+        assert!(registry.get(200).is_none());
+        assert_eq!(registry.iter().count(), 19);
+        assert_eq!(registry.get(LAMP).unwrap().emission, [15, 13, 9]);
+        assert_eq!(registry.get(STONE).unwrap().emission, [0; 3]);
+        assert!(registry.get(SAND).unwrap().falls);
+        assert!(!registry.get(STONE).unwrap().falls);
+    }
+
+    #[test]
+    fn test_register() {
+        let mut registry = BlockRegistry::default();
+        assert_eq!(registry.register(42, "glass", [200, 230, 255, 80]), Ok(()));
+        assert_eq!(registry.id_by_name("glass"), Some(42));
+        assert_eq!(
+            registry.register(AIR, "void", [0; 4]),
+            Err(RegistryError::ReservedId(AIR))
+        );
+        assert_eq!(
+            registry.register(42, "other", [0; 4]),
+            Err(RegistryError::IdTaken(42))
+        );
+        assert_eq!(
+            registry.register(43, "glass", [0; 4]),
+            Err(RegistryError::NameTaken("glass".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_color() {
+        let registry = BlockRegistry::default();
+        assert_eq!(
+            registry.color(STONE),
+            Vec3::new(128.0, 128.0, 133.0) / 255.0
+        );
+        //Unregistered ids still get a stable color.
+        assert_eq!(registry.color(99), registry.color(99));
+        assert_ne!(registry.color(99), registry.color(100));
+    }
+
+    #[test]
+    fn test_closest_color() {
+        let registry = BlockRegistry::default();
+        assert_eq!(registry.closest_color([92, 158, 71, 255]), Some(GRASS));
+        assert_eq!(registry.closest_color([220, 200, 140, 255]), Some(SAND));
+        assert_eq!(registry.closest_color([200, 10, 10, 255]), Some(RED_LAMP));
+        //Air is never picked, even for a fully transparent color.
+        assert_ne!(registry.closest_color([0, 0, 0, 0]), Some(AIR));
+        assert_eq!(BlockRegistry::new().closest_color([0, 0, 0, 0]), None);
+    }
+}
@@ -0,0 +1,154 @@
+mod tests;
+
+use crate::world::World;
+use crate::world::chunk::{CHUNK_SIDE_SIZE, Chunk};
+use glam::IVec3;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+//Scheduled ticks run in one world tick at most. The rest wait for the next ticks.
+pub const SCHEDULED_TICKS_PER_TICK: usize = 512;
+//Voxels of every loaded chunk picked for a random tick each world tick.
+pub const RANDOM_TICKS_PER_CHUNK: usize = 3;
+//Keeps random ticks apart from the world generation that derives from the same seed.
+const RANDOM_TICK_SALT: u64 = 7727;
+
+//Runs for the block at a voxel, when a tick scheduled there is due or when the voxel is picked
+//for a random tick.
+pub type TickHandler = fn(&mut World, IVec3);
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TickHandlers {
+    pub scheduled: Option<TickHandler>,
+    pub random: Option<TickHandler>,
+}
+
+//Ticks waiting at a position for their due tick. A position can wait for several due ticks, but
+//only once for each.
+#[derive(Default)]
+pub(crate) struct BlockTicks {
+    //The priority queue, by due tick and then by the order the ticks were scheduled in.
+    queue: BTreeMap<(u64, u64), IVec3>,
+    scheduled: HashSet<(IVec3, u64)>,
+    next_order: u64,
+    //Ticks of unloaded chunks by chunk position, with the ticks they still had to wait. They are
+    //kept with the chunk and wait again once it is loaded.
+    suspended: HashMap<IVec3, Vec<(IVec3, u64)>>,
+}
+
+impl BlockTicks {
+    fn schedule(&mut self, world_pos: IVec3, due: u64) {
+        if self.scheduled.insert((world_pos, due)) {
+            self.queue.insert((due, self.next_order), world_pos);
+            self.next_order += 1;
+        }
+    }
+
+    fn pop_due(&mut self, tick: u64) -> Option<IVec3> {
+        let entry = self.queue.first_entry()?;
+        let (due, _) = *entry.key();
+        if due > tick {
+            return None;
+        }
+        let world_pos = entry.remove();
+        self.scheduled.remove(&(world_pos, due));
+        Some(world_pos)
+    }
+
+    //Moves the ticks inside unloaded chunks out of the queue.
+    pub(crate) fn suspend_chunks(&mut self, chunk_positions: &HashSet<IVec3>, tick: u64) {
+        let suspended = &mut self.suspended;
+        let scheduled = &mut self.scheduled;
+        self.queue.retain(|(due, _), world_pos| {
+            let chunk_pos = Chunk::split_world_pos(*world_pos).0;
+            if !chunk_positions.contains(&chunk_pos) {
+                return true;
+            }
+            scheduled.remove(&(*world_pos, *due));
+            suspended
+                .entry(chunk_pos)
+                .or_default()
+                .push((*world_pos, due.saturating_sub(tick)));
+            false
+        });
+    }
+
+    //Puts the ticks of a chunk loaded again back into the queue, in their old order.
+    pub(crate) fn resume_chunk(&mut self, chunk_pos: IVec3, tick: u64) {
+        for (world_pos, remaining) in self.suspended.remove(&chunk_pos).unwrap_or_default() {
+            self.schedule(world_pos, tick + remaining);
+        }
+    }
+}
+
+//Blocks react to time through the handlers registered for them in the block registry. Scheduled
+//ticks run in due order, oldest first, and random ticks hit RANDOM_TICKS_PER_CHUNK voxels of each
+//loaded chunk per world tick. Both only depend on the world state and the tick count, so the same
+//edits always play out the same way.
+impl World {
+    //Schedules a tick for the block at `world_pos` in `delay` ticks, at least one. The handler
+    //that runs is the one of the block there when the tick is due. Ticks in unloaded chunks are
+    //dropped.
+    pub fn schedule_block_tick(&mut self, world_pos: IVec3, delay: u64) {
+        if self.get_block(world_pos).is_none() {
+            return;
+        }
+        self.block_ticks
+            .schedule(world_pos, self.tick_count + delay.max(1));
+    }
+
+    //Scheduled ticks in loaded chunks that did not run yet.
+    pub fn pending_block_ticks(&self) -> usize {
+        self.block_ticks.queue.len()
+    }
+
+    pub(crate) fn run_block_ticks(&mut self) {
+        let mut budget = SCHEDULED_TICKS_PER_TICK;
+        while budget > 0
+            && let Some(world_pos) = self.block_ticks.pop_due(self.tick_count)
+        {
+            budget -= 1;
+            let handler = self
+                .get_block(world_pos)
+                .and_then(|block| self.registry.tick_handlers(block).scheduled);
+            if let Some(handler) = handler {
+                handler(self, world_pos);
+            }
+        }
+        self.run_random_ticks();
+    }
+
+    fn run_random_ticks(&mut self) {
+        if !(0..=u8::MAX).any(|block| self.registry.tick_handlers(block).random.is_some()) {
+            return;
+        }
+        let mut chunk_positions: Vec<IVec3> = self
+            .loaded_chunks
+            .iter()
+            .filter(|(_, chunk)| chunk.is_some())
+            .map(|(chunk_pos, _)| *chunk_pos)
+            .collect();
+        chunk_positions.sort_by_key(|chunk_pos| chunk_pos.to_array());
+        for chunk_pos in chunk_positions {
+            let mut rng = StdRng::seed_from_u64(
+                self.seed()
+                    .position_seed(chunk_pos, RANDOM_TICK_SALT.wrapping_add(self.tick_count)),
+            );
+            for _ in 0..RANDOM_TICKS_PER_CHUNK {
+                let local_pos = IVec3::new(
+                    rng.random_range(0..CHUNK_SIDE_SIZE),
+                    rng.random_range(0..CHUNK_SIDE_SIZE),
+                    rng.random_range(0..CHUNK_SIDE_SIZE),
+                );
+                let world_pos = chunk_pos * CHUNK_SIDE_SIZE + local_pos;
+                let handler = self
+                    .get_block(world_pos)
+                    .and_then(|block| self.registry.tick_handlers(block).random);
+                if let Some(handler) = handler {
+                    handler(self, world_pos);
+                }
+            }
+        }
+    }
+}
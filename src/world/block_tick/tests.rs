@@ -0,0 +1,155 @@
+#[cfg(test)]
+mod tests {
+    use crate::world::World;
+    use crate::world::block_registry::{GRASS, STONE, WOOD};
+    use crate::world::block_tick::SCHEDULED_TICKS_PER_TICK;
+    use glam::{IVec3, ivec3};
+
+    const MARKER: u8 = 42;
+
+    //Marker blocks turn into stone on their scheduled tick, which shows up in the block changes.
+    fn create_test_world() -> World {
+        let mut world = World::new(4).unwrap();
+        let registry = world.registry_mut();
+        registry
+            .register(MARKER, "marker", [255, 0, 255, 255])
+            .unwrap();
+        registry.set_scheduled_tick(MARKER, |world, world_pos| {
+            world.set_block(world_pos, STONE);
+        });
+        world.record_block_changes(true);
+        world
+    }
+
+    fn place_markers(world: &mut World, positions: &[IVec3]) {
+        for position in positions {
+            world.set_block(*position, MARKER);
+        }
+        world.take_block_changes();
+    }
+
+    fn ticked_positions(world: &mut World) -> Vec<IVec3> {
+        world
+            .take_block_changes()
+            .iter()
+            .map(|change| change.position)
+            .collect()
+    }
+
+    #[test]
+    fn test_ticks_run_in_due_order() {
+        let mut world = create_test_world();
+        let positions = [ivec3(1, 1, 70), ivec3(2, 1, 70), ivec3(3, 1, 70)];
+        place_markers(&mut world, &positions);
+        world.schedule_block_tick(positions[0], 3);
+        world.schedule_block_tick(positions[1], 1);
+        world.schedule_block_tick(positions[2], 3);
+        world.schedule_block_tick(positions[1], 1);
+        assert_eq!(world.pending_block_ticks(), 3);
+
+        world.tick();
+        assert_eq!(ticked_positions(&mut world), vec![positions[1]]);
+        world.tick();
+        assert!(ticked_positions(&mut world).is_empty());
+        //Ticks due at the same time run in the order they were scheduled.
+        world.tick();
+        assert_eq!(
+            ticked_positions(&mut world),
+            vec![positions[0], positions[2]]
+        );
+        assert_eq!(world.pending_block_ticks(), 0);
+    }
+
+    #[test]
+    fn test_tick_runs_the_current_block_handler() {
+        let mut world = create_test_world();
+        let position = ivec3(1, 1, 70);
+        place_markers(&mut world, &[position]);
+        world.schedule_block_tick(position, 2);
+        world.set_block(position, WOOD);
+        world.take_block_changes();
+        world.tick();
+        world.tick();
+        assert_eq!(world.pending_block_ticks(), 0);
+        assert_eq!(world.get_block(position), Some(WOOD));
+    }
+
+    #[test]
+    fn test_budget_keeps_order() {
+        let mut world = create_test_world();
+        let positions: Vec<IVec3> = (0..SCHEDULED_TICKS_PER_TICK as i32 + 10)
+            .map(|i| ivec3(i % 100, i / 100, 70))
+            .collect();
+        place_markers(&mut world, &positions);
+        for position in &positions {
+            world.schedule_block_tick(*position, 1);
+        }
+        world.tick();
+        assert_eq!(
+            ticked_positions(&mut world),
+            positions[..SCHEDULED_TICKS_PER_TICK]
+        );
+        assert_eq!(world.pending_block_ticks(), 10);
+        world.tick();
+        assert_eq!(
+            ticked_positions(&mut world),
+            positions[SCHEDULED_TICKS_PER_TICK..]
+        );
+    }
+
+    #[test]
+    fn test_ticks_wait_while_chunk_is_unloaded() {
+        let mut world = World::new(4).unwrap();
+        world
+            .registry_mut()
+            .set_scheduled_tick(STONE, |world, world_pos| {
+                world.set_block(world_pos, WOOD);
+            });
+        //Inside chunk (-3, 0, 0), which is unloaded when the map moves to (1, 0, 0).
+        let position = ivec3(-90, 5, -10);
+        assert_eq!(world.get_block(position), Some(STONE));
+        world.schedule_block_tick(position, 10);
+        for _ in 0..4 {
+            world.tick();
+        }
+        world.update_map_position(IVec3::new(1, 0, 0));
+        assert_eq!(world.pending_block_ticks(), 0);
+        for _ in 0..20 {
+            world.tick();
+        }
+        world.update_map_position(IVec3::ZERO);
+        assert_eq!(world.pending_block_ticks(), 1);
+        //The tick had 6 ticks left to wait when the chunk was unloaded.
+        for _ in 0..5 {
+            world.tick();
+        }
+        assert_eq!(world.get_block(position), Some(STONE));
+        world.tick();
+        assert_eq!(world.get_block(position), Some(WOOD));
+    }
+
+    #[test]
+    fn test_random_ticks_are_deterministic() {
+        let run = || {
+            let mut world = World::new(4).unwrap();
+            world
+                .registry_mut()
+                .set_random_tick(GRASS, |world, world_pos| {
+                    world.set_block(world_pos, STONE);
+                });
+            world.record_block_changes(true);
+            for _ in 0..10 {
+                world.tick();
+            }
+            world.take_block_changes()
+        };
+        let changes = run();
+        assert!(!changes.is_empty());
+        assert!(
+            changes
+                .iter()
+                .all(|change| change.old == GRASS && change.new == STONE)
+        );
+        assert_eq!(changes, run());
+    }
+}
@@ -1,53 +1,55 @@
-use fastnoise_lite::{FastNoiseLite, NoiseType};
+use crate::world::decoration;
+use crate::world::structures;
+use crate::world::terrain::Terrain;
 use glam::IVec3;
 
-const MIN_HEIGHT: i32 = 1;
-const MAX_HEIGHT: i32 = 30;
-const CHUNK_SIDE_SIZE: i32 = 32;
+pub(crate) const MIN_HEIGHT: i32 = 1;
+pub(crate) const MAX_HEIGHT: i32 = 30;
+pub(crate) const CHUNK_SIDE_SIZE: i32 = 32;
 const CHUNK_SIDE_SIZE_SQR: i32 = CHUNK_SIDE_SIZE * CHUNK_SIDE_SIZE;
-const CHUNK_SIZE: i32 = CHUNK_SIDE_SIZE * CHUNK_SIDE_SIZE * CHUNK_SIDE_SIZE;
+pub(crate) const CHUNK_SIZE: i32 = CHUNK_SIDE_SIZE * CHUNK_SIDE_SIZE * CHUNK_SIDE_SIZE;
 pub struct Chunk {
     pub texture: Vec<u8>,
     pub position: IVec3,
 }
 
 impl Chunk {
-    pub fn new(position: IVec3) -> Option<Box<Self>> {
+    pub(crate) fn new(position: IVec3, terrain: &Terrain) -> Option<Box<Self>> {
         let mut texture = vec![0; CHUNK_SIZE as usize];
 
-        let mut noise = FastNoiseLite::with_seed(1944);
-        noise.set_noise_type(Some(NoiseType::OpenSimplex2));
-        noise.set_frequency(Some(0.05));
-        let mut not_empty = false;
-        for x in 0..CHUNK_SIDE_SIZE {
-            let x_coord = position.x * CHUNK_SIDE_SIZE + x;
-            for y in 0..CHUNK_SIDE_SIZE {
-                let y_coord = position.y * CHUNK_SIDE_SIZE + y;
-                let mut value = noise.get_noise_2d(x_coord as f32, y_coord as f32);
-                value += 1.0;
-                value /= 2.0;
-                value *= (MAX_HEIGHT - MIN_HEIGHT) as f32;
-                value += MIN_HEIGHT as f32;
-                let z_start = position.z * CHUNK_SIDE_SIZE;
-                if value < z_start as f32 {
-                    continue;
-                }
-                not_empty = true;
-                for z in 0..CHUNK_SIDE_SIZE {
-                    let z_coord = z_start + z;
-                    if z_coord as f32 <= value {
-                        let texture_index = x + y * CHUNK_SIDE_SIZE + z * CHUNK_SIDE_SIZE_SQR;
-                        texture[texture_index as usize] = 1;
-                    } else {
-                        break;
-                    }
-                }
-            }
-        }
+        let mut not_empty = terrain.fill(position, &mut texture);
+        let mut chunk = Self { texture, position };
+        not_empty |= decoration::decorate(terrain, &mut chunk);
+        not_empty |= structures::place_structures(terrain, &mut chunk);
         if !not_empty {
             None
         } else {
-            Some(Box::from(Self { texture, position }))
+            Some(Box::from(chunk))
         }
     }
+
+    pub fn empty(position: IVec3) -> Box<Self> {
+        Box::from(Self {
+            texture: vec![0; CHUNK_SIZE as usize],
+            position,
+        })
+    }
+
+    //Splits a world voxel position into the chunk position and the position inside that chunk.
+    pub fn split_world_pos(world_pos: IVec3) -> (IVec3, IVec3) {
+        let side = IVec3::splat(CHUNK_SIDE_SIZE);
+        (world_pos.div_euclid(side), world_pos.rem_euclid(side))
+    }
+
+    pub fn get_block(&self, local_pos: IVec3) -> u8 {
+        self.texture[Self::texture_index(local_pos)]
+    }
+
+    pub fn set_block(&mut self, local_pos: IVec3, block: u8) -> u8 {
+        std::mem::replace(&mut self.texture[Self::texture_index(local_pos)], block)
+    }
+
+    pub(crate) fn texture_index(local_pos: IVec3) -> usize {
+        (local_pos.x + local_pos.y * CHUNK_SIDE_SIZE + local_pos.z * CHUNK_SIDE_SIZE_SQR) as usize
+    }
 }
@@ -0,0 +1,206 @@
+mod tests;
+
+use crate::world::block_registry::{AIR, COAL_ORE, GOLD_ORE, IRON_ORE, STONE};
+use crate::world::chunk::{CHUNK_SIDE_SIZE, Chunk};
+use crate::world::seed::WorldSeed;
+use crate::world::structures::{StructureKind, structure_blocks};
+use crate::world::terrain::{LOWEST_SURFACE, Terrain};
+use glam::{IVec2, IVec3, ivec3};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+//Spots per chunk column that get a tree or a plant with the chance of the biome there.
+const TREE_CANDIDATES: u32 = 8;
+const PLANT_CANDIDATES: u32 = 32;
+const TREE_SALT: u64 = 100;
+const PLANT_SALT: u64 = 101;
+
+struct Ore {
+    block: u8,
+    veins_per_chunk: u32,
+    vein_size: u32,
+    //Veins start between these heights, most often halfway between them.
+    min_z: i32,
+    max_z: i32,
+}
+
+const ORES: [Ore; 3] = [
+    Ore {
+        block: COAL_ORE,
+        veins_per_chunk: 6,
+        vein_size: 10,
+        min_z: -96,
+        max_z: 24,
+    },
+    Ore {
+        block: IRON_ORE,
+        veins_per_chunk: 4,
+        vein_size: 7,
+        min_z: -112,
+        max_z: 0,
+    },
+    Ore {
+        block: GOLD_ORE,
+        veins_per_chunk: 2,
+        vein_size: 5,
+        min_z: -128,
+        max_z: -48,
+    },
+];
+
+//Places ores, trees and plants into a chunk whose terrain is filled. Features are generated for
+//the chunk (or chunk column) they start in from a seed of its position only, and every chunk
+//they reach generates them again and keeps its own part, so they line up across chunk borders
+//no matter which chunk is generated first. Returns whether any block was placed.
+pub(crate) fn decorate(terrain: &Terrain, chunk: &mut Chunk) -> bool {
+    let mut placed = place_ores(terrain.seed(), chunk);
+    let chunk_min = chunk.position * CHUNK_SIDE_SIZE;
+    let chunk_max = chunk_min + CHUNK_SIDE_SIZE - 1;
+    let tree_height = structure_blocks(StructureKind::Tree, 0).size().z;
+    if chunk_max.z >= LOWEST_SURFACE && chunk_min.z <= terrain.max_surface_height() + tree_height {
+        placed |= place_trees(terrain, chunk);
+        placed |= place_plants(terrain, chunk);
+    }
+    placed
+}
+
+//Veins are random walks from a start in their chunk. They are shorter than a chunk, so only the
+//chunks next to this one can reach it. Only stone is replaced.
+fn place_ores(seed: WorldSeed, chunk: &mut Chunk) -> bool {
+    let chunk_min = chunk.position * CHUNK_SIDE_SIZE;
+    let chunk_max = chunk_min + CHUNK_SIDE_SIZE - 1;
+    let mut placed = false;
+    for (salt, ore) in ORES.iter().enumerate() {
+        let reach = ore.vein_size as i32;
+        if chunk_min.z > ore.max_z + reach || chunk_max.z < ore.min_z - reach {
+            continue;
+        }
+        for z in -1..=1 {
+            for y in -1..=1 {
+                for x in -1..=1 {
+                    let source = chunk.position + ivec3(x, y, z);
+                    let mut rng = StdRng::seed_from_u64(seed.position_seed(source, salt as u64));
+                    for _ in 0..ore.veins_per_chunk {
+                        let start = source * CHUNK_SIDE_SIZE
+                            + IVec3::from_array(
+                                [(); 3].map(|_| rng.random_range(0..CHUNK_SIDE_SIZE)),
+                            );
+                        if !rng.random_bool(depth_weight(ore, start.z)) {
+                            continue;
+                        }
+                        let mut pos = start;
+                        for _ in 0..ore.vein_size {
+                            if pos.cmpge(chunk_min).all() && pos.cmple(chunk_max).all() {
+                                let local_pos = pos - chunk_min;
+                                if chunk.get_block(local_pos) == STONE {
+                                    chunk.set_block(local_pos, ore.block);
+                                    placed = true;
+                                }
+                            }
+                            let mut step = IVec3::ZERO;
+                            step[rng.random_range(0..3)] =
+                                if rng.random_bool(0.5) { 1 } else { -1 };
+                            pos += step;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    placed
+}
+
+//How likely a vein starting at height z is kept, rising from 0 at the ends of the ore's range to
+//1 in its middle.
+fn depth_weight(ore: &Ore, z: i32) -> f64 {
+    let half = (ore.max_z - ore.min_z) as f64 / 2.0;
+    let middle = ore.min_z as f64 + half;
+    (1.0 - (z as f64 - middle).abs() / half).max(0.0)
+}
+
+//Trees stand on the surface of their column and can reach into the columns around it, so those
+//are generated too, always in the same order. A tree only fills air, so the first tree keeps the
+//voxels two trees share and trees never cut into the ground.
+fn place_trees(terrain: &Terrain, chunk: &mut Chunk) -> bool {
+    let chunk_min = chunk.position * CHUNK_SIDE_SIZE;
+    let chunk_max = chunk_min + CHUNK_SIDE_SIZE - 1;
+    let mut placed = false;
+    for column_y in -1..=1 {
+        for column_x in -1..=1 {
+            let column = chunk.position.truncate() + IVec2::new(column_x, column_y);
+            let mut rng =
+                StdRng::seed_from_u64(terrain.seed().position_seed(column.extend(0), TREE_SALT));
+            for _ in 0..TREE_CANDIDATES {
+                let base = column * CHUNK_SIDE_SIZE
+                    + IVec2::from_array([(); 2].map(|_| rng.random_range(0..CHUNK_SIDE_SIZE)));
+                let rotation = rng.random_range(0..4);
+                let chance = terrain.biome(base.x, base.y).vegetation().tree_chance;
+                if !rng.random_bool(chance) {
+                    continue;
+                }
+                let blocks = structure_blocks(StructureKind::Tree, rotation);
+                let size = blocks.size();
+                let tree_min = base - size.truncate() / 2;
+                let tree_max = tree_min + size.truncate() - 1;
+                if tree_min.cmpgt(chunk_max.truncate()).any()
+                    || tree_max.cmplt(chunk_min.truncate()).any()
+                {
+                    continue;
+                }
+                let ground = terrain.surface_height(base.x, base.y);
+                if ground <= LOWEST_SURFACE {
+                    continue;
+                }
+                let min = tree_min.extend(ground + 1);
+                let overlap_min = min.max(chunk_min);
+                let overlap_max = (min + size - 1).min(chunk_max);
+                for z in overlap_min.z..=overlap_max.z {
+                    for y in overlap_min.y..=overlap_max.y {
+                        for x in overlap_min.x..=overlap_max.x {
+                            let world_pos = ivec3(x, y, z);
+                            let block = blocks.get(world_pos - min);
+                            let local_pos = world_pos - chunk_min;
+                            if block != AIR && chunk.get_block(local_pos) == AIR {
+                                chunk.set_block(local_pos, block);
+                                placed = true;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    placed
+}
+
+//Plants take a single voxel, so only the chunk they grow in places them.
+fn place_plants(terrain: &Terrain, chunk: &mut Chunk) -> bool {
+    let chunk_min = chunk.position * CHUNK_SIDE_SIZE;
+    let mut rng = StdRng::seed_from_u64(
+        terrain
+            .seed()
+            .position_seed(chunk.position.truncate().extend(0), PLANT_SALT),
+    );
+    let mut placed = false;
+    for _ in 0..PLANT_CANDIDATES {
+        let local_x = rng.random_range(0..CHUNK_SIDE_SIZE);
+        let local_y = rng.random_range(0..CHUNK_SIDE_SIZE);
+        let (x, y) = (chunk_min.x + local_x, chunk_min.y + local_y);
+        let vegetation = terrain.biome(x, y).vegetation();
+        if vegetation.plants.is_empty() || !rng.random_bool(vegetation.plant_chance) {
+            continue;
+        }
+        let plant = vegetation.plants[rng.random_range(0..vegetation.plants.len())];
+        let ground = terrain.surface_height(x, y);
+        let local_z = ground + 1 - chunk_min.z;
+        if ground <= LOWEST_SURFACE || !(0..CHUNK_SIDE_SIZE).contains(&local_z) {
+            continue;
+        }
+        let local_pos = ivec3(local_x, local_y, local_z);
+        if chunk.get_block(local_pos) == AIR {
+            chunk.set_block(local_pos, plant);
+            placed = true;
+        }
+    }
+    placed
+}
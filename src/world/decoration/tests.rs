@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod tests {
+    use crate::world::World;
+    use crate::world::block_registry::{
+        AIR, CACTUS, COAL_ORE, FLOWER, GOLD_ORE, IRON_ORE, TALL_GRASS, WOOD,
+    };
+    use crate::world::chunk::{CHUNK_SIDE_SIZE, Chunk};
+    use crate::world::decoration::ORES;
+    use crate::world::seed::DEFAULT_SEED;
+    use crate::world::terrain::Terrain;
+    use glam::{IVec3, ivec3};
+
+    #[test]
+    fn test_ores_follow_their_depths() {
+        let terrain = Terrain::new(DEFAULT_SEED);
+        let mut found = [0; ORES.len()];
+        for chunk_pos in [
+            ivec3(0, 0, -1),
+            ivec3(1, 0, -2),
+            ivec3(0, -1, -3),
+            ivec3(-1, 1, -4),
+        ] {
+            let chunk = Chunk::new(chunk_pos, &terrain).unwrap();
+            for (index, block) in chunk.texture.iter().enumerate() {
+                let Some(ore) = ORES.iter().position(|ore| ore.block == *block) else {
+                    continue;
+                };
+                let z = chunk_pos.z * CHUNK_SIDE_SIZE
+                    + index as i32 / (CHUNK_SIDE_SIZE * CHUNK_SIDE_SIZE);
+                //A vein can wander up to its size away from where it started.
+                let reach = ORES[ore].vein_size as i32;
+                assert!(z >= ORES[ore].min_z - reach && z <= ORES[ore].max_z + reach);
+                found[ore] += 1;
+            }
+        }
+        assert!(found.iter().all(|count| *count > 0), "{:?}", found);
+        assert_eq!([COAL_ORE, IRON_ORE, GOLD_ORE], ORES.map(|ore| ore.block));
+    }
+
+    #[test]
+    fn test_trees_line_up_across_chunks() {
+        let world = World::new(4).unwrap();
+        let mut trunks = 0;
+        let mut crossing_border = 0;
+        for x in -64..64 {
+            for y in -64..64 {
+                //A trunk base is wood on the ground, the crown sits on top of the trunk.
+                for z in -32..32 {
+                    let pos = ivec3(x, y, z);
+                    let below = world.get_block(pos - IVec3::Z);
+                    if world.get_block(pos) != Some(WOOD)
+                        || below == Some(WOOD)
+                        || below == Some(AIR)
+                    {
+                        continue;
+                    }
+                    let top = (1..5).all(|h| world.get_block(pos + IVec3::Z * h) == Some(WOOD));
+                    if !top {
+                        continue;
+                    }
+                    trunks += 1;
+                    let crown = pos + IVec3::Z * 5;
+                    let mut chunks = vec![Chunk::split_world_pos(pos).0];
+                    for offset in [IVec3::X, -IVec3::X, IVec3::Y, -IVec3::Y] {
+                        let leaves = crown + offset * 2 - IVec3::Z;
+                        //Only terrain in the way of the crown can replace its leaves.
+                        assert_ne!(world.get_block(leaves), Some(AIR), "{}", leaves);
+                        chunks.push(Chunk::split_world_pos(leaves).0);
+                    }
+                    if chunks.iter().any(|chunk| *chunk != chunks[0]) {
+                        crossing_border += 1;
+                    }
+                }
+            }
+        }
+        assert!(trunks > 5);
+        assert!(crossing_border > 0);
+    }
+
+    #[test]
+    fn test_plants_grow_on_the_ground() {
+        let world = World::new(4).unwrap();
+        let mut plants = 0;
+        for x in -64..64 {
+            for y in -64..64 {
+                for z in -32..32 {
+                    let pos = ivec3(x, y, z);
+                    let block = world.get_block(pos).unwrap();
+                    if ![TALL_GRASS, FLOWER, CACTUS].contains(&block) {
+                        continue;
+                    }
+                    let below = world.get_block(pos - IVec3::Z).unwrap();
+                    assert!(![AIR, TALL_GRASS, FLOWER, CACTUS].contains(&below));
+                    plants += 1;
+                }
+            }
+        }
+        assert!(plants > 0);
+    }
+}
@@ -0,0 +1,309 @@
+mod tests;
+
+use crate::world::chunk::{CHUNK_SIDE_SIZE, Chunk};
+use crate::world::history::{ChunkEdit, HistoryError};
+use crate::world::{BlockChange, NEIGHBOUR_OFFSETS, World};
+use glam::{IVec3, ivec3};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+//Axis aligned box of world voxel positions, both corners inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub min: IVec3,
+    pub max: IVec3,
+}
+
+impl Region {
+    pub fn new(corner: IVec3, other_corner: IVec3) -> Self {
+        Self {
+            min: corner.min(other_corner),
+            max: corner.max(other_corner),
+        }
+    }
+
+    pub fn size(&self) -> IVec3 {
+        self.max - self.min + 1
+    }
+
+    pub fn volume(&self) -> usize {
+        let size = self.size().as_i64vec3();
+        (size.x * size.y * size.z) as usize
+    }
+
+    pub fn contains(&self, position: IVec3) -> bool {
+        position.cmpge(self.min).all() && position.cmple(self.max).all()
+    }
+
+    pub fn expanded(&self, amount: i32) -> Self {
+        Self::new(self.min - amount, self.max + amount)
+    }
+
+    fn is_on_border(&self, position: IVec3) -> bool {
+        position.cmpeq(self.min).any() || position.cmpeq(self.max).any()
+    }
+
+    //Inclusive range of the chunk positions the region overlaps.
+    pub(crate) fn chunk_range(&self) -> (IVec3, IVec3) {
+        let (chunk_min, _) = Chunk::split_world_pos(self.min);
+        let (chunk_max, _) = Chunk::split_world_pos(self.max);
+        (chunk_min, chunk_max)
+    }
+
+    //Part of the region inside the chunk as inclusive local positions.
+    fn local_range(&self, chunk_pos: IVec3) -> (IVec3, IVec3) {
+        let chunk_origin = chunk_pos * CHUNK_SIDE_SIZE;
+        let local_min = (self.min - chunk_origin).max(IVec3::ZERO);
+        let local_max = (self.max - chunk_origin).min(IVec3::splat(CHUNK_SIDE_SIZE - 1));
+        (local_min, local_max)
+    }
+}
+
+//Blocks copied out of a world, indexed x + y * size.x + z * size.x * size.y like chunk textures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Clipboard {
+    size: IVec3,
+    blocks: Vec<u8>,
+}
+
+impl Clipboard {
+    pub fn new(size: IVec3) -> Self {
+        assert!(
+            size.cmpgt(IVec3::ZERO).all(),
+            "Clipboard size must be positive"
+        );
+        let volume = size.x as usize * size.y as usize * size.z as usize;
+        Self {
+            size,
+            blocks: vec![0; volume],
+        }
+    }
+
+    //Returns None if the block count does not match the size.
+    pub fn from_blocks(size: IVec3, blocks: Vec<u8>) -> Option<Self> {
+        if size.cmple(IVec3::ZERO).any() {
+            return None;
+        }
+        let volume = size.x as usize * size.y as usize * size.z as usize;
+        (blocks.len() == volume).then_some(Self { size, blocks })
+    }
+
+    pub fn size(&self) -> IVec3 {
+        self.size
+    }
+
+    pub fn blocks(&self) -> &[u8] {
+        &self.blocks
+    }
+
+    pub fn get(&self, position: IVec3) -> u8 {
+        self.blocks[self.index(position)]
+    }
+
+    pub fn set(&mut self, position: IVec3, block: u8) {
+        let index = self.index(position);
+        self.blocks[index] = block;
+    }
+
+    //Rotates counter-clockwise around the vertical (z) axis in steps of 90 degrees.
+    pub fn rotated(&self, quarter_turns: i32) -> Self {
+        let quarter_turns = quarter_turns.rem_euclid(4);
+        let size = if quarter_turns % 2 == 0 {
+            self.size
+        } else {
+            ivec3(self.size.y, self.size.x, self.size.z)
+        };
+        let mut rotated = Self::new(size);
+        for position in self.positions() {
+            let target = match quarter_turns {
+                0 => position,
+                1 => ivec3(self.size.y - 1 - position.y, position.x, position.z),
+                2 => ivec3(
+                    self.size.x - 1 - position.x,
+                    self.size.y - 1 - position.y,
+                    position.z,
+                ),
+                _ => ivec3(position.y, self.size.x - 1 - position.x, position.z),
+            };
+            rotated.set(target, self.get(position));
+        }
+        rotated
+    }
+
+    pub fn mirrored(&self, axis: Axis) -> Self {
+        let mut mirrored = Self::new(self.size);
+        for position in self.positions() {
+            let mut target = position;
+            match axis {
+                Axis::X => target.x = self.size.x - 1 - position.x,
+                Axis::Y => target.y = self.size.y - 1 - position.y,
+                Axis::Z => target.z = self.size.z - 1 - position.z,
+            }
+            mirrored.set(target, self.get(position));
+        }
+        mirrored
+    }
+
+    fn positions(&self) -> impl Iterator<Item = IVec3> + use<> {
+        let size = self.size;
+        (0..size.z).flat_map(move |z| {
+            (0..size.y).flat_map(move |y| (0..size.x).map(move |x| ivec3(x, y, z)))
+        })
+    }
+
+    fn index(&self, position: IVec3) -> usize {
+        debug_assert!(position.cmpge(IVec3::ZERO).all() && position.cmplt(self.size).all());
+        position.x as usize
+            + position.y as usize * self.size.x as usize
+            + position.z as usize * self.size.x as usize * self.size.y as usize
+    }
+}
+
+//Bulk edits work chunk by chunk on `loaded_chunks` instead of going through set_block, so dirty
+//tracking happens once per chunk. Each operation is a single undo step and returns the number of
+//changed blocks, or an error if the edit history refused it and it was rolled back. Parts of a
+//region in unloaded chunks are skipped, and read as air when copying.
+impl World {
+    pub fn copy_region(&self, region: &Region) -> Clipboard {
+        let mut clipboard = Clipboard::new(region.size());
+        let (chunk_min, chunk_max) = region.chunk_range();
+        for chunk_z in chunk_min.z..=chunk_max.z {
+            for chunk_y in chunk_min.y..=chunk_max.y {
+                for chunk_x in chunk_min.x..=chunk_max.x {
+                    let chunk_pos = ivec3(chunk_x, chunk_y, chunk_z);
+                    let Some(Some(chunk)) = self.loaded_chunks.get(&chunk_pos) else {
+                        continue;
+                    };
+                    let (local_min, local_max) = region.local_range(chunk_pos);
+                    let offset = chunk_pos * CHUNK_SIDE_SIZE - region.min;
+                    for z in local_min.z..=local_max.z {
+                        for y in local_min.y..=local_max.y {
+                            for x in local_min.x..=local_max.x {
+                                let local_pos = ivec3(x, y, z);
+                                clipboard.set(local_pos + offset, chunk.get_block(local_pos));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        clipboard
+    }
+
+    //Pastes with the clipboard's minimum corner at `position`. Air in the clipboard only
+    //overwrites the world if `include_air` is set.
+    pub fn paste(
+        &mut self,
+        clipboard: &Clipboard,
+        position: IVec3,
+        include_air: bool,
+    ) -> Result<usize, HistoryError> {
+        let region = Region::new(position, position + clipboard.size() - 1);
+        self.edit_region(&region, |world_pos, old| {
+            let block = clipboard.get(world_pos - position);
+            if block == 0 && !include_air {
+                old
+            } else {
+                block
+            }
+        })
+    }
+
+    pub fn fill(&mut self, region: &Region, block: u8) -> Result<usize, HistoryError> {
+        self.edit_region(region, |_, _| block)
+    }
+
+    pub fn replace(&mut self, region: &Region, from: u8, to: u8) -> Result<usize, HistoryError> {
+        self.edit_region(region, |_, old| if old == from { to } else { old })
+    }
+
+    //Sets the six faces of the region to `block`.
+    pub fn outline(&mut self, region: &Region, block: u8) -> Result<usize, HistoryError> {
+        self.edit_region(region, |world_pos, old| {
+            if region.is_on_border(world_pos) {
+                block
+            } else {
+                old
+            }
+        })
+    }
+
+    //Clears solid blocks whose six neighbours are all solid, leaving a one block thick shell.
+    pub fn hollow(&mut self, region: &Region) -> Result<usize, HistoryError> {
+        let before = self.copy_region(&region.expanded(1));
+        let is_solid = |position: IVec3| before.get(position - region.min + 1) != 0;
+        self.edit_region(region, |world_pos, old| {
+            let enclosed = NEIGHBOUR_OFFSETS
+                .iter()
+                .all(|offset| is_solid(world_pos + *offset));
+            if old != 0 && enclosed { 0 } else { old }
+        })
+    }
+
+    //History keeps one run length encoded entry per chunk instead of a change per voxel.
+    fn edit_region(
+        &mut self,
+        region: &Region,
+        mut block_at: impl FnMut(IVec3, u8) -> u8,
+    ) -> Result<usize, HistoryError> {
+        let mut changed_positions = Vec::new();
+        self.begin_transaction();
+        let (chunk_min, chunk_max) = region.chunk_range();
+        for chunk_z in chunk_min.z..=chunk_max.z {
+            for chunk_y in chunk_min.y..=chunk_max.y {
+                for chunk_x in chunk_min.x..=chunk_max.x {
+                    let chunk_pos = ivec3(chunk_x, chunk_y, chunk_z);
+                    let Some(entry) = self.loaded_chunks.get_mut(&chunk_pos) else {
+                        continue;
+                    };
+                    let chunk_origin = chunk_pos * CHUNK_SIDE_SIZE;
+                    let (local_min, local_max) = region.local_range(chunk_pos);
+                    let created_chunk = entry.is_none();
+                    let mut edit = ChunkEdit::new(chunk_pos, local_min, local_max);
+                    let mut changed_min = IVec3::MAX;
+                    let mut changed_max = IVec3::MIN;
+                    for z in local_min.z..=local_max.z {
+                        for y in local_min.y..=local_max.y {
+                            for x in local_min.x..=local_max.x {
+                                let local_pos = ivec3(x, y, z);
+                                let world_pos = chunk_origin + local_pos;
+                                let old =
+                                    entry.as_ref().map_or(0, |chunk| chunk.get_block(local_pos));
+                                let new = block_at(world_pos, old);
+                                edit.push(old, new);
+                                if new == old {
+                                    continue;
+                                }
+                                entry
+                                    .get_or_insert_with(|| Chunk::empty(chunk_pos))
+                                    .set_block(local_pos, new);
+                                if let Some(changes) = &mut self.block_changes {
+                                    changes.push(BlockChange {
+                                        position: world_pos,
+                                        old,
+                                        new,
+                                    });
+                                }
+                                changed_min = changed_min.min(local_pos);
+                                changed_max = changed_max.max(local_pos);
+                                changed_positions.push(world_pos);
+                            }
+                        }
+                    }
+                    if changed_min.cmple(changed_max).all() {
+                        self.history.record_chunk(edit, created_chunk);
+                        self.mark_dirty(chunk_pos, changed_min, changed_max);
+                    }
+                }
+            }
+        }
+        self.blocks_edited(&changed_positions);
+        self.commit_transaction()?;
+        Ok(changed_positions.len())
+    }
+}
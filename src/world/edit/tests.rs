@@ -0,0 +1,165 @@
+#[cfg(test)]
+mod tests {
+    use crate::world::World;
+    use crate::world::edit::{Axis, Clipboard, Region};
+    use crate::world::tests::tests::create_test_world;
+    use glam::{IVec3, ivec3};
+
+    fn count_blocks(world: &World, region: &Region, block: u8) -> usize {
+        let mut count = 0;
+        for z in region.min.z..=region.max.z {
+            for y in region.min.y..=region.max.y {
+                for x in region.min.x..=region.max.x {
+                    if world.get_block(ivec3(x, y, z)) == Some(block) {
+                        count += 1;
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    fn numbered_clipboard(size: IVec3) -> Clipboard {
+        let mut clipboard = Clipboard::new(size);
+        let mut block = 1;
+        for z in 0..size.z {
+            for y in 0..size.y {
+                for x in 0..size.x {
+                    clipboard.set(ivec3(x, y, z), block);
+                    block += 1;
+                }
+            }
+        }
+        clipboard
+    }
+
+    #[test]
+    fn test_region_from_corners() {
+        let region = Region::new(ivec3(3, -2, 5), ivec3(-1, 4, 5));
+        assert_eq!(region.min, ivec3(-1, -2, 5));
+        assert_eq!(region.max, ivec3(3, 4, 5));
+        assert_eq!(region.size(), ivec3(5, 7, 1));
+        assert_eq!(region.volume(), 35);
+        assert!(region.contains(ivec3(0, 0, 5)));
+        assert!(!region.contains(ivec3(0, 0, 6)));
+    }
+
+    #[test]
+    fn test_fill_across_chunks() {
+        let mut world = create_test_world();
+        let region = Region::new(ivec3(-40, -3, 30), ivec3(40, 3, 95));
+        let changed = world.fill(&region, 2).unwrap();
+        assert_eq!(count_blocks(&world, &region, 2), region.volume());
+        assert!(changed <= region.volume());
+        assert_eq!(world.fill(&region, 2).unwrap(), 0);
+
+        let dirty = world.take_dirty_chunks();
+        assert!(dirty.contains(&ivec3(-2, -1, 2)));
+        assert!(dirty.contains(&ivec3(1, 0, 0)));
+        //Not touched by the region, but it borders a changed block.
+        assert!(dirty.contains(&ivec3(0, 0, 3)));
+        assert!(!dirty.contains(&ivec3(0, 2, 1)));
+    }
+
+    #[test]
+    fn test_fill_is_one_undo_step() {
+        let mut world = create_test_world();
+        let region = Region::new(ivec3(-10, -10, -10), ivec3(10, 10, 40));
+        let before = world.copy_region(&region);
+        world.fill(&region, 3).unwrap();
+        assert_eq!(world.history().undo_count(), 1);
+        assert!(world.undo());
+        assert_eq!(world.copy_region(&region), before);
+    }
+
+    #[test]
+    fn test_replace() {
+        let mut world = create_test_world();
+        let region = Region::new(ivec3(-20, -20, -5), ivec3(20, 20, 5));
+        let stone = count_blocks(&world, &region, 1);
+        let air = count_blocks(&world, &region, 0);
+        assert_eq!(world.replace(&region, 1, 4).unwrap(), stone);
+        assert_eq!(count_blocks(&world, &region, 4), stone);
+        assert_eq!(count_blocks(&world, &region, 0), air);
+    }
+
+    #[test]
+    fn test_copy_paste_across_chunks() {
+        let mut world = create_test_world();
+        let source = Region::new(ivec3(-5, -5, -5), ivec3(5, 5, 5));
+        let clipboard = world.copy_region(&source);
+        assert_eq!(clipboard.size(), ivec3(11, 11, 11));
+
+        let target = ivec3(28, 28, 60);
+        world.paste(&clipboard, target, true).unwrap();
+        let pasted = Region::new(target, target + clipboard.size() - 1);
+        assert_eq!(world.copy_region(&pasted), clipboard);
+    }
+
+    #[test]
+    fn test_paste_skips_air() {
+        let mut world = create_test_world();
+        let region = Region::new(ivec3(0, 0, 40), ivec3(2, 0, 40));
+        world.fill(&region, 5).unwrap();
+        let mut clipboard = Clipboard::new(ivec3(3, 1, 1));
+        clipboard.set(ivec3(1, 0, 0), 6);
+
+        assert_eq!(world.paste(&clipboard, region.min, false).unwrap(), 1);
+        assert_eq!(world.get_block(ivec3(0, 0, 40)), Some(5));
+        assert_eq!(world.get_block(ivec3(1, 0, 40)), Some(6));
+        assert_eq!(world.paste(&clipboard, region.min, true).unwrap(), 2);
+        assert_eq!(world.get_block(ivec3(0, 0, 40)), Some(0));
+    }
+
+    #[test]
+    fn test_rotation() {
+        let clipboard = numbered_clipboard(ivec3(3, 2, 2));
+        let rotated = clipboard.rotated(1);
+        assert_eq!(rotated.size(), ivec3(2, 3, 2));
+        //+x turns into +y.
+        assert_eq!(rotated.get(ivec3(1, 0, 0)), clipboard.get(ivec3(0, 0, 0)));
+        assert_eq!(rotated.get(ivec3(1, 1, 0)), clipboard.get(ivec3(1, 0, 0)));
+        assert_eq!(rotated.get(ivec3(0, 2, 1)), clipboard.get(ivec3(2, 1, 1)));
+
+        assert_eq!(clipboard.rotated(4), clipboard);
+        assert_eq!(clipboard.rotated(-1), clipboard.rotated(3));
+        assert_eq!(clipboard.rotated(1).rotated(1), clipboard.rotated(2));
+        assert_eq!(rotated.rotated(3), clipboard);
+    }
+
+    #[test]
+    fn test_mirror() {
+        let clipboard = numbered_clipboard(ivec3(3, 2, 4));
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            assert_eq!(clipboard.mirrored(axis).mirrored(axis), clipboard);
+        }
+        let mirrored = clipboard.mirrored(Axis::Z);
+        assert_eq!(mirrored.get(ivec3(2, 1, 0)), clipboard.get(ivec3(2, 1, 3)));
+        //Mirroring both horizontal axes is half a turn.
+        assert_eq!(
+            clipboard.mirrored(Axis::X).mirrored(Axis::Y),
+            clipboard.rotated(2)
+        );
+    }
+
+    #[test]
+    fn test_outline() {
+        let mut world = create_test_world();
+        let region = Region::new(ivec3(30, 30, 40), ivec3(34, 34, 44));
+        assert_eq!(world.outline(&region, 2).unwrap(), 125 - 27);
+        assert_eq!(world.get_block(ivec3(32, 32, 42)), Some(0));
+        assert_eq!(world.get_block(ivec3(30, 32, 42)), Some(2));
+    }
+
+    #[test]
+    fn test_hollow() {
+        let mut world = create_test_world();
+        let cube = Region::new(ivec3(30, 30, 40), ivec3(34, 34, 44));
+        world.fill(&cube, 2).unwrap();
+        //Selecting more than the cube keeps the outer faces.
+        assert_eq!(world.hollow(&cube.expanded(2)).unwrap(), 27);
+        assert_eq!(count_blocks(&world, &cube, 2), 125 - 27);
+        //The shell is not enclosed any more.
+        assert_eq!(world.hollow(&cube).unwrap(), 0);
+    }
+}
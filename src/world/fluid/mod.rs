@@ -0,0 +1,180 @@
+mod tests;
+
+use crate::world::block_registry::AIR;
+use crate::world::chunk::Chunk;
+use crate::world::{NEIGHBOUR_OFFSETS, World};
+use glam::IVec3;
+use std::collections::{HashMap, HashSet};
+
+//Level of source blocks. Flowing fluid has lower levels and disappears at 0.
+pub const MAX_FLUID_LEVEL: u8 = 8;
+//Level of fluid falling into the voxel below, which then spreads like a fresh flow.
+const FALLING_LEVEL: u8 = MAX_FLUID_LEVEL - 1;
+
+const HORIZONTAL_OFFSETS: [IVec3; 4] = [IVec3::NEG_X, IVec3::X, IVec3::NEG_Y, IVec3::Y];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fluid {
+    //Level lost with every voxel of horizontal spread.
+    pub decay: u8,
+    //Ticks between a change next to the fluid and its reaction.
+    pub delay: u64,
+}
+
+//Levels of flowing fluid voxels. Fluid blocks without a level are sources, so fluids placed by
+//edits, undo or redo are always sources.
+#[derive(Default)]
+pub(crate) struct FluidState {
+    levels: HashMap<IVec3, u8>,
+}
+
+impl FluidState {
+    //Forgets the levels inside chunks that were unloaded.
+    pub(crate) fn drop_chunks(&mut self, chunk_positions: &HashSet<IVec3>) {
+        self.levels.retain(|world_pos, _| {
+            !chunk_positions.contains(&Chunk::split_world_pos(*world_pos).0)
+        });
+    }
+}
+
+//Cellular fluids: every update makes a fluid voxel take the level its neighbours feed it, then
+//flow down, or sideways with `decay` less if it rests on something. Updates are scheduled block
+//ticks of the fluid blocks, `delay` ticks after a change next to them. Fluid never flows into
+//unloaded chunks.
+impl World {
+    pub(crate) fn fluid(&self, block: u8) -> Option<Fluid> {
+        self.registry.get(block).and_then(|block| block.fluid)
+    }
+
+    //0 if the voxel holds no fluid.
+    pub fn fluid_level(&self, world_pos: IVec3) -> u8 {
+        match self.get_block(world_pos) {
+            Some(block) if self.fluid(block).is_some() => self
+                .fluids
+                .levels
+                .get(&world_pos)
+                .copied()
+                .unwrap_or(MAX_FLUID_LEVEL),
+            _ => 0,
+        }
+    }
+
+    //Fluids at `positions` were placed by an edit and become sources, and the fluids around them
+    //react to the change.
+    pub(crate) fn fluids_edited(&mut self, positions: &[IVec3]) {
+        for position in positions {
+            self.fluids.levels.remove(position);
+        }
+        self.schedule_fluid_updates(positions);
+    }
+
+    //Schedules updates for the fluids at and next to `positions`, whose blocks changed.
+    fn schedule_fluid_updates(&mut self, positions: &[IVec3]) {
+        for position in positions {
+            self.schedule_fluid_update(*position);
+            for offset in NEIGHBOUR_OFFSETS {
+                self.schedule_fluid_update(position + offset);
+            }
+        }
+    }
+
+    fn schedule_fluid_update(&mut self, world_pos: IVec3) {
+        let Some(fluid) = self
+            .get_block(world_pos)
+            .and_then(|block| self.fluid(block))
+        else {
+            return;
+        };
+        self.schedule_block_tick(world_pos, fluid.delay);
+    }
+
+    pub(crate) fn update_fluid(&mut self, world_pos: IVec3) {
+        let Some(block) = self.get_block(world_pos) else {
+            return;
+        };
+        let Some(fluid) = self.fluid(block) else {
+            return;
+        };
+        let mut level = self.fluid_level(world_pos);
+        if level < MAX_FLUID_LEVEL {
+            let fed = self.fed_level(world_pos, block, fluid);
+            if fed == 0 {
+                self.set_fluid(world_pos, AIR, 0);
+                return;
+            }
+            if fed != level {
+                self.set_fluid(world_pos, block, fed);
+                level = fed;
+            }
+        }
+
+        let below = world_pos + IVec3::NEG_Z;
+        match self.get_block(below) {
+            Some(AIR) => {
+                self.set_fluid(below, block, FALLING_LEVEL);
+                return;
+            }
+            Some(below_block) if below_block == block => {
+                if self.fluid_level(below) < FALLING_LEVEL {
+                    self.set_fluid(below, block, FALLING_LEVEL);
+                }
+                return;
+            }
+            _ => {}
+        }
+        let spread = level.saturating_sub(fluid.decay);
+        if spread == 0 {
+            return;
+        }
+        for offset in HORIZONTAL_OFFSETS {
+            let neighbour = world_pos + offset;
+            match self.get_block(neighbour) {
+                Some(AIR) => self.set_fluid(neighbour, block, spread),
+                Some(neighbour_block)
+                    if neighbour_block == block && self.fluid_level(neighbour) < spread =>
+                {
+                    self.set_fluid(neighbour, block, spread)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    //Level a flowing voxel gets from the same fluid above it or beside it. Only fluid resting on
+    //something spreads sideways.
+    fn fed_level(&self, world_pos: IVec3, block: u8, fluid: Fluid) -> u8 {
+        if self.get_block(world_pos + IVec3::Z) == Some(block) {
+            return FALLING_LEVEL;
+        }
+        HORIZONTAL_OFFSETS
+            .iter()
+            .map(|offset| world_pos + offset)
+            .filter(|neighbour| self.get_block(*neighbour) == Some(block))
+            .filter(|neighbour| self.rests_on_ground(*neighbour, block))
+            .map(|neighbour| self.fluid_level(neighbour).saturating_sub(fluid.decay))
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn rests_on_ground(&self, world_pos: IVec3, block: u8) -> bool {
+        self.get_block(world_pos + IVec3::NEG_Z)
+            .is_none_or(|below| below != AIR && below != block)
+    }
+
+    //Writes a fluid change without the edit history and reacts to it like to any block change.
+    pub(crate) fn set_fluid(&mut self, world_pos: IVec3, block: u8, level: u8) {
+        let Some((old, _)) = self.write_block(world_pos, block) else {
+            return;
+        };
+        if block == AIR || level == MAX_FLUID_LEVEL {
+            self.fluids.levels.remove(&world_pos);
+        } else {
+            self.fluids.levels.insert(world_pos, level);
+        }
+        if old != block {
+            self.relight(&[world_pos]);
+        }
+        self.schedule_fluid_updates(&[world_pos]);
+        self.schedule_falls(&[world_pos]);
+    }
+}
@@ -0,0 +1,151 @@
+#[cfg(test)]
+mod tests {
+    use crate::world::World;
+    use crate::world::block_registry::{AIR, LAVA, STONE, WATER};
+    use crate::world::block_tick::SCHEDULED_TICKS_PER_TICK;
+    use crate::world::edit::Region;
+    use crate::world::fluid::MAX_FLUID_LEVEL;
+    use crate::world::tests::tests::create_test_world_with_floor;
+    use glam::{IVec3, ivec3};
+
+    fn run_ticks(world: &mut World, ticks: u32) {
+        for _ in 0..ticks {
+            world.tick();
+        }
+    }
+
+    fn run_until_settled(world: &mut World) {
+        for _ in 0..10_000 {
+            if world.pending_block_ticks() == 0 {
+                return;
+            }
+            world.tick();
+        }
+        panic!("Fluids did not settle");
+    }
+
+    #[test]
+    fn test_source_spreads_on_floor() {
+        let mut world = create_test_world_with_floor();
+        let source = ivec3(15, 15, 65);
+        world.set_block(source, WATER);
+        run_ticks(&mut world, 4);
+        assert_eq!(world.get_block(source + IVec3::X), Some(AIR));
+        run_until_settled(&mut world);
+
+        assert_eq!(world.fluid_level(source), MAX_FLUID_LEVEL);
+        for distance in 1..8 {
+            let level = MAX_FLUID_LEVEL - distance as u8;
+            assert_eq!(world.fluid_level(source + IVec3::X * distance), level);
+            assert_eq!(world.fluid_level(source - IVec3::Y * distance), level);
+        }
+        assert_eq!(world.get_block(source + IVec3::X * 8), Some(AIR));
+        assert_eq!(world.fluid_level(source + ivec3(3, 2, 0)), 3);
+        assert_eq!(world.get_block(source + ivec3(4, 4, 0)), Some(AIR));
+        //Flowing fluid stays on the floor.
+        assert_eq!(world.get_block(source + ivec3(1, 0, 1)), Some(AIR));
+        assert!(world.take_dirty_chunks().contains(&ivec3(0, 0, 2)));
+    }
+
+    #[test]
+    fn test_fluid_falls_and_spreads_below() {
+        let mut world = create_test_world_with_floor();
+        world
+            .fill(&Region::new(ivec3(10, 10, 70), ivec3(12, 12, 70)), STONE)
+            .unwrap();
+        let source = ivec3(12, 11, 71);
+        world.set_block(source, WATER);
+        run_until_settled(&mut world);
+
+        //It runs off the platform edge and falls to the floor.
+        assert_eq!(world.fluid_level(ivec3(13, 11, 71)), 7);
+        for z in 65..71 {
+            assert_eq!(world.get_block(ivec3(13, 11, z)), Some(WATER), "z {}", z);
+        }
+        assert_eq!(world.get_block(ivec3(14, 11, 70)), Some(AIR));
+        //The falling column lands and spreads with one level less than a source would.
+        assert_eq!(world.fluid_level(ivec3(13, 11, 65)), 7);
+        assert_eq!(world.fluid_level(ivec3(19, 11, 65)), 1);
+        assert_eq!(world.get_block(ivec3(20, 11, 65)), Some(AIR));
+    }
+
+    #[test]
+    fn test_removing_source_drains() {
+        let mut world = create_test_world_with_floor();
+        let source = ivec3(15, 15, 65);
+        world.set_block(source, WATER);
+        run_until_settled(&mut world);
+        assert_eq!(world.fluid_level(source + IVec3::X * 3), 5);
+
+        world.set_block(source, AIR);
+        run_until_settled(&mut world);
+        let above_floor = world.copy_region(&Region::new(ivec3(0, 0, 65), ivec3(30, 30, 66)));
+        assert!(above_floor.blocks().iter().all(|block| *block == AIR));
+    }
+
+    #[test]
+    fn test_walls_contain_fluid() {
+        let mut world = create_test_world_with_floor();
+        world
+            .outline(&Region::new(ivec3(9, 9, 64), ivec3(12, 12, 66)), STONE)
+            .unwrap();
+        world
+            .fill(&Region::new(ivec3(10, 10, 66), ivec3(11, 11, 66)), AIR)
+            .unwrap();
+        world.set_block(ivec3(10, 10, 65), WATER);
+        run_until_settled(&mut world);
+        assert_eq!(world.fluid_level(ivec3(11, 11, 65)), 6);
+        assert_eq!(world.get_block(ivec3(13, 10, 65)), Some(AIR));
+        assert_eq!(world.get_block(ivec3(10, 10, 66)), Some(AIR));
+    }
+
+    #[test]
+    fn test_lava_is_slow_and_short() {
+        let mut world = create_test_world_with_floor();
+        let source = ivec3(15, 15, 65);
+        world.set_block(source, LAVA);
+        run_ticks(&mut world, 10);
+        assert_eq!(world.get_block(source + IVec3::X), Some(AIR));
+        run_until_settled(&mut world);
+        assert_eq!(world.fluid_level(source + IVec3::X), 6);
+        assert_eq!(world.fluid_level(source + IVec3::X * 3), 2);
+        assert_eq!(world.get_block(source + IVec3::X * 4), Some(AIR));
+        //Lava glows.
+        assert!(world.light(source + IVec3::Z, crate::world::light::LightChannel::Red) > 10);
+    }
+
+    #[test]
+    fn test_update_budget() {
+        let mut world = create_test_world_with_floor();
+        //A walled pool of sources, whose updates change nothing.
+        world
+            .fill(&Region::new(ivec3(0, 0, 65), ivec3(30, 30, 65)), STONE)
+            .unwrap();
+        world
+            .fill(&Region::new(ivec3(1, 1, 65), ivec3(29, 29, 65)), WATER)
+            .unwrap();
+        let scheduled = world.pending_block_ticks();
+        assert!(scheduled > SCHEDULED_TICKS_PER_TICK);
+        run_ticks(&mut world, 5);
+        assert_eq!(
+            world.pending_block_ticks(),
+            scheduled - SCHEDULED_TICKS_PER_TICK
+        );
+        run_until_settled(&mut world);
+    }
+
+    #[test]
+    fn test_stops_at_unloaded_chunks() {
+        let mut world = create_test_world_with_floor();
+        //Chunk x = 4 is the last loaded one along x at this height.
+        let edge = ivec3(159, 3, 70);
+        assert!(world.get_block(edge + IVec3::X).is_none());
+        world
+            .fill(&Region::new(ivec3(150, 0, 69), ivec3(159, 6, 69)), STONE)
+            .unwrap();
+        world.set_block(edge - IVec3::X * 2, WATER);
+        run_until_settled(&mut world);
+        assert_eq!(world.fluid_level(edge), 6);
+        assert!(world.get_block(edge + IVec3::X).is_none());
+    }
+}
@@ -0,0 +1,50 @@
+mod tests;
+
+use crate::world::World;
+use crate::world::block_registry::AIR;
+use glam::IVec3;
+
+//Ticks a falling block hangs in a voxel before it drops into the one below.
+pub const FALL_DELAY: u64 = 2;
+
+//Blocks flagged `falls` in the registry drop one voxel at a time while there is air or fluid
+//below them, and land on the first block that is neither. Fluid they fall into is displaced: it
+//swaps places with the falling block and keeps its level.
+//Every change schedules a check of the blocks at and above it, so a column whose support is
+//removed falls block by block. Blocks never fall into unloaded chunks.
+impl World {
+    fn falls(&self, block: u8) -> bool {
+        self.registry.get(block).is_some_and(|block| block.falls)
+    }
+
+    //Schedules a fall for the falling blocks at and above `positions`, whose blocks changed.
+    pub(crate) fn schedule_falls(&mut self, positions: &[IVec3]) {
+        for position in positions {
+            for world_pos in [*position, position + IVec3::Z] {
+                if self
+                    .get_block(world_pos)
+                    .is_some_and(|block| self.falls(block))
+                {
+                    self.schedule_block_tick(world_pos, FALL_DELAY);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn update_falling(&mut self, world_pos: IVec3) {
+        let Some(block) = self.get_block(world_pos) else {
+            return;
+        };
+        let below = world_pos + IVec3::NEG_Z;
+        let Some(below_block) = self.get_block(below) else {
+            return;
+        };
+        if below_block != AIR && self.fluid(below_block).is_none() {
+            return;
+        }
+        let below_level = self.fluid_level(below);
+        self.write_block(below, block);
+        self.blocks_edited(&[below]);
+        self.set_fluid(world_pos, below_block, below_level);
+    }
+}
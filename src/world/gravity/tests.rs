@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod tests {
+    use crate::world::World;
+    use crate::world::block_registry::{AIR, SAND, STONE, WATER};
+    use crate::world::edit::Region;
+    use crate::world::fluid::MAX_FLUID_LEVEL;
+    use crate::world::gravity::FALL_DELAY;
+    use crate::world::tests::tests::create_test_world_with_floor;
+    use glam::ivec3;
+
+    fn run_until_settled(world: &mut World) {
+        for _ in 0..10_000 {
+            if world.pending_block_ticks() == 0 {
+                return;
+            }
+            world.tick();
+        }
+        panic!("Blocks did not settle");
+    }
+
+    #[test]
+    fn test_sand_falls_to_the_floor() {
+        let mut world = create_test_world_with_floor();
+        world.set_block(ivec3(5, 5, 70), SAND);
+        for _ in 0..FALL_DELAY {
+            world.tick();
+        }
+        assert_eq!(world.get_block(ivec3(5, 5, 70)), Some(AIR));
+        assert_eq!(world.get_block(ivec3(5, 5, 69)), Some(SAND));
+        run_until_settled(&mut world);
+        assert_eq!(world.get_block(ivec3(5, 5, 65)), Some(SAND));
+        assert_eq!(world.get_block(ivec3(5, 5, 66)), Some(AIR));
+        //Other blocks stay where they are placed.
+        world.set_block(ivec3(5, 5, 70), STONE);
+        run_until_settled(&mut world);
+        assert_eq!(world.get_block(ivec3(5, 5, 70)), Some(STONE));
+    }
+
+    #[test]
+    fn test_column_cascades_when_support_is_removed() {
+        let mut world = create_test_world_with_floor();
+        world.set_block(ivec3(5, 5, 65), STONE);
+        world
+            .fill(&Region::new(ivec3(5, 5, 66), ivec3(5, 5, 69)), SAND)
+            .unwrap();
+        run_until_settled(&mut world);
+        assert_eq!(world.get_block(ivec3(5, 5, 69)), Some(SAND));
+
+        world.set_block(ivec3(5, 5, 65), AIR);
+        run_until_settled(&mut world);
+        for z in 65..69 {
+            assert_eq!(world.get_block(ivec3(5, 5, z)), Some(SAND), "z {}", z);
+        }
+        assert_eq!(world.get_block(ivec3(5, 5, 69)), Some(AIR));
+    }
+
+    #[test]
+    fn test_sand_sinks_through_water() {
+        let mut world = create_test_world_with_floor();
+        //A well two water sources deep with room above them.
+        world
+            .fill(&Region::new(ivec3(4, 4, 65), ivec3(6, 6, 68)), STONE)
+            .unwrap();
+        world
+            .fill(&Region::new(ivec3(5, 5, 67), ivec3(5, 5, 68)), AIR)
+            .unwrap();
+        world
+            .fill(&Region::new(ivec3(5, 5, 65), ivec3(5, 5, 66)), WATER)
+            .unwrap();
+        run_until_settled(&mut world);
+        world.set_block(ivec3(5, 5, 70), SAND);
+        run_until_settled(&mut world);
+        //The water moved up instead of disappearing.
+        assert_eq!(world.get_block(ivec3(5, 5, 65)), Some(SAND));
+        assert_eq!(world.get_block(ivec3(5, 5, 66)), Some(WATER));
+        assert_eq!(world.get_block(ivec3(5, 5, 67)), Some(WATER));
+        assert_eq!(world.get_block(ivec3(5, 5, 68)), Some(AIR));
+        assert_eq!(world.fluid_level(ivec3(5, 5, 67)), MAX_FLUID_LEVEL);
+    }
+
+    #[test]
+    fn test_undo_and_redo_move_support() {
+        let mut world = create_test_world_with_floor();
+        world.set_block(ivec3(5, 5, 65), STONE);
+        world.set_block(ivec3(5, 5, 66), SAND);
+        world.set_block(ivec3(5, 5, 65), AIR);
+        //The support is back before the sand's fall is due.
+        assert!(world.undo());
+        run_until_settled(&mut world);
+        assert_eq!(world.get_block(ivec3(5, 5, 66)), Some(SAND));
+
+        assert!(world.redo());
+        run_until_settled(&mut world);
+        assert_eq!(world.get_block(ivec3(5, 5, 65)), Some(SAND));
+        assert_eq!(world.get_block(ivec3(5, 5, 66)), Some(AIR));
+    }
+}
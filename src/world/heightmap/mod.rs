@@ -0,0 +1,231 @@
+mod tests;
+
+use crate::world::block_registry::BlockRegistry;
+use crate::world::chunk::{MAX_HEIGHT, MIN_HEIGHT};
+use crate::world::terrain::LOWEST_SURFACE;
+use glam::IVec2;
+use png::{ColorType, Decoder, Transformations};
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeightmapOptions {
+    //Voxels between the heights of a black and a white pixel.
+    pub scale: f32,
+    //Height of a black pixel.
+    pub offset: i32,
+    //Side of the square of world columns each pixel covers.
+    pub pixel_size: i32,
+    //World column at the corner of the first pixel. Image rows go along y.
+    pub origin: IVec2,
+    //Repeats the image in every direction. Otherwise columns outside of it are as high as a black
+    //pixel.
+    pub tile: bool,
+}
+
+impl Default for HeightmapOptions {
+    fn default() -> Self {
+        Self {
+            scale: (MAX_HEIGHT - MIN_HEIGHT) as f32,
+            offset: MIN_HEIGHT,
+            pixel_size: 1,
+            origin: IVec2::ZERO,
+            tile: false,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum HeightmapError {
+    Decode(String),
+    UnsupportedColorType(String),
+    Empty,
+    SizeMismatch { heights: IVec2, materials: IVec2 },
+    InvalidPixelSize(i32),
+}
+
+impl Display for HeightmapError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HeightmapError::Decode(err) => write!(f, "Could not decode PNG: {}", err),
+            HeightmapError::UnsupportedColorType(color_type) => {
+                write!(f, "Unsupported PNG color type {}", color_type)
+            }
+            HeightmapError::Empty => write!(f, "Heightmap has no pixels"),
+            HeightmapError::SizeMismatch { heights, materials } => write!(
+                f,
+                "Material image is {} but the heightmap is {}",
+                materials, heights
+            ),
+            HeightmapError::InvalidPixelSize(size) => {
+                write!(f, "Pixel size {} is not positive", size)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HeightmapError {}
+
+//Column heights read from a grayscale image, brighter is higher, and optionally the surface block
+//of each column from a second image of the same size painted in block colors.
+pub struct Heightmap {
+    size: IVec2,
+    heights: Vec<i32>,
+    //Of the whole map, outside of the image included.
+    max_height: i32,
+    materials: Option<Vec<Option<u8>>>,
+    options: HeightmapOptions,
+}
+
+impl Heightmap {
+    //Color images are read by their luminance. In the material image every opaque pixel picks the
+    //block of `registry` with the closest color, transparent pixels keep the biome's surface block.
+    //Heights are kept above LOWEST_SURFACE, where the generator places its surface blocks, but can
+    //go above MAX_HEIGHT.
+    pub fn parse(
+        heights: &[u8],
+        materials: Option<&[u8]>,
+        registry: &BlockRegistry,
+        options: HeightmapOptions,
+    ) -> Result<Self, HeightmapError> {
+        if options.pixel_size < 1 {
+            return Err(HeightmapError::InvalidPixelSize(options.pixel_size));
+        }
+        let (size, pixels) = decode(heights)?;
+        if pixels.is_empty() {
+            return Err(HeightmapError::Empty);
+        }
+        let heights: Vec<i32> = pixels
+            .iter()
+            .map(|[r, g, b, _]| {
+                let luminance = 0.299 * *r as f32 + 0.587 * *g as f32 + 0.114 * *b as f32;
+                let height = options
+                    .offset
+                    .saturating_add((luminance / 255.0 * options.scale).round() as i32);
+                height.max(LOWEST_SURFACE + 1)
+            })
+            .collect();
+        let materials = match materials {
+            Some(materials) => {
+                let (materials_size, pixels) = decode(materials)?;
+                if materials_size != size {
+                    return Err(HeightmapError::SizeMismatch {
+                        heights: size,
+                        materials: materials_size,
+                    });
+                }
+                Some(
+                    pixels
+                        .into_iter()
+                        .map(|color| match color[3] {
+                            0 => None,
+                            _ => registry.closest_color(color),
+                        })
+                        .collect(),
+                )
+            }
+            None => None,
+        };
+        let max_height = heights
+            .iter()
+            .copied()
+            .fold(outside_height(&options), i32::max);
+        Ok(Self {
+            size,
+            heights,
+            max_height,
+            materials,
+            options,
+        })
+    }
+
+    pub fn load(
+        path: impl AsRef<Path>,
+        materials_path: Option<&Path>,
+        registry: &BlockRegistry,
+        options: HeightmapOptions,
+    ) -> std::io::Result<Self> {
+        let heights = std::fs::read(path)?;
+        let materials = materials_path.map(std::fs::read).transpose()?;
+        Self::parse(&heights, materials.as_deref(), registry, options)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+    }
+
+    //Width and height of the image in pixels.
+    pub fn size(&self) -> IVec2 {
+        self.size
+    }
+
+    //Height of the highest solid voxel of the world column (x, y).
+    pub(crate) fn height(&self, x: i32, y: i32) -> i32 {
+        match self.pixel_index(x, y) {
+            Some(index) => self.heights[index],
+            None => outside_height(&self.options),
+        }
+    }
+
+    //Height of the highest column.
+    pub(crate) fn max_height(&self) -> i32 {
+        self.max_height
+    }
+
+    //Surface block painted for the world column (x, y), if any.
+    pub(crate) fn material(&self, x: i32, y: i32) -> Option<u8> {
+        let materials = self.materials.as_ref()?;
+        materials[self.pixel_index(x, y)?]
+    }
+
+    fn pixel_index(&self, x: i32, y: i32) -> Option<usize> {
+        let pixel = (IVec2::new(x, y) - self.options.origin)
+            .div_euclid(IVec2::splat(self.options.pixel_size));
+        let pixel = if self.options.tile {
+            pixel.rem_euclid(self.size)
+        } else if pixel.cmplt(IVec2::ZERO).any() || pixel.cmpge(self.size).any() {
+            return None;
+        } else {
+            pixel
+        };
+        Some((pixel.x + pixel.y * self.size.x) as usize)
+    }
+}
+
+//Size and 8 bit RGBA pixels of a PNG of any color type and bit depth.
+fn decode(bytes: &[u8]) -> Result<(IVec2, Vec<[u8; 4]>), HeightmapError> {
+    let mut decoder = Decoder::new(bytes);
+    decoder.set_transformations(Transformations::normalize_to_color8());
+    let mut reader = decoder
+        .read_info()
+        .map_err(|err| HeightmapError::Decode(err.to_string()))?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buffer)
+        .map_err(|err| HeightmapError::Decode(err.to_string()))?;
+    let channels = match info.color_type {
+        ColorType::Grayscale => 1,
+        ColorType::GrayscaleAlpha => 2,
+        ColorType::Rgb => 3,
+        ColorType::Rgba => 4,
+        ColorType::Indexed => {
+            return Err(HeightmapError::UnsupportedColorType(format!(
+                "{:?}",
+                info.color_type
+            )));
+        }
+    };
+    let pixels = buffer[..info.buffer_size()]
+        .chunks_exact(channels)
+        .map(|pixel| match pixel {
+            [gray] => [*gray, *gray, *gray, u8::MAX],
+            [gray, alpha] => [*gray, *gray, *gray, *alpha],
+            [r, g, b] => [*r, *g, *b, u8::MAX],
+            [r, g, b, a] => [*r, *g, *b, *a],
+            _ => unreachable!(),
+        })
+        .collect();
+    Ok((IVec2::new(info.width as i32, info.height as i32), pixels))
+}
+
+//Columns outside of the image are as high as black.
+fn outside_height(options: &HeightmapOptions) -> i32 {
+    options.offset.max(LOWEST_SURFACE + 1)
+}
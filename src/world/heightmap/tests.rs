@@ -0,0 +1,265 @@
+#[cfg(test)]
+mod tests {
+    use crate::world::World;
+    use crate::world::block_registry::{AIR, BlockRegistry, SAND, SNOW, STONE};
+    use crate::world::chunk::{CHUNK_SIDE_SIZE, CHUNK_SIZE, Chunk};
+    use crate::world::heightmap::{Heightmap, HeightmapError, HeightmapOptions};
+    use crate::world::seed::DEFAULT_SEED;
+    use crate::world::terrain::Terrain;
+    use glam::{IVec2, ivec2, ivec3};
+    use png::ColorType;
+    use std::io::ErrorKind;
+
+    fn encode(size: IVec2, color_type: ColorType, data: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, size.x as u32, size.y as u32);
+        encoder.set_color(color_type);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(data).unwrap();
+        writer.finish().unwrap();
+        bytes
+    }
+
+    fn options(scale: f32, offset: i32) -> HeightmapOptions {
+        HeightmapOptions {
+            scale,
+            offset,
+            ..HeightmapOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_pixels_map_to_heights() {
+        let registry = BlockRegistry::default();
+        let png = encode(ivec2(2, 2), ColorType::Grayscale, &[0, 255, 128, 51]);
+        let heightmap = Heightmap::parse(&png, None, &registry, options(20.0, 2)).unwrap();
+        assert_eq!(heightmap.size(), ivec2(2, 2));
+        assert_eq!(heightmap.height(0, 0), 2);
+        assert_eq!(heightmap.height(1, 0), 22);
+        assert_eq!(heightmap.height(0, 1), 12);
+        assert_eq!(heightmap.height(1, 1), 6);
+        //Outside of the image everything is as high as black.
+        assert_eq!(heightmap.height(2, 0), 2);
+        assert_eq!(heightmap.height(-1, -1), 2);
+        //Color images are read by their luminance and heights can go above MAX_HEIGHT.
+        let png = encode(ivec2(1, 1), ColorType::Rgb, &[255, 255, 255]);
+        let heightmap = Heightmap::parse(&png, None, &registry, options(100.0, 0)).unwrap();
+        assert_eq!(heightmap.height(0, 0), 100);
+    }
+
+    #[test]
+    fn test_tall_heightmap_fills_high_chunks() {
+        let registry = BlockRegistry::default();
+        let png = encode(ivec2(1, 1), ColorType::Grayscale, &[255]);
+        let heightmap = Heightmap::parse(
+            &png,
+            None,
+            &registry,
+            HeightmapOptions {
+                tile: true,
+                ..options(50.0, 0)
+            },
+        )
+        .unwrap();
+        let terrain = Terrain::with_heightmap(DEFAULT_SEED, heightmap);
+        let mut texture = vec![AIR; CHUNK_SIZE as usize];
+        assert!(terrain.fill(ivec3(0, 0, 1), &mut texture));
+        let surface = 50 - CHUNK_SIDE_SIZE;
+        let biome = terrain.biome(3, 4);
+        assert_eq!(
+            texture[Chunk::texture_index(ivec3(3, 4, surface))],
+            biome.surface_block()
+        );
+        assert_eq!(texture[Chunk::texture_index(ivec3(3, 4, surface + 1))], AIR);
+        assert_eq!(texture[Chunk::texture_index(ivec3(3, 4, 0))], STONE);
+        texture.fill(AIR);
+        assert!(!terrain.fill(ivec3(0, 0, 2), &mut texture));
+    }
+
+    #[test]
+    fn test_pixel_size_origin_and_tiling() {
+        let registry = BlockRegistry::default();
+        let png = encode(ivec2(2, 1), ColorType::Grayscale, &[0, 255]);
+        let heightmap = Heightmap::parse(
+            &png,
+            None,
+            &registry,
+            HeightmapOptions {
+                scale: 10.0,
+                offset: 1,
+                pixel_size: 4,
+                origin: ivec2(-4, 10),
+                tile: true,
+            },
+        )
+        .unwrap();
+        assert_eq!(heightmap.height(-4, 10), 1);
+        assert_eq!(heightmap.height(-1, 13), 1);
+        assert_eq!(heightmap.height(0, 10), 11);
+        assert_eq!(heightmap.height(3, 13), 11);
+        //The image repeats every 8 columns along x and every 4 along y.
+        assert_eq!(heightmap.height(4, 10), 1);
+        assert_eq!(heightmap.height(-8, 6), 11);
+        assert_eq!(heightmap.height(-12, 2), 1);
+    }
+
+    #[test]
+    fn test_materials() {
+        let registry = BlockRegistry::default();
+        let heights = encode(ivec2(3, 1), ColorType::Grayscale, &[10, 10, 10]);
+        let sand = registry.get(SAND).unwrap().color;
+        let snow = registry.get(SNOW).unwrap().color;
+        #[rustfmt::skip]
+        let materials = encode(ivec2(3, 1), ColorType::Rgba, &[
+            sand[0], sand[1], sand[2], 255,
+            //Close to snow is snow.
+            snow[0] - 4, snow[1], snow[2] + 2, 255,
+            255, 255, 255, 0,
+        ]);
+        let heightmap =
+            Heightmap::parse(&heights, Some(&materials), &registry, options(10.0, 1)).unwrap();
+        assert_eq!(heightmap.material(0, 0), Some(SAND));
+        assert_eq!(heightmap.material(1, 0), Some(SNOW));
+        assert_eq!(heightmap.material(2, 0), None);
+        assert_eq!(heightmap.material(3, 0), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let registry = BlockRegistry::default();
+        let heights = encode(ivec2(2, 2), ColorType::Grayscale, &[0; 4]);
+        let materials = encode(ivec2(1, 2), ColorType::Grayscale, &[0; 2]);
+        assert!(matches!(
+            Heightmap::parse(b"not a png", None, &registry, HeightmapOptions::default()),
+            Err(HeightmapError::Decode(_))
+        ));
+        assert_eq!(
+            Heightmap::parse(
+                &heights,
+                Some(&materials),
+                &registry,
+                HeightmapOptions::default()
+            )
+            .err(),
+            Some(HeightmapError::SizeMismatch {
+                heights: ivec2(2, 2),
+                materials: ivec2(1, 2),
+            })
+        );
+        let options = HeightmapOptions {
+            pixel_size: 0,
+            ..HeightmapOptions::default()
+        };
+        assert_eq!(
+            Heightmap::parse(&heights, None, &registry, options).err(),
+            Some(HeightmapError::InvalidPixelSize(0))
+        );
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let registry = BlockRegistry::default();
+        let options = HeightmapOptions::default();
+        let path = std::env::temp_dir().join(format!("vxl_heightmap_{}.png", std::process::id()));
+        let missing = Heightmap::load(&path, None, &registry, options);
+        std::fs::write(&path, b"not a png").unwrap();
+        let invalid = Heightmap::load(&path, None, &registry, options);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(missing.err().unwrap().kind(), ErrorKind::NotFound);
+        let invalid = invalid.err().unwrap();
+        assert_eq!(invalid.kind(), ErrorKind::InvalidData);
+        assert!(matches!(
+            invalid.into_inner().unwrap().downcast_ref(),
+            Some(HeightmapError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn test_fill_follows_the_heightmap() {
+        let registry = BlockRegistry::default();
+        let size = ivec2(CHUNK_SIDE_SIZE, CHUNK_SIDE_SIZE);
+        let gray: Vec<u8> = (0..size.x * size.y).map(|i| (i * 7 % 256) as u8).collect();
+        let sand = registry.get(SAND).unwrap().color;
+        let materials: Vec<u8> = (0..size.x * size.y)
+            .flat_map(|i| match i % 3 {
+                0 => [sand[0], sand[1], sand[2], 255],
+                _ => [0; 4],
+            })
+            .collect();
+        let heightmap = Heightmap::parse(
+            &encode(size, ColorType::Grayscale, &gray),
+            Some(&encode(size, ColorType::Rgba, &materials)),
+            &registry,
+            options(20.0, -10),
+        )
+        .unwrap();
+        let terrain = Terrain::with_heightmap(DEFAULT_SEED, heightmap);
+        let mut chunks = [ivec3(0, 0, -1), ivec3(0, 0, 0)].map(|chunk_pos| {
+            let mut texture = vec![AIR; CHUNK_SIZE as usize];
+            assert!(terrain.fill(chunk_pos, &mut texture));
+            texture
+        });
+        let block = |z: i32, x: i32, y: i32| {
+            let (chunk_pos, local_pos) = Chunk::split_world_pos(ivec3(x, y, z));
+            chunks[(chunk_pos.z + 1) as usize][Chunk::texture_index(local_pos)]
+        };
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let height = terrain.surface_height(x, y);
+                assert_eq!(
+                    height,
+                    -10 + (gray[(x + y * size.x) as usize] as f32 / 255.0 * 20.0).round() as i32
+                );
+                assert_eq!(block(height + 1, x, y), AIR);
+                let biome = terrain.biome(x, y);
+                let surface = if (x + y * size.x) % 3 == 0 {
+                    SAND
+                } else {
+                    biome.surface_block()
+                };
+                assert_eq!(block(height, x, y), surface);
+                assert_eq!(block(height - 1, x, y), biome.subsurface_block());
+                assert_eq!(block(height - 4, x, y), STONE);
+            }
+        }
+        //Deep under the heightmap everything is stone.
+        chunks[0].fill(AIR);
+        assert!(terrain.fill(ivec3(0, 0, -3), &mut chunks[0]));
+        assert!(chunks[0].iter().all(|block| *block == STONE));
+    }
+
+    #[test]
+    fn test_world_from_heightmap() {
+        let registry = BlockRegistry::default();
+        //A ramp climbing along x, tiled so it covers the whole world.
+        let ramp: Vec<u8> = (0..64).map(|x| (x * 4) as u8).collect();
+        let heightmap = Heightmap::parse(
+            &encode(ivec2(64, 1), ColorType::Grayscale, &ramp),
+            None,
+            &registry,
+            HeightmapOptions {
+                tile: true,
+                ..options(16.0, 4)
+            },
+        )
+        .unwrap();
+        let world = World::with_heightmap(2, DEFAULT_SEED, heightmap).unwrap();
+        let mut on_the_surface = 0;
+        for x in -32i32..32 {
+            for y in -32..32 {
+                let height =
+                    4 + (ramp[x.rem_euclid(64) as usize] as f32 / 255.0 * 16.0).round() as i32;
+                assert_ne!(world.get_block(ivec3(x, y, height - 1)), Some(AIR));
+                //Plants, trees and structures can stand on top.
+                if world.get_block(ivec3(x, y, height + 1)) == Some(AIR)
+                    && world.get_block(ivec3(x, y, height)) != Some(AIR)
+                {
+                    on_the_surface += 1;
+                }
+            }
+        }
+        assert!(on_the_surface > 64 * 64 / 2, "{}", on_the_surface);
+    }
+}
@@ -0,0 +1,363 @@
+mod tests;
+
+use crate::world::chunk::{CHUNK_SIDE_SIZE, Chunk};
+use crate::world::{BlockChange, World};
+use glam::{IVec3, ivec3};
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+
+pub(crate) const DEFAULT_MEMORY_LIMIT: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum HistoryError {
+    TransactionTooLarge { size: usize, limit: usize },
+}
+
+impl Display for HistoryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HistoryError::TransactionTooLarge { size, limit } => write!(
+                f,
+                "Edit needs {} bytes of history but the limit is {} bytes",
+                size, limit
+            ),
+        }
+    }
+}
+
+impl std::error::Error for HistoryError {}
+
+//Old and new blocks of the part of a bulk edit inside one chunk, run length encoded in the order
+//the voxels of [local_min, local_max] are visited: x first, then y, then z. Voxels the edit left
+//alone are stored as air to air, so they merge into one run whatever their block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ChunkEdit {
+    chunk_pos: IVec3,
+    local_min: IVec3,
+    local_max: IVec3,
+    runs: Vec<Run>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Run {
+    length: u16,
+    old: u8,
+    new: u8,
+}
+
+impl ChunkEdit {
+    pub(crate) fn new(chunk_pos: IVec3, local_min: IVec3, local_max: IVec3) -> Self {
+        Self {
+            chunk_pos,
+            local_min,
+            local_max,
+            runs: Vec::new(),
+        }
+    }
+
+    //Adds the next voxel.
+    pub(crate) fn push(&mut self, old: u8, new: u8) {
+        let (old, new) = if old == new { (0, 0) } else { (old, new) };
+        match self.runs.last_mut() {
+            Some(run) if run.old == old && run.new == new && run.length < u16::MAX => {
+                run.length += 1;
+            }
+            _ => self.runs.push(Run {
+                length: 1,
+                old,
+                new,
+            }),
+        }
+    }
+
+    fn changes(&self) -> impl Iterator<Item = BlockChange> + '_ {
+        let (min, max) = (self.local_min, self.local_max);
+        let chunk_origin = self.chunk_pos * CHUNK_SIDE_SIZE;
+        let positions = (min.z..=max.z).flat_map(move |z| {
+            (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| ivec3(x, y, z)))
+        });
+        let blocks = self
+            .runs
+            .iter()
+            .flat_map(|run| std::iter::repeat_n((run.old, run.new), run.length as usize));
+        positions
+            .zip(blocks)
+            .filter(|(_, (old, new))| old != new)
+            .map(move |(local_pos, (old, new))| BlockChange {
+                position: chunk_origin + local_pos,
+                old,
+                new,
+            })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Change {
+    Block(BlockChange),
+    Chunk(ChunkEdit),
+}
+
+//A group of block changes that is undone and redone as a unit. `created_chunks` are the chunks
+//that were all air (None) before the transaction and were allocated by it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Transaction {
+    changes: Vec<Change>,
+    created_chunks: Vec<IVec3>,
+}
+
+impl Transaction {
+    //Every changed block, in the order the changes were made.
+    pub fn changes(&self) -> impl Iterator<Item = BlockChange> + '_ {
+        self.changes.iter().flat_map(|change| {
+            let (single, edit) = match change {
+                Change::Block(change) => (Some(*change), None),
+                Change::Chunk(edit) => (None, Some(edit)),
+            };
+            single
+                .into_iter()
+                .chain(edit.into_iter().flat_map(ChunkEdit::changes))
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn memory_usage(&self) -> usize {
+        let runs: usize = self
+            .changes
+            .iter()
+            .map(|change| match change {
+                Change::Block(_) => 0,
+                Change::Chunk(edit) => edit.runs.len(),
+            })
+            .sum();
+        self.changes.len() * size_of::<Change>()
+            + runs * size_of::<Run>()
+            + self.created_chunks.len() * size_of::<IVec3>()
+    }
+}
+
+//Undo and redo stacks of committed transactions. The memory limit covers both stacks, the oldest
+//undo transactions are dropped first when it is exceeded. A transaction that alone needs more
+//than the limit is refused instead, since it would end up dropping itself.
+pub struct EditHistory {
+    undo_stack: VecDeque<Transaction>,
+    redo_stack: Vec<Transaction>,
+    open_transaction: Option<Transaction>,
+    open_depth: usize,
+    memory_limit: usize,
+    memory_usage: usize,
+}
+
+impl EditHistory {
+    pub fn new(memory_limit: usize) -> Self {
+        Self {
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            open_transaction: None,
+            open_depth: 0,
+            memory_limit,
+            memory_usage: 0,
+        }
+    }
+
+    pub fn undo_count(&self) -> usize {
+        self.undo_stack.len()
+    }
+
+    pub fn redo_count(&self) -> usize {
+        self.redo_stack.len()
+    }
+
+    pub fn memory_usage(&self) -> usize {
+        self.memory_usage
+    }
+
+    pub fn memory_limit(&self) -> usize {
+        self.memory_limit
+    }
+
+    pub fn set_memory_limit(&mut self, memory_limit: usize) {
+        self.memory_limit = memory_limit;
+        self.enforce_memory_limit();
+    }
+
+    //Committed transactions from oldest to newest, the ones that have been undone excluded.
+    pub fn transactions(&self) -> impl Iterator<Item = &Transaction> {
+        self.undo_stack.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.memory_usage = 0;
+    }
+
+    //Adds a change to the open transaction, which World commits.
+    pub(crate) fn record(&mut self, change: BlockChange, created_chunk: bool) {
+        let (chunk_pos, _) = Chunk::split_world_pos(change.position);
+        self.push(Change::Block(change), created_chunk.then_some(chunk_pos));
+    }
+
+    pub(crate) fn record_chunk(&mut self, edit: ChunkEdit, created_chunk: bool) {
+        let chunk_pos = edit.chunk_pos;
+        self.push(Change::Chunk(edit), created_chunk.then_some(chunk_pos));
+    }
+
+    fn push(&mut self, change: Change, created_chunk: Option<IVec3>) {
+        let transaction = self.open_transaction.get_or_insert_default();
+        transaction.changes.push(change);
+        transaction.created_chunks.extend(created_chunk);
+    }
+
+    //Moves the open transaction onto the undo stack. A transaction larger than the memory limit
+    //is handed back instead.
+    fn commit(&mut self) -> Result<(), Transaction> {
+        let Some(transaction) = self.open_transaction.take() else {
+            return Ok(());
+        };
+        if transaction.is_empty() {
+            return Ok(());
+        }
+        if transaction.memory_usage() > self.memory_limit {
+            return Err(transaction);
+        }
+        for redo in self.redo_stack.drain(..) {
+            self.memory_usage -= redo.memory_usage();
+        }
+        self.memory_usage += transaction.memory_usage();
+        self.undo_stack.push_back(transaction);
+        self.enforce_memory_limit();
+        Ok(())
+    }
+
+    fn enforce_memory_limit(&mut self) {
+        while self.memory_usage > self.memory_limit {
+            let dropped = if let Some(transaction) = self.undo_stack.pop_front() {
+                transaction
+            } else if !self.redo_stack.is_empty() {
+                self.redo_stack.remove(0)
+            } else {
+                break;
+            };
+            self.memory_usage -= dropped.memory_usage();
+        }
+    }
+}
+
+impl World {
+    pub fn history(&self) -> &EditHistory {
+        &self.history
+    }
+
+    pub fn history_mut(&mut self) -> &mut EditHistory {
+        &mut self.history
+    }
+
+    //Groups every block change until the matching commit_transaction into one undo step.
+    //Transactions can be nested, only the outermost commit closes the group.
+    pub fn begin_transaction(&mut self) {
+        self.history.open_depth += 1;
+    }
+
+    //Returns an error if the history refused the transaction because it alone needs more memory
+    //than the limit. Its changes are then rolled back, older transactions are kept.
+    pub fn commit_transaction(&mut self) -> Result<(), HistoryError> {
+        if self.history.open_depth == 0 {
+            return Ok(());
+        }
+        self.history.open_depth -= 1;
+        if self.history.open_depth == 0 {
+            return self.commit_history();
+        }
+        Ok(())
+    }
+
+    //Reverts the newest transaction, committing any open one first. Changes in chunks that are
+    //no longer loaded are skipped. Returns false if there was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        self.close_open_transaction();
+        let Some(transaction) = self.history.undo_stack.pop_back() else {
+            return false;
+        };
+        self.revert(&transaction);
+        self.history.redo_stack.push(transaction);
+        true
+    }
+
+    //Reapplies the newest undone transaction. Returns false if there was nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        self.close_open_transaction();
+        let Some(transaction) = self.history.redo_stack.pop() else {
+            return false;
+        };
+        for change in transaction.changes() {
+            self.write_block(change.position, change.new);
+        }
+        self.blocks_edited(&changed_positions(&transaction));
+        self.history.undo_stack.push_back(transaction);
+        true
+    }
+
+    //Applies the changes of a transaction recorded in another world as a new transaction of this
+    //one. Returns the number of blocks that were set.
+    pub fn replay(&mut self, transaction: &Transaction) -> Result<usize, HistoryError> {
+        self.begin_transaction();
+        let applied = transaction
+            .changes()
+            .filter(|change| self.set_block(change.position, change.new).is_some())
+            .count();
+        self.commit_transaction()?;
+        Ok(applied)
+    }
+
+    fn commit_history(&mut self) -> Result<(), HistoryError> {
+        let Err(refused) = self.history.commit() else {
+            return Ok(());
+        };
+        self.revert(&refused);
+        Err(HistoryError::TransactionTooLarge {
+            size: refused.memory_usage(),
+            limit: self.history.memory_limit,
+        })
+    }
+
+    fn close_open_transaction(&mut self) {
+        self.history.open_depth = 0;
+        //A refused transaction is already rolled back, undo and redo go on with the older ones.
+        let _ = self.commit_history();
+    }
+
+    fn revert(&mut self, transaction: &Transaction) {
+        for change in transaction.changes.iter().rev() {
+            match change {
+                Change::Block(change) => {
+                    self.write_block(change.position, change.old);
+                }
+                Change::Chunk(edit) => {
+                    for change in edit.changes() {
+                        self.write_block(change.position, change.old);
+                    }
+                }
+            }
+        }
+        self.blocks_edited(&changed_positions(transaction));
+        for chunk_pos in &transaction.created_chunks {
+            if let Some(entry) = self.loaded_chunks.get_mut(chunk_pos)
+                && entry
+                    .as_ref()
+                    .is_some_and(|chunk| chunk.texture.iter().all(|block| *block == 0))
+            {
+                *entry = None;
+            }
+        }
+    }
+}
+
+fn changed_positions(transaction: &Transaction) -> Vec<IVec3> {
+    transaction
+        .changes()
+        .map(|change| change.position)
+        .collect()
+}
@@ -0,0 +1,251 @@
+#[cfg(test)]
+mod tests {
+    use crate::world::edit::Region;
+    use crate::world::history::HistoryError;
+    use crate::world::tests::tests::create_test_world;
+    use crate::world::{BlockChange, World};
+    use glam::{IVec3, ivec3};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::HashMap;
+
+    type Snapshot = HashMap<IVec3, Option<Vec<u8>>>;
+
+    fn snapshot(world: &World) -> Snapshot {
+        world
+            .loaded_chunks
+            .iter()
+            .map(|(pos, chunk)| (*pos, chunk.as_ref().map(|chunk| chunk.texture.clone())))
+            .collect()
+    }
+
+    //Edits around the origin cross chunk borders, dig into the terrain and build into all-air
+    //chunks above it.
+    fn random_edits(world: &mut World, rng: &mut StdRng, count: usize) {
+        for _ in 0..count {
+            if rng.random_range(0..8) == 0 {
+                world.begin_transaction();
+                for _ in 0..rng.random_range(1..20) {
+                    random_edit(world, rng);
+                }
+                world.commit_transaction().unwrap();
+            } else {
+                random_edit(world, rng);
+            }
+        }
+    }
+
+    fn random_edit(world: &mut World, rng: &mut StdRng) {
+        let position = IVec3::new(
+            rng.random_range(-40..40),
+            rng.random_range(-40..40),
+            rng.random_range(-20..70),
+        );
+        world.set_block(position, rng.random_range(0..4));
+    }
+
+    #[test]
+    fn test_random_edits_full_undo() {
+        for seed in 0..8 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut world = create_test_world();
+            let original = snapshot(&world);
+            random_edits(&mut world, &mut rng, 300);
+            let edited = snapshot(&world);
+            assert_ne!(edited, original);
+
+            while world.undo() {}
+            assert!(snapshot(&world) == original, "seed {}", seed);
+
+            while world.redo() {}
+            assert!(snapshot(&world) == edited, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn test_undo_removes_created_chunk() {
+        let mut world = create_test_world();
+        let chunk_pos = IVec3::new(0, 0, 2);
+        assert!(world.loaded_chunks[&chunk_pos].is_none());
+        world.set_block(IVec3::new(1, 2, 70), 1);
+        assert!(world.loaded_chunks[&chunk_pos].is_some());
+        assert!(world.undo());
+        assert!(world.loaded_chunks[&chunk_pos].is_none());
+        assert!(world.redo());
+        assert_eq!(world.get_block(IVec3::new(1, 2, 70)), Some(1));
+    }
+
+    #[test]
+    fn test_transaction_is_one_step() {
+        let mut world = create_test_world();
+        world.begin_transaction();
+        world.set_block(IVec3::new(0, 0, 40), 1);
+        world.begin_transaction();
+        world.set_block(IVec3::new(1, 0, 40), 2);
+        world.commit_transaction().unwrap();
+        assert_eq!(world.history().undo_count(), 0);
+        world.set_block(IVec3::new(2, 0, 40), 3);
+        world.commit_transaction().unwrap();
+        assert_eq!(world.history().undo_count(), 1);
+
+        assert!(world.undo());
+        for x in 0..3 {
+            assert_eq!(world.get_block(IVec3::new(x, 0, 40)), Some(0));
+        }
+        assert!(!world.undo());
+    }
+
+    #[test]
+    fn test_new_edit_clears_redo() {
+        let mut world = create_test_world();
+        world.set_block(IVec3::new(0, 0, 40), 1);
+        world.set_block(IVec3::new(0, 0, 40), 2);
+        assert!(world.undo());
+        assert_eq!(world.history().redo_count(), 1);
+        world.set_block(IVec3::new(0, 0, 41), 3);
+        assert_eq!(world.history().redo_count(), 0);
+        assert!(!world.redo());
+        assert_eq!(world.get_block(IVec3::new(0, 0, 40)), Some(1));
+    }
+
+    #[test]
+    fn test_unchanged_blocks_are_not_recorded() {
+        let mut world = create_test_world();
+        world.set_block(IVec3::new(0, 0, 40), 0);
+        world.set_block(IVec3::new(0, 0, 1000), 1);
+        world.begin_transaction();
+        world.commit_transaction().unwrap();
+        assert_eq!(world.history().undo_count(), 0);
+        assert!(!world.undo());
+    }
+
+    #[test]
+    fn test_undo_emits_block_changes() {
+        let mut world = create_test_world();
+        world.set_block(IVec3::new(0, 0, 40), 1);
+        world.record_block_changes(true);
+        world.take_dirty_chunks();
+        world.undo();
+        let changes = world.take_block_changes();
+        assert_eq!(changes.len(), 1);
+        assert_eq!((changes[0].old, changes[0].new), (1, 0));
+        assert!(world.is_dirty(IVec3::new(0, 0, 1)));
+    }
+
+    #[test]
+    fn test_memory_limit_drops_oldest() {
+        let mut world = create_test_world();
+        for x in 0..10 {
+            world.set_block(IVec3::new(x, 0, 40), 1);
+        }
+        let single = world.history().memory_usage() / 10;
+        world.history_mut().set_memory_limit(single * 4);
+        assert_eq!(world.history().undo_count(), 4);
+        assert!(world.history().memory_usage() <= world.history().memory_limit());
+
+        world.set_block(IVec3::new(10, 0, 40), 1);
+        assert_eq!(world.history().undo_count(), 4);
+        while world.undo() {}
+        //The oldest edits can no longer be undone.
+        for x in 0..7 {
+            assert_eq!(world.get_block(IVec3::new(x, 0, 40)), Some(1));
+        }
+        for x in 7..11 {
+            assert_eq!(world.get_block(IVec3::new(x, 0, 40)), Some(0));
+        }
+    }
+
+    #[test]
+    fn test_replay_onto_fresh_world() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut world = create_test_world();
+        random_edits(&mut world, &mut rng, 200);
+        //Undone transactions are not part of the replay.
+        world.undo();
+        world.undo();
+
+        let mut fresh = create_test_world();
+        for transaction in world.history().transactions() {
+            fresh.replay(transaction).unwrap();
+        }
+        assert!(snapshot(&fresh) == snapshot(&world));
+        assert_eq!(fresh.history().undo_count(), world.history().undo_count());
+    }
+
+    #[test]
+    fn test_replayed_region_is_one_step() {
+        let mut world = create_test_world();
+        let region = Region::new(ivec3(-4, -4, 38), ivec3(4, 4, 42));
+        world.fill(&region, 2).unwrap();
+        let transaction = world.history().transactions().next().unwrap().clone();
+
+        let mut fresh = create_test_world();
+        let before = snapshot(&fresh);
+        assert_eq!(fresh.replay(&transaction).unwrap(), region.volume());
+        assert_eq!(fresh.history().undo_count(), 1);
+        assert!(fresh.undo());
+        assert!(snapshot(&fresh) == before);
+        assert!(!fresh.undo());
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut world = create_test_world();
+        world.set_block(IVec3::new(0, 0, 40), 1);
+        world.set_block(IVec3::new(1, 0, 40), 1);
+        world.undo();
+        world.history_mut().clear();
+        assert_eq!(world.history().undo_count(), 0);
+        assert_eq!(world.history().redo_count(), 0);
+        assert_eq!(world.history().memory_usage(), 0);
+        assert!(!world.undo());
+        assert!(!world.redo());
+        assert_eq!(world.get_block(IVec3::new(0, 0, 40)), Some(1));
+    }
+
+    #[test]
+    fn test_multi_chunk_fill_undo() {
+        let mut world = create_test_world();
+        //Through the terrain and across eight chunks.
+        let region = Region::new(ivec3(-16, -16, -16), ivec3(15, 15, 15));
+        let before = world.copy_region(&region);
+        world.history_mut().set_memory_limit(64 * 1024);
+        let changed = world.fill(&region, 2).unwrap();
+        //A change per voxel would not have fit.
+        assert!(changed * size_of::<BlockChange>() > world.history().memory_limit());
+        assert_eq!(world.history().undo_count(), 1);
+        let transaction = world.history().transactions().next().unwrap();
+        assert_eq!(transaction.changes().count(), changed);
+
+        assert!(world.undo());
+        assert!(world.copy_region(&region) == before);
+        assert!(world.redo());
+        assert_eq!(world.copy_region(&region).get(ivec3(3, 30, 31)), 2);
+        assert!(world.undo());
+        assert!(world.copy_region(&region) == before);
+    }
+
+    #[test]
+    fn test_transaction_larger_than_the_limit_is_refused() {
+        let mut world = create_test_world();
+        world.set_block(IVec3::new(0, 0, 40), 1);
+        let limit = world.history().memory_usage() * 4;
+        world.history_mut().set_memory_limit(limit);
+        let before = snapshot(&world);
+
+        world.begin_transaction();
+        for x in -8..=8 {
+            world.set_block(IVec3::new(x, 5, 41), 2);
+        }
+        assert!(matches!(
+            world.commit_transaction(),
+            Err(HistoryError::TransactionTooLarge { limit: l, .. }) if l == limit
+        ));
+        //The refused edit is rolled back and the older one can still be undone.
+        assert!(snapshot(&world) == before);
+        assert_eq!(world.history().undo_count(), 1);
+        assert!(world.undo());
+        assert_eq!(world.get_block(IVec3::new(0, 0, 40)), Some(0));
+        assert!(world.redo());
+    }
+}
@@ -0,0 +1,389 @@
+mod tests;
+
+use crate::world::block_registry::AIR;
+use crate::world::chunk::{CHUNK_SIDE_SIZE, CHUNK_SIZE, Chunk};
+use crate::world::{NEIGHBOUR_OFFSETS, World};
+use glam::{IVec3, ivec3};
+use std::collections::{HashSet, VecDeque};
+
+pub const MAX_LIGHT: u8 = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LightChannel {
+    //Light from the open sky above the loaded world. It travels straight down without fading.
+    Sky,
+    //Colour components of the light from emissive blocks. Each spreads and fades on its own, so
+    //overlapping lights mix by taking the brightest of every component.
+    Red,
+    Green,
+    Blue,
+}
+
+pub(crate) const CHANNELS: [LightChannel; 4] = [
+    LightChannel::Sky,
+    LightChannel::Red,
+    LightChannel::Green,
+    LightChannel::Blue,
+];
+
+impl LightChannel {
+    fn shift(self) -> u32 {
+        match self {
+            LightChannel::Sky => 12,
+            LightChannel::Red => 8,
+            LightChannel::Green => 4,
+            LightChannel::Blue => 0,
+        }
+    }
+
+    //What unloaded chunks read as, so faces at the edge of the world are not dark.
+    fn unloaded_level(self) -> u8 {
+        match self {
+            LightChannel::Sky => MAX_LIGHT,
+            _ => 0,
+        }
+    }
+}
+
+//Light levels of every voxel of a chunk, indexed like chunk textures. Each channel is a 4 bit
+//level, packed from the highest nibble down as sky, red, green and blue. All-air chunks have light
+//too.
+pub struct ChunkLight {
+    levels: Vec<u16>,
+}
+
+impl ChunkLight {
+    fn new() -> Box<Self> {
+        Box::from(Self {
+            levels: vec![0; CHUNK_SIZE as usize],
+        })
+    }
+
+    pub fn get(&self, local_pos: IVec3, channel: LightChannel) -> u8 {
+        (self.levels[Chunk::texture_index(local_pos)] >> channel.shift() & 0xf) as u8
+    }
+
+    fn set(&mut self, local_pos: IVec3, channel: LightChannel, level: u8) {
+        let value = &mut self.levels[Chunk::texture_index(local_pos)];
+        *value = *value & !(0xf << channel.shift()) | (level as u16) << channel.shift();
+    }
+}
+
+//Only air lets light through. Emissive blocks are opaque but light their own voxel.
+pub(crate) fn is_transparent(block: u8) -> bool {
+    block == AIR
+}
+
+//Level a voxel gets from a neighbour at `level`, with `offset` pointing from the neighbour to the
+//voxel. Full sky light keeps its level going down so open columns stay fully lit.
+fn propagated_level(channel: LightChannel, level: u8, offset: IVec3) -> u8 {
+    if channel == LightChannel::Sky && level == MAX_LIGHT && offset == IVec3::NEG_Z {
+        MAX_LIGHT
+    } else {
+        level.saturating_sub(1)
+    }
+}
+
+fn is_inside_chunk(local_pos: IVec3) -> bool {
+    local_pos.cmpge(IVec3::ZERO).all() && local_pos.cmplt(IVec3::splat(CHUNK_SIDE_SIZE)).all()
+}
+
+//Light is flood filled breadth first: adding light spreads from the changed voxels, and removing
+//it first clears everything the old light could have reached, then refills that area from the
+//brighter voxels found on its edge. Light is not recomputed when chunks unload.
+impl World {
+    pub fn light(&self, world_pos: IVec3, channel: LightChannel) -> u8 {
+        self.get_light(world_pos, channel)
+            .unwrap_or(channel.unloaded_level())
+    }
+
+    pub(crate) fn chunk_light(&self, chunk_pos: IVec3) -> Option<&ChunkLight> {
+        self.light.get(&chunk_pos).map(|light| light.as_ref())
+    }
+
+    fn get_light(&self, world_pos: IVec3, channel: LightChannel) -> Option<u8> {
+        let (chunk_pos, local_pos) = Chunk::split_world_pos(world_pos);
+        Some(self.light.get(&chunk_pos)?.get(local_pos, channel))
+    }
+
+    fn set_light(&mut self, world_pos: IVec3, channel: LightChannel, level: u8) {
+        let (chunk_pos, local_pos) = Chunk::split_world_pos(world_pos);
+        if let Some(light) = self.light.get_mut(&chunk_pos) {
+            light.set(local_pos, channel, level);
+            self.mark_dirty(chunk_pos, local_pos, local_pos);
+        }
+    }
+
+    fn emission(&self, block: u8, channel: LightChannel) -> u8 {
+        let Some(block) = self.registry.get(block) else {
+            return 0;
+        };
+        match channel {
+            LightChannel::Sky => 0,
+            LightChannel::Red => block.emission[0],
+            LightChannel::Green => block.emission[1],
+            LightChannel::Blue => block.emission[2],
+        }
+    }
+
+    //Sky light enters voxels whose upper neighbour is not loaded.
+    fn is_open_to_sky(&self, world_pos: IVec3) -> bool {
+        let (chunk_pos, _) = Chunk::split_world_pos(world_pos + IVec3::Z);
+        !self.light.contains_key(&chunk_pos)
+    }
+
+    //Lights chunks that were just inserted into `loaded_chunks`, then exchanges light with their
+    //already lit neighbours.
+    pub(crate) fn light_new_chunks(&mut self, chunk_positions: &[IVec3]) {
+        let mut new_chunks = chunk_positions.to_vec();
+        //Top down, so sky light coming from a new chunk above is known.
+        new_chunks.sort_by_key(|chunk_pos| -chunk_pos.z);
+        let emissions: [[u8; 4]; 256] = std::array::from_fn(|block| {
+            CHANNELS.map(|channel| self.emission(block as u8, channel))
+        });
+        let mut fully_lit = HashSet::new();
+        //One queue per channel in the order of CHANNELS, so sky light comes first.
+        let mut queues: [VecDeque<IVec3>; CHANNELS.len()] = Default::default();
+        let layer = (CHUNK_SIDE_SIZE * CHUNK_SIDE_SIZE) as usize;
+        let full_sky = (MAX_LIGHT as u16) << LightChannel::Sky.shift();
+        for &chunk_pos in &new_chunks {
+            let mut light = ChunkLight::new();
+            let chunk = self
+                .loaded_chunks
+                .get(&chunk_pos)
+                .and_then(|chunk| chunk.as_deref());
+            let above = self.light.get(&(chunk_pos + IVec3::Z));
+            //Columns are indexed x + y * CHUNK_SIDE_SIZE, which is also their index in the bottom
+            //layer of the chunk above.
+            let open: Vec<bool> = (0..layer)
+                .map(|column| above.is_none_or(|above| above.levels[column] & 0xf000 == full_sky))
+                .collect();
+            if chunk.is_none() && open.iter().all(|open| *open) {
+                light.levels.fill(full_sky);
+                fully_lit.insert(chunk_pos);
+            } else {
+                for column in (0..layer).filter(|column| open[*column]) {
+                    for z in (0..CHUNK_SIDE_SIZE as usize).rev() {
+                        let index = column + z * layer;
+                        if chunk.is_some_and(|chunk| !is_transparent(chunk.texture[index])) {
+                            break;
+                        }
+                        light.levels[index] |= full_sky;
+                    }
+                }
+            }
+            if let Some(chunk) = chunk {
+                for (index, block) in chunk.texture.iter().enumerate() {
+                    if emissions[*block as usize] == [0; CHANNELS.len()] {
+                        continue;
+                    }
+                    for ((queue, channel), emission) in queues
+                        .iter_mut()
+                        .zip(CHANNELS)
+                        .zip(emissions[*block as usize])
+                    {
+                        if emission > 0 {
+                            light.levels[index] |= (emission as u16) << channel.shift();
+                            queue.push_back(chunk_pos * CHUNK_SIDE_SIZE + index_to_local(index));
+                        }
+                    }
+                }
+            }
+            self.light.insert(chunk_pos, light);
+        }
+
+        //Sky light only moved down so far. Dark air next to a brighter voxel gets lit from it.
+        for &chunk_pos in &new_chunks {
+            if fully_lit.contains(&chunk_pos) {
+                continue;
+            }
+            let chunk = self
+                .loaded_chunks
+                .get(&chunk_pos)
+                .and_then(|chunk| chunk.as_deref());
+            let light = &self.light[&chunk_pos];
+            for (index, level) in light.levels.iter().enumerate() {
+                let level = (level >> LightChannel::Sky.shift()) as u8;
+                if level == MAX_LIGHT
+                    || chunk.is_some_and(|chunk| !is_transparent(chunk.texture[index]))
+                {
+                    continue;
+                }
+                let local_pos = index_to_local(index);
+                for offset in NEIGHBOUR_OFFSETS {
+                    let neighbour = local_pos - offset;
+                    let neighbour_level = if is_inside_chunk(neighbour) {
+                        Some(light.get(neighbour, LightChannel::Sky))
+                    } else {
+                        self.get_light(chunk_pos * CHUNK_SIDE_SIZE + neighbour, LightChannel::Sky)
+                    };
+                    if let Some(neighbour_level) = neighbour_level
+                        && propagated_level(LightChannel::Sky, neighbour_level, offset) > level
+                    {
+                        queues[0].push_back(chunk_pos * CHUNK_SIDE_SIZE + neighbour);
+                    }
+                }
+            }
+        }
+
+        //Old neighbours spread their light into the new chunks, and new chunks above old ones can
+        //shade them.
+        let mut sky_removal = VecDeque::new();
+        for &chunk_pos in &new_chunks {
+            for offset in NEIGHBOUR_OFFSETS {
+                let neighbour_pos = chunk_pos + offset;
+                if new_chunks.contains(&neighbour_pos) || !self.light.contains_key(&neighbour_pos) {
+                    continue;
+                }
+                let neighbour_origin = neighbour_pos * CHUNK_SIDE_SIZE;
+                for local_pos in border_layer(-offset) {
+                    let world_pos = neighbour_origin + local_pos;
+                    for (queue, channel) in queues.iter_mut().zip(CHANNELS) {
+                        if self.light(world_pos, channel) > 0 {
+                            queue.push_back(world_pos);
+                        }
+                    }
+                    if offset == IVec3::NEG_Z
+                        && self.light(world_pos, LightChannel::Sky) == MAX_LIGHT
+                        && self.light(world_pos + IVec3::Z, LightChannel::Sky) < MAX_LIGHT
+                    {
+                        self.set_light(world_pos, LightChannel::Sky, 0);
+                        sky_removal.push_back((world_pos, MAX_LIGHT));
+                    }
+                }
+            }
+        }
+        self.remove_light(LightChannel::Sky, sky_removal, &mut queues[0]);
+        for (queue, channel) in queues.into_iter().zip(CHANNELS) {
+            self.propagate_light(channel, queue);
+        }
+    }
+
+    //Updates light after the blocks at `positions` changed.
+    pub(crate) fn relight(&mut self, positions: &[IVec3]) {
+        for channel in CHANNELS {
+            let mut removal = VecDeque::new();
+            let mut queue = VecDeque::new();
+            for position in positions {
+                if let Some(level) = self.get_light(*position, channel)
+                    && level > 0
+                {
+                    self.set_light(*position, channel, 0);
+                    removal.push_back((*position, level));
+                }
+            }
+            self.remove_light(channel, removal, &mut queue);
+            for position in positions {
+                let Some(block) = self.get_block(*position) else {
+                    continue;
+                };
+                if is_transparent(block) {
+                    if channel == LightChannel::Sky && self.is_open_to_sky(*position) {
+                        self.set_light(*position, channel, MAX_LIGHT);
+                        queue.push_back(*position);
+                    }
+                    for offset in NEIGHBOUR_OFFSETS {
+                        if self
+                            .get_light(position + offset, channel)
+                            .is_some_and(|level| level > 0)
+                        {
+                            queue.push_back(position + offset);
+                        }
+                    }
+                } else if self.emission(block, channel) > 0 {
+                    self.set_light(*position, channel, self.emission(block, channel));
+                    queue.push_back(*position);
+                }
+            }
+            self.propagate_light(channel, queue);
+        }
+    }
+
+    //Clears light that came from the voxels in `removal`, which are already dark and carry their
+    //old level. Voxels lit from elsewhere are added to `queue` to fill the cleared area again.
+    fn remove_light(
+        &mut self,
+        channel: LightChannel,
+        mut removal: VecDeque<(IVec3, u8)>,
+        queue: &mut VecDeque<IVec3>,
+    ) {
+        while let Some((position, level)) = removal.pop_front() {
+            for offset in NEIGHBOUR_OFFSETS {
+                let neighbour = position + offset;
+                let Some(neighbour_level) = self.get_light(neighbour, channel) else {
+                    continue;
+                };
+                if neighbour_level == 0 {
+                    continue;
+                }
+                if neighbour_level <= propagated_level(channel, level, offset) {
+                    self.set_light(neighbour, channel, 0);
+                    removal.push_back((neighbour, neighbour_level));
+                    let emission = self
+                        .get_block(neighbour)
+                        .map_or(0, |block| self.emission(block, channel));
+                    if emission > 0 {
+                        self.set_light(neighbour, channel, emission);
+                        queue.push_back(neighbour);
+                    }
+                } else {
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+    }
+
+    fn propagate_light(&mut self, channel: LightChannel, mut queue: VecDeque<IVec3>) {
+        while let Some(position) = queue.pop_front() {
+            let Some(level) = self.get_light(position, channel) else {
+                continue;
+            };
+            for offset in NEIGHBOUR_OFFSETS {
+                let neighbour = position + offset;
+                let target = propagated_level(channel, level, offset);
+                if target == 0 || !self.get_block(neighbour).is_some_and(is_transparent) {
+                    continue;
+                }
+                if self
+                    .get_light(neighbour, channel)
+                    .is_some_and(|neighbour_level| neighbour_level < target)
+                {
+                    self.set_light(neighbour, channel, target);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+    }
+}
+
+fn index_to_local(index: usize) -> IVec3 {
+    let index = index as i32;
+    ivec3(
+        index % CHUNK_SIDE_SIZE,
+        index / CHUNK_SIDE_SIZE % CHUNK_SIDE_SIZE,
+        index / (CHUNK_SIDE_SIZE * CHUNK_SIDE_SIZE),
+    )
+}
+
+//Local positions of the chunk layer facing `direction`.
+fn border_layer(direction: IVec3) -> impl Iterator<Item = IVec3> {
+    let axis = if direction.x != 0 {
+        0
+    } else if direction.y != 0 {
+        1
+    } else {
+        2
+    };
+    let fixed = if direction.max_element() > 0 {
+        CHUNK_SIDE_SIZE - 1
+    } else {
+        0
+    };
+    (0..CHUNK_SIDE_SIZE * CHUNK_SIDE_SIZE).map(move |i| {
+        let mut position = [0; 3];
+        position[axis] = fixed;
+        position[(axis + 1) % 3] = i % CHUNK_SIDE_SIZE;
+        position[(axis + 2) % 3] = i / CHUNK_SIDE_SIZE;
+        IVec3::from_array(position)
+    })
+}
@@ -0,0 +1,173 @@
+#[cfg(test)]
+mod tests {
+    use crate::world::World;
+    use crate::world::block_registry::{AIR, BLUE_LAMP, LAMP, RED_LAMP, STONE};
+    use crate::world::edit::Region;
+    use crate::world::light::LightChannel::{Blue, Green, Red, Sky};
+    use crate::world::light::MAX_LIGHT;
+    use crate::world::tests::tests::create_test_world;
+    use glam::{IVec3, ivec3};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    //A closed stone box from (0, 0, 64) to (6, 6, 70) with a dark 5 x 5 x 5 inside.
+    fn create_box(world: &mut World) {
+        world
+            .outline(&Region::new(ivec3(0, 0, 64), ivec3(6, 6, 70)), STONE)
+            .unwrap();
+    }
+
+    //Lighting every loaded chunk from scratch must give the same result as the incremental
+    //updates that led here.
+    fn assert_matches_full_relight(world: &mut World) {
+        let chunk_positions: Vec<IVec3> = world.loaded_chunks.keys().copied().collect();
+        let incremental = std::mem::take(&mut world.light);
+        world.light_new_chunks(&chunk_positions);
+        for (chunk_pos, light) in &incremental {
+            assert!(
+                light.levels == world.light[chunk_pos].levels,
+                "Light differs in chunk {}",
+                chunk_pos
+            );
+        }
+    }
+
+    #[test]
+    fn test_generated_light() {
+        let mut world = create_test_world();
+        assert_eq!(world.light(ivec3(5, 5, 70), Sky), MAX_LIGHT);
+        assert_eq!(world.light(ivec3(5, 5, 70), Red), 0);
+        assert_eq!(world.light(ivec3(3, -7, -1), Sky), 0);
+        //Unloaded chunks read as open sky.
+        assert_eq!(world.light(ivec3(1000, 0, 0), Sky), MAX_LIGHT);
+        assert_eq!(world.light(ivec3(1000, 0, 0), Red), 0);
+        assert_matches_full_relight(&mut world);
+    }
+
+    #[test]
+    fn test_shadow() {
+        let mut world = create_test_world();
+        world.set_block(ivec3(5, 5, 80), STONE);
+        assert_eq!(world.light(ivec3(5, 5, 80), Sky), 0);
+        //Sky light only reaches the column below from the side.
+        assert_eq!(world.light(ivec3(5, 5, 79), Sky), MAX_LIGHT - 1);
+        assert_eq!(world.light(ivec3(5, 5, 65), Sky), MAX_LIGHT - 1);
+        assert_eq!(world.light(ivec3(6, 5, 79), Sky), MAX_LIGHT);
+        assert!(world.take_dirty_chunks().contains(&ivec3(0, 0, 1)));
+
+        world.set_block(ivec3(5, 5, 80), AIR);
+        assert_eq!(world.light(ivec3(5, 5, 65), Sky), MAX_LIGHT);
+    }
+
+    #[test]
+    fn test_light_enters_opening() {
+        let mut world = create_test_world();
+        create_box(&mut world);
+        assert_eq!(world.light(ivec3(3, 3, 67), Sky), 0);
+        assert_eq!(world.light(ivec3(1, 1, 65), Sky), 0);
+
+        world.set_block(ivec3(3, 0, 67), AIR);
+        assert_eq!(world.light(ivec3(3, 0, 67), Sky), 14);
+        assert_eq!(world.light(ivec3(3, 1, 67), Sky), 13);
+        assert_eq!(world.light(ivec3(3, 3, 67), Sky), 11);
+        assert_eq!(world.light(ivec3(1, 5, 65), Sky), 5);
+        assert_matches_full_relight(&mut world);
+
+        world.set_block(ivec3(3, 0, 67), STONE);
+        assert_eq!(world.light(ivec3(3, 3, 67), Sky), 0);
+        assert_eq!(world.light(ivec3(3, 1, 67), Sky), 0);
+    }
+
+    #[test]
+    fn test_emissive_block() {
+        let mut world = create_test_world();
+        create_box(&mut world);
+        world.set_block(ivec3(3, 3, 67), LAMP);
+        assert_eq!(world.light(ivec3(3, 3, 67), Red), MAX_LIGHT);
+        assert_eq!(world.light(ivec3(3, 3, 68), Red), 14);
+        assert_eq!(world.light(ivec3(1, 1, 66), Red), 10);
+        //The walls stop it.
+        assert_eq!(world.light(ivec3(3, 3, 70), Red), 0);
+        assert_eq!(world.light(ivec3(3, 3, 71), Red), 0);
+        assert_eq!(world.light(ivec3(3, 3, 68), Sky), 0);
+
+        world.set_block(ivec3(3, 3, 67), AIR);
+        assert_eq!(world.light(ivec3(3, 3, 67), Red), 0);
+        assert_eq!(world.light(ivec3(1, 1, 66), Red), 0);
+    }
+
+    #[test]
+    fn test_coloured_light_mixes() {
+        let mut world = create_test_world();
+        create_box(&mut world);
+        world.set_block(ivec3(1, 3, 67), RED_LAMP);
+        world.set_block(ivec3(5, 3, 67), BLUE_LAMP);
+        let levels = |world: &World, position| {
+            [Red, Green, Blue].map(|channel| world.light(position, channel))
+        };
+        assert_eq!(levels(&world, ivec3(1, 3, 67)), [15, 3, 2]);
+        //Every channel fades on its own and the brighter light wins per channel.
+        assert_eq!(levels(&world, ivec3(2, 3, 67)), [14, 4, 12]);
+        assert_eq!(levels(&world, ivec3(3, 3, 67)), [13, 5, 13]);
+        assert_eq!(levels(&world, ivec3(4, 3, 67)), [12, 6, 14]);
+        assert_matches_full_relight(&mut world);
+
+        world.set_block(ivec3(5, 3, 67), STONE);
+        assert_eq!(levels(&world, ivec3(4, 3, 67)), [12, 0, 0]);
+        assert_matches_full_relight(&mut world);
+    }
+
+    #[test]
+    fn test_light_crosses_chunk_border() {
+        let mut world = create_test_world();
+        world.set_block(ivec3(31, 5, 70), LAMP);
+        assert_eq!(world.light(ivec3(32, 5, 70), Red), 14);
+        assert_eq!(world.light(ivec3(35, 5, 70), Red), 11);
+        assert!(world.take_dirty_chunks().contains(&ivec3(1, 0, 2)));
+
+        //Removing a lamp next to another one keeps the light of the remaining one.
+        world.set_block(ivec3(34, 5, 70), LAMP);
+        world.set_block(ivec3(31, 5, 70), AIR);
+        assert_eq!(world.light(ivec3(32, 5, 70), Red), 13);
+        assert_eq!(world.light(ivec3(31, 5, 70), Red), 12);
+        assert_matches_full_relight(&mut world);
+    }
+
+    #[test]
+    fn test_random_edits_match_full_relight() {
+        let mut world = create_test_world();
+        let mut rng = StdRng::seed_from_u64(7);
+        let before: Vec<u16> = world.light[&ivec3(0, 0, 2)].levels.clone();
+        world.begin_transaction();
+        for _ in 0..300 {
+            let position = ivec3(
+                rng.random_range(0..16),
+                rng.random_range(28..36),
+                rng.random_range(60..72),
+            );
+            let block = [AIR, STONE, STONE, LAMP][rng.random_range(0..4)];
+            world.set_block(position, block);
+        }
+        world
+            .fill(&Region::new(ivec3(2, 28, 66), ivec3(12, 34, 66)), STONE)
+            .unwrap();
+        world.commit_transaction().unwrap();
+        assert_matches_full_relight(&mut world);
+
+        world.undo();
+        assert_eq!(world.light[&ivec3(0, 0, 2)].levels, before);
+        assert_matches_full_relight(&mut world);
+        world.redo();
+        assert_matches_full_relight(&mut world);
+    }
+
+    #[test]
+    fn test_streamed_chunks_are_lit() {
+        let mut world = create_test_world();
+        assert!(world.chunk_light(ivec3(5, 0, 0)).is_none());
+        world.update_map_position(ivec3(1, 0, 0));
+        assert!(world.chunk_light(ivec3(5, 0, 0)).is_some());
+        assert!(world.chunk_light(ivec3(-3, 0, 0)).is_none());
+        assert_eq!(world.light(ivec3(5 * 32 + 3, 3, 70), Sky), MAX_LIGHT);
+    }
+}
@@ -0,0 +1,393 @@
+mod tests;
+
+use crate::world::World;
+use crate::world::block_registry::BlockRegistry;
+use crate::world::edit::Region;
+use crate::world::mesher::{ChunkMesh, mesh_chunk};
+use glam::{IVec3, Vec3, ivec3};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const GLB_VERSION: u32 = 2;
+const GLB_JSON_CHUNK: &[u8; 4] = b"JSON";
+const GLB_BIN_CHUNK: &[u8; 4] = b"BIN\0";
+const GL_FLOAT: u32 = 5126;
+const GL_UNSIGNED_INT: u32 = 5125;
+const GL_ARRAY_BUFFER: u32 = 34962;
+const GL_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+//Meshes of the chunks to export. Both OBJ and glTF are y up, so the world z axis is written as y
+//and the world y axis as -z.
+pub struct MeshExport<'a> {
+    pub chunks: Vec<(IVec3, ChunkMesh)>,
+    registry: &'a BlockRegistry,
+}
+
+impl World {
+    //Meshes the loaded chunks in `chunk_positions`. All-air and unloaded chunks are skipped.
+    pub fn export_chunks(
+        &self,
+        chunk_positions: impl IntoIterator<Item = IVec3>,
+    ) -> MeshExport<'_> {
+        let mut chunk_positions: Vec<IVec3> = chunk_positions.into_iter().collect();
+        chunk_positions.sort_by_key(|pos| (pos.z, pos.y, pos.x));
+        chunk_positions.dedup();
+        let chunks = chunk_positions
+            .into_iter()
+            .map(|chunk_pos| (chunk_pos, mesh_chunk(self, chunk_pos)))
+            .filter(|(_, mesh)| !mesh.is_empty())
+            .collect();
+        MeshExport {
+            chunks,
+            registry: self.registry(),
+        }
+    }
+
+    //Greedy quads span whole chunks, so every chunk the region overlaps is exported uncropped.
+    pub fn export_region(&self, region: &Region) -> MeshExport<'_> {
+        let (chunk_min, chunk_max) = region.chunk_range();
+        let mut chunk_positions = Vec::new();
+        for z in chunk_min.z..=chunk_max.z {
+            for y in chunk_min.y..=chunk_max.y {
+                for x in chunk_min.x..=chunk_max.x {
+                    chunk_positions.push(ivec3(x, y, z));
+                }
+            }
+        }
+        self.export_chunks(chunk_positions)
+    }
+
+    pub fn export_loaded_chunks(&self) -> MeshExport<'_> {
+        self.export_chunks(self.loaded_chunks.keys().copied())
+    }
+}
+
+impl MeshExport<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    //Returns the OBJ and MTL file contents. `mtl_file_name` is referenced from the OBJ and should
+    //be the MTL file's name relative to the OBJ.
+    pub fn to_obj(&self, mtl_file_name: &str) -> (String, String) {
+        let mut obj = String::new();
+        writeln!(obj, "mtllib {}", mtl_file_name).unwrap();
+        let mut vertex_offset = 1;
+        for (chunk_pos, mesh) in &self.chunks {
+            writeln!(obj, "o {}", chunk_name(*chunk_pos)).unwrap();
+            for vertex in &mesh.vertices {
+                let position = to_y_up(vertex.position);
+                writeln!(obj, "v {} {} {}", position.x, position.y, position.z).unwrap();
+            }
+            for vertex in &mesh.vertices {
+                writeln!(obj, "vt {} {}", vertex.uv.x, vertex.uv.y).unwrap();
+            }
+            for vertex in &mesh.vertices {
+                let normal = to_y_up(vertex.normal);
+                writeln!(obj, "vn {} {} {}", normal.x, normal.y, normal.z).unwrap();
+            }
+            for (block, triangles) in triangles_by_block(mesh) {
+                writeln!(obj, "usemtl {}", self.material_name(block)).unwrap();
+                for triangle in triangles {
+                    let [a, b, c] = triangle.map(|index| index + vertex_offset);
+                    writeln!(obj, "f {a}/{a}/{a} {b}/{b}/{b} {c}/{c}/{c}").unwrap();
+                }
+            }
+            vertex_offset += mesh.vertices.len() as u32;
+        }
+
+        let mut mtl = String::new();
+        for block in self.blocks() {
+            let color = self.registry.color(block);
+            let alpha = self.registry.get(block).map_or(255, |block| block.color[3]);
+            writeln!(mtl, "newmtl {}", self.material_name(block)).unwrap();
+            writeln!(mtl, "Kd {} {} {}", color.x, color.y, color.z).unwrap();
+            writeln!(mtl, "d {}", alpha as f32 / 255.0).unwrap();
+            writeln!(mtl).unwrap();
+        }
+        (obj, mtl)
+    }
+
+    //Binary glTF 2.0 with one node per chunk and one primitive per block type.
+    pub fn to_glb(&self) -> Vec<u8> {
+        let blocks = self.blocks();
+        let mut buffer = Vec::new();
+        let mut buffer_views = Vec::new();
+        let mut accessors = Vec::new();
+        let mut meshes = Vec::new();
+        let mut nodes = Vec::new();
+        for (chunk_pos, mesh) in &self.chunks {
+            let mut primitives = Vec::new();
+            for (block, part) in split_by_block(mesh) {
+                let positions: Vec<Vec3> = part
+                    .vertices
+                    .iter()
+                    .map(|vertex| to_y_up(vertex.position))
+                    .collect();
+                let min = positions.iter().fold(Vec3::MAX, |min, pos| min.min(*pos));
+                let max = positions.iter().fold(Vec3::MIN, |max, pos| max.max(*pos));
+                let mut attribute = |data: Vec<f32>, kind: &str, extra: String| {
+                    let components = if kind == "VEC2" { 2 } else { 3 };
+                    let count = data.len() / components;
+                    let bytes: Vec<u8> =
+                        data.iter().flat_map(|value| value.to_le_bytes()).collect();
+                    let view =
+                        push_buffer_view(&mut buffer, &mut buffer_views, &bytes, GL_ARRAY_BUFFER);
+                    accessors.push(format!(
+                        r#"{{"bufferView":{},"componentType":{},"count":{},"type":"{}"{}}}"#,
+                        view, GL_FLOAT, count, kind, extra
+                    ));
+                    accessors.len() - 1
+                };
+                let position = attribute(
+                    positions.iter().flat_map(|pos| pos.to_array()).collect(),
+                    "VEC3",
+                    format!(
+                        r#","min":[{},{},{}],"max":[{},{},{}]"#,
+                        min.x, min.y, min.z, max.x, max.y, max.z
+                    ),
+                );
+                let normal = attribute(
+                    part.vertices
+                        .iter()
+                        .flat_map(|vertex| to_y_up(vertex.normal).to_array())
+                        .collect(),
+                    "VEC3",
+                    String::new(),
+                );
+                let uv = attribute(
+                    part.vertices
+                        .iter()
+                        .flat_map(|vertex| vertex.uv.to_array())
+                        .collect(),
+                    "VEC2",
+                    String::new(),
+                );
+                let indices: Vec<u8> = part
+                    .indices
+                    .iter()
+                    .flat_map(|index| index.to_le_bytes())
+                    .collect();
+                let view = push_buffer_view(
+                    &mut buffer,
+                    &mut buffer_views,
+                    &indices,
+                    GL_ELEMENT_ARRAY_BUFFER,
+                );
+                accessors.push(format!(
+                    r#"{{"bufferView":{},"componentType":{},"count":{},"type":"SCALAR"}}"#,
+                    view,
+                    GL_UNSIGNED_INT,
+                    part.indices.len()
+                ));
+                let material = blocks.iter().position(|other| *other == block).unwrap();
+                primitives.push(format!(
+                    r#"{{"attributes":{{"POSITION":{},"NORMAL":{},"TEXCOORD_0":{}}},"indices":{},"material":{}}}"#,
+                    position,
+                    normal,
+                    uv,
+                    accessors.len() - 1,
+                    material
+                ));
+            }
+            nodes.push(format!(
+                r#"{{"name":{},"mesh":{}}}"#,
+                json_string(&chunk_name(*chunk_pos)),
+                meshes.len()
+            ));
+            meshes.push(format!(r#"{{"primitives":[{}]}}"#, primitives.join(",")));
+        }
+        let materials: Vec<String> = blocks
+            .iter()
+            .map(|block| {
+                let color = srgb_to_linear(self.registry.color(*block));
+                let alpha = self.registry.get(*block).map_or(255, |block| block.color[3]);
+                format!(
+                    r#"{{"name":{},"pbrMetallicRoughness":{{"baseColorFactor":[{},{},{},{}],"metallicFactor":0,"roughnessFactor":1}}}}"#,
+                    json_string(&self.material_name(*block)),
+                    color.x,
+                    color.y,
+                    color.z,
+                    alpha as f32 / 255.0
+                )
+            })
+            .collect();
+        //Empty arrays are not allowed in glTF, so optional sections are left out when unused.
+        let scene = if nodes.is_empty() {
+            String::new()
+        } else {
+            let node_indices: Vec<String> =
+                (0..nodes.len()).map(|index| index.to_string()).collect();
+            format!(r#""nodes":[{}]"#, node_indices.join(","))
+        };
+        let mut json = format!(
+            r#"{{"asset":{{"version":"2.0","generator":"vxl"}},"scene":0,"scenes":[{{{}}}]"#,
+            scene
+        );
+        let sections = [
+            ("nodes", nodes),
+            ("meshes", meshes),
+            ("materials", materials),
+            ("accessors", accessors),
+            ("bufferViews", buffer_views),
+        ];
+        for (name, items) in sections {
+            if !items.is_empty() {
+                write!(json, r#","{}":[{}]"#, name, items.join(",")).unwrap();
+            }
+        }
+        if !buffer.is_empty() {
+            write!(json, r#","buffers":[{{"byteLength":{}}}]"#, buffer.len()).unwrap();
+        }
+        json.push('}');
+
+        let mut json = json.into_bytes();
+        json.resize(json.len().next_multiple_of(4), b' ');
+        buffer.resize(buffer.len().next_multiple_of(4), 0);
+        let mut total_len = 12 + 8 + json.len();
+        if !buffer.is_empty() {
+            total_len += 8 + buffer.len();
+        }
+        let mut bytes = Vec::with_capacity(total_len);
+        bytes.extend_from_slice(GLB_MAGIC);
+        bytes.extend_from_slice(&GLB_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(total_len as u32).to_le_bytes());
+        bytes.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(GLB_JSON_CHUNK);
+        bytes.extend_from_slice(&json);
+        if !buffer.is_empty() {
+            bytes.extend_from_slice(&(buffer.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(GLB_BIN_CHUNK);
+            bytes.extend_from_slice(&buffer);
+        }
+        bytes
+    }
+
+    //Writes the OBJ to `path` and its materials next to it with an .mtl extension.
+    pub fn save_obj(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let path = path.as_ref();
+        let mtl_path = path.with_extension("mtl");
+        let mtl_file_name = mtl_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid OBJ file name")
+            })?;
+        let (obj, mtl) = self.to_obj(mtl_file_name);
+        std::fs::write(path, obj)?;
+        std::fs::write(&mtl_path, mtl)
+    }
+
+    pub fn save_glb(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_glb())
+    }
+
+    //Block ids used by any exported chunk, in ascending order. Each one becomes a material.
+    fn blocks(&self) -> Vec<u8> {
+        let mut used = [false; 256];
+        for (_, mesh) in &self.chunks {
+            for vertex in &mesh.vertices {
+                used[vertex.block as usize] = true;
+            }
+        }
+        (0..=255u8).filter(|block| used[*block as usize]).collect()
+    }
+
+    //Registry names with whitespace replaced, since OBJ material names end at whitespace.
+    fn material_name(&self, block: u8) -> String {
+        match self.registry.get(block) {
+            Some(definition) => definition
+                .name
+                .chars()
+                .map(|c| if c.is_whitespace() { '_' } else { c })
+                .collect(),
+            None => format!("block_{}", block),
+        }
+    }
+}
+
+fn chunk_name(chunk_pos: IVec3) -> String {
+    format!("chunk_{}_{}_{}", chunk_pos.x, chunk_pos.y, chunk_pos.z)
+}
+
+//glTF base colors are linear, the registry colors sRGB.
+fn srgb_to_linear(color: Vec3) -> Vec3 {
+    color.map(|c| {
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    })
+}
+
+//Subtracting from zero instead of negating avoids writing -0 for positions on the y = 0 plane.
+fn to_y_up(v: Vec3) -> Vec3 {
+    Vec3::new(v.x, v.z, 0.0 - v.y)
+}
+
+//Triangles of the mesh grouped by the block of their first vertex. Quads never mix blocks.
+fn triangles_by_block(mesh: &ChunkMesh) -> BTreeMap<u8, Vec<[u32; 3]>> {
+    let mut triangles: BTreeMap<u8, Vec<[u32; 3]>> = BTreeMap::new();
+    for triangle in mesh.indices.chunks_exact(3) {
+        let block = mesh.vertices[triangle[0] as usize].block;
+        triangles
+            .entry(block)
+            .or_default()
+            .push([triangle[0], triangle[1], triangle[2]]);
+    }
+    triangles
+}
+
+//One mesh per block with only the vertices its triangles use, reindexed from zero.
+fn split_by_block(mesh: &ChunkMesh) -> BTreeMap<u8, ChunkMesh> {
+    let mut parts = BTreeMap::new();
+    for (block, triangles) in triangles_by_block(mesh) {
+        let mut part = ChunkMesh::default();
+        let mut remap = vec![u32::MAX; mesh.vertices.len()];
+        for index in triangles.into_iter().flatten() {
+            if remap[index as usize] == u32::MAX {
+                remap[index as usize] = part.vertices.len() as u32;
+                part.vertices.push(mesh.vertices[index as usize]);
+            }
+            part.indices.push(remap[index as usize]);
+        }
+        parts.insert(block, part);
+    }
+    parts
+}
+
+//Appends `data` to the binary buffer and returns the index of its new buffer view. Every element
+//is 4 bytes, so views stay aligned without padding.
+fn push_buffer_view(
+    buffer: &mut Vec<u8>,
+    buffer_views: &mut Vec<String>,
+    data: &[u8],
+    target: u32,
+) -> usize {
+    buffer_views.push(format!(
+        r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":{}}}"#,
+        buffer.len(),
+        data.len(),
+        target
+    ));
+    buffer.extend_from_slice(data);
+    buffer_views.len() - 1
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => write!(escaped, "\\u{:04x}", c as u32).unwrap(),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
@@ -0,0 +1,153 @@
+#[cfg(test)]
+mod tests {
+    use crate::world::block_registry::STONE;
+    use crate::world::edit::Region;
+    use crate::world::tests::tests::create_test_world;
+    use glam::{IVec3, ivec3};
+
+    fn lines<'a>(text: &'a str, prefix: &str) -> Vec<&'a str> {
+        text.lines()
+            .filter(|line| line.starts_with(prefix))
+            .collect()
+    }
+
+    fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_export_chunks() {
+        let mut world = create_test_world();
+        world.set_block(ivec3(3, 4, 70), STONE);
+        world.set_block(ivec3(40, 4, 70), 200);
+        let export = world.export_chunks([ivec3(0, 0, 2), ivec3(1, 0, 2), ivec3(2, 0, 2)]);
+        //The third chunk is all air.
+        assert_eq!(export.chunks.len(), 2);
+        assert!(world.export_chunks([ivec3(0, 0, 3)]).is_empty());
+        assert!(world.export_chunks([ivec3(99, 0, 0)]).is_empty());
+
+        let region = Region::new(ivec3(0, 0, 64), ivec3(32, 31, 95));
+        let positions: Vec<IVec3> = world
+            .export_region(&region)
+            .chunks
+            .iter()
+            .map(|(chunk_pos, _)| *chunk_pos)
+            .collect();
+        assert_eq!(positions, vec![ivec3(0, 0, 2), ivec3(1, 0, 2)]);
+    }
+
+    #[test]
+    fn test_obj() {
+        let mut world = create_test_world();
+        world.set_block(ivec3(3, 4, 70), STONE);
+        world.set_block(ivec3(3, 4, 71), STONE);
+        world.set_block(ivec3(40, 4, 70), 200);
+        let export = world.export_region(&Region::new(ivec3(0, 0, 64), ivec3(63, 31, 95)));
+        let (obj, mtl) = export.to_obj("world.mtl");
+
+        assert_eq!(lines(&obj, "mtllib "), vec!["mtllib world.mtl"]);
+        assert_eq!(lines(&obj, "o ").len(), 2);
+        assert_eq!(lines(&obj, "v ").len(), 48);
+        assert_eq!(lines(&obj, "vt ").len(), 48);
+        assert_eq!(lines(&obj, "vn ").len(), 48);
+        assert_eq!(lines(&obj, "f ").len(), 24);
+        assert_eq!(
+            lines(&obj, "usemtl "),
+            vec!["usemtl stone", "usemtl block_200"]
+        );
+        //Indices are 1-based and continue across chunks.
+        let indices: Vec<u32> = lines(&obj, "f ")
+            .iter()
+            .flat_map(|line| line[2..].split(' '))
+            .map(|corner| corner.split('/').next().unwrap().parse().unwrap())
+            .collect();
+        assert_eq!(indices.iter().min(), Some(&1));
+        assert_eq!(indices.iter().max(), Some(&48));
+        //World z is up and becomes y.
+        assert!(obj.contains("\nv 3 72 -4\n"));
+        assert!(obj.contains("\nvn 0 1 0\n"));
+
+        assert_eq!(
+            lines(&mtl, "newmtl "),
+            vec!["newmtl stone", "newmtl block_200"]
+        );
+        assert_eq!(
+            lines(&mtl, "Kd ")[0],
+            format!(
+                "Kd {} {} {}",
+                128.0f32 / 255.0,
+                128.0f32 / 255.0,
+                133.0f32 / 255.0
+            )
+        );
+    }
+
+    #[test]
+    fn test_glb() {
+        let mut world = create_test_world();
+        world.set_block(ivec3(3, 4, 70), STONE);
+        world.set_block(ivec3(4, 4, 70), 200);
+        let bytes = world.export_chunks([ivec3(0, 0, 2)]).to_glb();
+
+        assert_eq!(&bytes[0..4], b"glTF");
+        assert_eq!(read_u32(&bytes, 4), 2);
+        assert_eq!(read_u32(&bytes, 8) as usize, bytes.len());
+        let json_len = read_u32(&bytes, 12) as usize;
+        assert_eq!(&bytes[16..20], b"JSON");
+        assert_eq!(json_len % 4, 0);
+        let json = std::str::from_utf8(&bytes[20..20 + json_len]).unwrap();
+        let bin = 20 + json_len;
+        assert_eq!(&bytes[bin + 4..bin + 8], b"BIN\0");
+
+        //Two blocks with five visible faces each, 4 vertices of 32 bytes and 6 indices per face.
+        let bin_len = read_u32(&bytes, bin) as usize;
+        assert_eq!(bin_len, 2 * 5 * (4 * 32 + 6 * 4));
+        assert_eq!(bytes.len(), bin + 8 + bin_len);
+        assert_eq!(json.matches("\"POSITION\"").count(), 2);
+        assert!(json.contains(r#""name":"stone""#));
+        assert!(json.contains(r#""name":"chunk_0_0_2""#));
+        assert!(json.contains(r#""min":[3,70,-5],"max":[4,71,-4]"#));
+        //Stone is (128, 128, 133) in sRGB.
+        let factor = json.split(r#""baseColorFactor":["#).nth(1).unwrap();
+        let factor: Vec<f32> = factor[..factor.find(']').unwrap()]
+            .split(',')
+            .map(|value| value.parse().unwrap())
+            .collect();
+        assert!((factor[0] - 0.2158).abs() < 1e-3, "{:?}", factor);
+        assert!((factor[2] - 0.2346).abs() < 1e-3, "{:?}", factor);
+        assert_eq!(factor[3], 1.0);
+
+        //The first accessor holds stone positions at the start of the buffer.
+        let first = f32::from_le_bytes(bytes[bin + 8..bin + 12].try_into().unwrap());
+        assert!(first == 3.0 || first == 4.0);
+    }
+
+    #[test]
+    fn test_glb_empty() {
+        let world = create_test_world();
+        let bytes = world.export_chunks([]).to_glb();
+        assert_eq!(read_u32(&bytes, 8) as usize, bytes.len());
+        let json = std::str::from_utf8(&bytes[20..]).unwrap();
+        assert!(json.contains(r#""scenes":[{}]"#));
+        assert!(!json.contains("buffers"));
+    }
+
+    #[test]
+    fn test_save_obj() {
+        let mut world = create_test_world();
+        world.set_block(ivec3(3, 4, 70), STONE);
+        let dir = std::env::temp_dir().join(format!("vxl_mesh_export_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let export = world.export_chunks([ivec3(0, 0, 2)]);
+        export.save_obj(dir.join("chunk.obj")).unwrap();
+        export.save_glb(dir.join("chunk.glb")).unwrap();
+        let obj = std::fs::read_to_string(dir.join("chunk.obj"));
+        let mtl = std::fs::read_to_string(dir.join("chunk.mtl"));
+        let glb = std::fs::read(dir.join("chunk.glb"));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(obj.unwrap().starts_with("mtllib chunk.mtl\n"));
+        assert!(mtl.unwrap().starts_with("newmtl stone\n"));
+        assert_eq!(glb.unwrap(), export.to_glb());
+    }
+}
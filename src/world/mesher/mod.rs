@@ -0,0 +1,271 @@
+mod tests;
+
+use crate::world::World;
+use crate::world::chunk::{CHUNK_SIDE_SIZE, Chunk};
+use crate::world::light::{CHANNELS, ChunkLight, LightChannel, MAX_LIGHT, is_transparent};
+use glam::{IVec3, Vec2, Vec3, ivec3};
+
+const AXES: [IVec3; 3] = [IVec3::X, IVec3::Y, IVec3::Z];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeshVertex {
+    pub position: Vec3,
+    pub normal: Vec3,
+    pub uv: Vec2,
+    pub block: u8,
+    pub color: Vec3,
+    //Smoothed light levels around the vertex, from 0 to 1. Block light is per colour channel.
+    pub sky_light: f32,
+    pub block_light: Vec3,
+    //Ambient occlusion from the blocks around the vertex, from 0 (fully occluded) to 3 (open).
+    pub ao: u8,
+}
+
+#[derive(Default)]
+pub struct ChunkMesh {
+    pub vertices: Vec<MeshVertex>,
+    pub indices: Vec<u32>,
+}
+
+impl ChunkMesh {
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    //Corners go counter-clockwise around the u x v axis, which is the face normal for positive
+    //faces. Triangles are wound so that (b - a) x (c - a) points along the normal.
+    fn push_quad(&mut self, quad: Quad) {
+        let uvs = [
+            Vec2::ZERO,
+            Vec2::new(quad.size.x, 0.0),
+            quad.size,
+            Vec2::new(0.0, quad.size.y),
+        ];
+        let base = self.vertices.len() as u32;
+        let corners = quad.corners.iter().zip(uvs).zip(quad.face.light);
+        for (((corner, uv), light), ao) in corners.zip(quad.face.ao) {
+            let level = |channel: usize| light[channel] as f32 / (4.0 * MAX_LIGHT as f32);
+            self.vertices.push(MeshVertex {
+                position: *corner,
+                normal: quad.normal,
+                uv,
+                block: quad.face.block,
+                color: quad.color,
+                sky_light: level(0),
+                block_light: Vec3::new(level(1), level(2), level(3)),
+                ao,
+            });
+        }
+        //Quads are split along the less occluded diagonal. Splitting along the other one would
+        //stretch a dark corner across the whole quad, depending on the quad orientation.
+        let positive = quad.normal.max_element() > 0.0;
+        let ao = quad.face.ao;
+        let flip = ao[0] + ao[2] < ao[1] + ao[3];
+        let order: [u32; 6] = match (positive, flip) {
+            (true, false) => [0, 1, 2, 0, 2, 3],
+            (true, true) => [0, 1, 3, 1, 2, 3],
+            (false, false) => [0, 2, 1, 0, 3, 2],
+            (false, true) => [0, 3, 1, 1, 3, 2],
+        };
+        self.indices.extend(order.iter().map(|index| base + index));
+    }
+}
+
+//Blocks and light of the chunk being meshed and of all 26 neighbours, so faces on the chunk border
+//can be culled and lit from the adjacent chunks. Unloaded and all-air neighbours read as air, and
+//unloaded ones as open sky.
+struct ChunkNeighbourhood<'a> {
+    chunks: [Option<&'a Chunk>; 27],
+    lights: [Option<&'a ChunkLight>; 27],
+}
+
+impl<'a> ChunkNeighbourhood<'a> {
+    fn new(world: &'a World, chunk_pos: IVec3) -> Self {
+        let offset = |index: usize| {
+            let index = index as i32;
+            ivec3(index % 3, index / 3 % 3, index / 9) - 1
+        };
+        Self {
+            chunks: std::array::from_fn(|index| {
+                world
+                    .loaded_chunks
+                    .get(&(chunk_pos + offset(index)))
+                    .and_then(|chunk| chunk.as_deref())
+            }),
+            lights: std::array::from_fn(|index| world.chunk_light(chunk_pos + offset(index))),
+        }
+    }
+
+    //Index of the chunk holding `local_pos`, which may be up to one chunk outside, and the
+    //position inside it.
+    fn locate(local_pos: IVec3) -> (usize, IVec3) {
+        let (offset, local_pos) = Chunk::split_world_pos(local_pos);
+        let offset = offset + 1;
+        ((offset.x + offset.y * 3 + offset.z * 9) as usize, local_pos)
+    }
+
+    fn get_block(&self, local_pos: IVec3) -> u8 {
+        let (index, local_pos) = Self::locate(local_pos);
+        self.chunks[index].map_or(0, |chunk| chunk.get_block(local_pos))
+    }
+
+    fn get_light(&self, local_pos: IVec3, channel: LightChannel) -> u8 {
+        let (index, local_pos) = Self::locate(local_pos);
+        match self.lights[index] {
+            Some(light) => light.get(local_pos, channel),
+            None if channel == LightChannel::Sky => MAX_LIGHT,
+            None => 0,
+        }
+    }
+
+    //Light at the four corners of the face whose air side is `front`, in quarter levels. Each
+    //corner averages the air voxels among `front` and the three others sharing the corner. The
+    //diagonal one is skipped when both voxels beside it are solid, so light does not leak
+    //through edges.
+    fn corner_light(&self, front: IVec3, u_axis: IVec3, v_axis: IVec3) -> [CornerLight; 4] {
+        [(-1, -1), (1, -1), (1, 1), (-1, 1)].map(|(u, v)| {
+            let mut sums = [0u32; CHANNELS.len()];
+            let mut samples = 0;
+            let mut add_sample = |local_pos: IVec3| {
+                for (sum, channel) in sums.iter_mut().zip(CHANNELS) {
+                    *sum += self.get_light(local_pos, channel) as u32;
+                }
+                samples += 1;
+            };
+            let side_u = front + u_axis * u;
+            let side_v = front + v_axis * v;
+            let open_u = is_transparent(self.get_block(side_u));
+            let open_v = is_transparent(self.get_block(side_v));
+            add_sample(front);
+            if open_u {
+                add_sample(side_u);
+            }
+            if open_v {
+                add_sample(side_v);
+            }
+            let diagonal = front + u_axis * u + v_axis * v;
+            if (open_u || open_v) && is_transparent(self.get_block(diagonal)) {
+                add_sample(diagonal);
+            }
+            sums.map(|sum| ((sum * 4 + samples / 2) / samples) as u8)
+        })
+    }
+
+    //Ambient occlusion at the four corners of the face whose air side is `front`, counting the
+    //solid voxels among the two beside the corner and the diagonal one. A corner between two
+    //solid sides is fully occluded whatever the diagonal is.
+    fn corner_occlusion(&self, front: IVec3, u_axis: IVec3, v_axis: IVec3) -> [u8; 4] {
+        [(-1, -1), (1, -1), (1, 1), (-1, 1)].map(|(u, v)| {
+            let solid = |local_pos: IVec3| !is_transparent(self.get_block(local_pos)) as u8;
+            let side_u = solid(front + u_axis * u);
+            let side_v = solid(front + v_axis * v);
+            let diagonal = solid(front + u_axis * u + v_axis * v);
+            if side_u == 1 && side_v == 1 {
+                0
+            } else {
+                3 - side_u - side_v - diagonal
+            }
+        })
+    }
+}
+
+//Light levels of each channel at a face corner, in quarter levels.
+type CornerLight = [u8; CHANNELS.len()];
+
+//What a visible face looks like. Faces only merge if they are equal, so merged quads keep the
+//corner light and occlusion of every face they cover.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct Face {
+    block: u8,
+    light: [CornerLight; 4],
+    ao: [u8; 4],
+}
+
+//Greedy mesher: visible faces of each slice are merged into the largest rectangles of the same
+//block, light and occlusion. Vertex positions are in world voxel units.
+pub fn mesh_chunk(world: &World, chunk_pos: IVec3) -> ChunkMesh {
+    let mut mesh = ChunkMesh::default();
+    let Some(Some(_)) = world.loaded_chunks.get(&chunk_pos) else {
+        return mesh;
+    };
+    let neighbourhood = ChunkNeighbourhood::new(world, chunk_pos);
+    let chunk_min = (chunk_pos * CHUNK_SIDE_SIZE).as_vec3();
+    let side = CHUNK_SIDE_SIZE as usize;
+    let mut mask = vec![Face::default(); side * side];
+    for (axis_index, axis) in AXES.iter().enumerate() {
+        let u_axis = AXES[(axis_index + 1) % 3];
+        let v_axis = AXES[(axis_index + 2) % 3];
+        for direction in [1, -1] {
+            let normal = *axis * direction;
+            for slice in 0..CHUNK_SIDE_SIZE {
+                for v in 0..CHUNK_SIDE_SIZE {
+                    for u in 0..CHUNK_SIDE_SIZE {
+                        let local_pos = *axis * slice + u_axis * u + v_axis * v;
+                        let block = neighbourhood.get_block(local_pos);
+                        let front = local_pos + normal;
+                        let visible = block != 0 && neighbourhood.get_block(front) == 0;
+                        mask[u as usize + v as usize * side] = if visible {
+                            Face {
+                                block,
+                                light: neighbourhood.corner_light(front, u_axis, v_axis),
+                                ao: neighbourhood.corner_occlusion(front, u_axis, v_axis),
+                            }
+                        } else {
+                            Face::default()
+                        };
+                    }
+                }
+                let plane = *axis * (slice + (direction > 0) as i32);
+                for v in 0..side {
+                    let mut u = 0;
+                    while u < side {
+                        let face = mask[u + v * side];
+                        if face.block == 0 {
+                            u += 1;
+                            continue;
+                        }
+                        let mut width = 1;
+                        while u + width < side && mask[u + width + v * side] == face {
+                            width += 1;
+                        }
+                        let mut height = 1;
+                        'grow: while v + height < side {
+                            for i in 0..width {
+                                if mask[u + i + (v + height) * side] != face {
+                                    break 'grow;
+                                }
+                            }
+                            height += 1;
+                        }
+                        for row in 0..height {
+                            mask[u + (v + row) * side..u + width + (v + row) * side]
+                                .fill(Face::default());
+                        }
+                        let origin =
+                            chunk_min + (plane + u_axis * u as i32 + v_axis * v as i32).as_vec3();
+                        let du = (u_axis * width as i32).as_vec3();
+                        let dv = (v_axis * height as i32).as_vec3();
+                        let quad = Quad {
+                            corners: [origin, origin + du, origin + du + dv, origin + dv],
+                            size: Vec2::new(width as f32, height as f32),
+                            normal: normal.as_vec3(),
+                            face,
+                            color: world.registry().color(face.block),
+                        };
+                        mesh.push_quad(quad);
+                        u += width;
+                    }
+                }
+            }
+        }
+    }
+    mesh
+}
+
+struct Quad {
+    corners: [Vec3; 4],
+    size: Vec2,
+    normal: Vec3,
+    face: Face,
+    color: Vec3,
+}
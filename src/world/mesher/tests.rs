@@ -0,0 +1,233 @@
+#[cfg(test)]
+mod tests {
+    use crate::world::World;
+    use crate::world::block_registry::LAMP;
+    use crate::world::mesher::{ChunkMesh, mesh_chunk};
+    use crate::world::tests::tests::create_test_world;
+    use glam::{IVec3, Vec3};
+
+    const CHUNK: IVec3 = IVec3::new(0, 0, 2);
+
+    fn quad_count(mesh: &ChunkMesh) -> usize {
+        assert_eq!(mesh.vertices.len() % 4, 0);
+        assert_eq!(mesh.indices.len(), mesh.vertices.len() / 4 * 6);
+        mesh.vertices.len() / 4
+    }
+
+    #[test]
+    fn test_single_block() {
+        let mut world = create_test_world();
+        world.set_block(IVec3::new(3, 4, 70), 2);
+        let mesh = mesh_chunk(&world, CHUNK);
+        assert_eq!(quad_count(&mesh), 6);
+        assert!(mesh.vertices.iter().all(|vertex| vertex.block == 2));
+        let min = mesh
+            .vertices
+            .iter()
+            .fold(Vec3::MAX, |min, vertex| min.min(vertex.position));
+        let max = mesh
+            .vertices
+            .iter()
+            .fold(Vec3::MIN, |max, vertex| max.max(vertex.position));
+        assert_eq!(min, Vec3::new(3.0, 4.0, 70.0));
+        assert_eq!(max, Vec3::new(4.0, 5.0, 71.0));
+    }
+
+    #[test]
+    fn test_merges_identical_neighbours() {
+        let mut world = create_test_world();
+        for x in 0..4 {
+            world.set_block(IVec3::new(x, 0, 70), 1);
+        }
+        let mesh = mesh_chunk(&world, CHUNK);
+        //The shaded bottom only merges between the two ends, whose outer corners are brighter.
+        assert_eq!(quad_count(&mesh), 8);
+        let top = mesh
+            .vertices
+            .iter()
+            .find(|vertex| vertex.normal == Vec3::Z)
+            .unwrap();
+        assert_eq!(top.block, 1);
+        let top_uv_max = mesh
+            .vertices
+            .iter()
+            .filter(|vertex| vertex.normal == Vec3::Z)
+            .fold(0.0f32, |max, vertex| max.max(vertex.uv.max_element()));
+        assert_eq!(top_uv_max, 4.0);
+    }
+
+    #[test]
+    fn test_different_blocks_do_not_merge() {
+        let mut world = create_test_world();
+        world.set_block(IVec3::new(0, 0, 70), 1);
+        world.set_block(IVec3::new(1, 0, 70), 2);
+        let mesh = mesh_chunk(&world, CHUNK);
+        //The shared face is hidden, every other face stays separate.
+        assert_eq!(quad_count(&mesh), 10);
+    }
+
+    #[test]
+    fn test_culls_faces_across_chunk_border() {
+        let mut world = create_test_world();
+        world.set_block(IVec3::new(31, 0, 70), 1);
+        world.set_block(IVec3::new(32, 0, 70), 1);
+        let mesh = mesh_chunk(&world, CHUNK);
+        assert_eq!(quad_count(&mesh), 5);
+        assert!(mesh.vertices.iter().all(|vertex| vertex.normal != Vec3::X));
+        let neighbour_mesh = mesh_chunk(&world, CHUNK + IVec3::X);
+        assert_eq!(quad_count(&neighbour_mesh), 5);
+        assert!(
+            neighbour_mesh
+                .vertices
+                .iter()
+                .all(|vertex| vertex.normal != -Vec3::X)
+        );
+    }
+
+    #[test]
+    fn test_winding_matches_normal() {
+        let mut world = create_test_world();
+        world.set_block(IVec3::new(5, 5, 70), 1);
+        world.set_block(IVec3::new(5, 6, 70), 1);
+        world.set_block(IVec3::new(5, 6, 71), 3);
+        let mesh = mesh_chunk(&world, CHUNK);
+        for triangle in mesh.indices.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[triangle[i] as usize]);
+            let normal = (b.position - a.position).cross(c.position - a.position);
+            assert!(normal.normalize().abs_diff_eq(a.normal, 1e-6));
+        }
+    }
+
+    #[test]
+    fn test_vertex_light() {
+        let mut world = create_test_world();
+        world.set_block(IVec3::new(5, 5, 70), 1);
+        world.set_block(IVec3::new(5, 5, 68), 1);
+        let mesh = mesh_chunk(&world, CHUNK);
+        let top_face = |z: f32| {
+            mesh.vertices
+                .iter()
+                .filter(move |vertex| vertex.normal == Vec3::Z && vertex.position.z == z)
+        };
+        assert!(top_face(71.0).all(|vertex| vertex.sky_light == 1.0));
+        //The lower block sits in the shadow of the upper one, lit from the side.
+        assert!(top_face(69.0).all(|vertex| vertex.sky_light < 1.0 && vertex.sky_light > 0.0));
+        assert!(
+            mesh.vertices
+                .iter()
+                .all(|vertex| vertex.block_light == Vec3::ZERO)
+        );
+
+        world.set_block(IVec3::new(5, 6, 69), LAMP);
+        let mesh = mesh_chunk(&world, CHUNK);
+        assert!(
+            mesh.vertices
+                .iter()
+                .any(|vertex| vertex.normal == Vec3::Z && vertex.block_light.x > 0.0)
+        );
+    }
+
+    //Occlusion of the top face corner at `corner` of the block at (5, 5, 70).
+    fn top_corner_ao(world: &World, corner: (f32, f32)) -> u8 {
+        let mesh = mesh_chunk(world, CHUNK);
+        mesh.vertices
+            .iter()
+            .find(|vertex| {
+                vertex.normal == Vec3::Z && vertex.position == Vec3::new(corner.0, corner.1, 71.0)
+            })
+            .unwrap()
+            .ao
+    }
+
+    #[test]
+    fn test_ambient_occlusion() {
+        let mut world = create_test_world();
+        world.set_block(IVec3::new(5, 5, 70), 1);
+        assert_eq!(top_corner_ao(&world, (6.0, 6.0)), 3);
+
+        //A diagonal block only darkens the corner it touches.
+        world.set_block(IVec3::new(6, 6, 71), 1);
+        assert_eq!(top_corner_ao(&world, (6.0, 6.0)), 2);
+        assert_eq!(top_corner_ao(&world, (5.0, 6.0)), 3);
+        assert_eq!(top_corner_ao(&world, (6.0, 5.0)), 3);
+
+        //A block along an edge darkens both corners of that edge.
+        world.set_block(IVec3::new(6, 6, 71), 0);
+        world.set_block(IVec3::new(6, 5, 71), 1);
+        assert_eq!(top_corner_ao(&world, (6.0, 5.0)), 2);
+        assert_eq!(top_corner_ao(&world, (6.0, 6.0)), 2);
+        assert_eq!(top_corner_ao(&world, (5.0, 5.0)), 3);
+        world.set_block(IVec3::new(6, 6, 71), 1);
+        assert_eq!(top_corner_ao(&world, (6.0, 6.0)), 1);
+
+        //Two solid sides fully occlude the corner between them, with or without the diagonal.
+        world.set_block(IVec3::new(5, 6, 71), 1);
+        assert_eq!(top_corner_ao(&world, (6.0, 6.0)), 0);
+        world.set_block(IVec3::new(6, 6, 71), 0);
+        assert_eq!(top_corner_ao(&world, (6.0, 6.0)), 0);
+        assert_eq!(top_corner_ao(&world, (5.0, 5.0)), 3);
+    }
+
+    #[test]
+    fn test_occluded_faces_do_not_merge() {
+        let mut world = create_test_world();
+        for x in 0..8 {
+            world.set_block(IVec3::new(x, 0, 70), 1);
+        }
+        world.set_block(IVec3::new(1, 1, 71), 2);
+        let mesh = mesh_chunk(&world, CHUNK);
+        let top_quads = mesh
+            .vertices
+            .iter()
+            .filter(|vertex| vertex.normal == Vec3::Z && vertex.block == 1)
+            .count()
+            / 4;
+        //The three blocks touching the occluder at an edge or corner keep their own quads, the
+        //rest of the row merges.
+        assert_eq!(top_quads, 4);
+    }
+
+    #[test]
+    fn test_quad_split_follows_occlusion() {
+        let mut world = create_test_world();
+        world.set_block(IVec3::new(5, 5, 70), 1);
+        world.set_block(IVec3::new(6, 6, 71), 1);
+        let mesh = mesh_chunk(&world, CHUNK);
+        let top = mesh
+            .vertices
+            .iter()
+            .position(|vertex| vertex.normal == Vec3::Z && vertex.position.z == 71.0)
+            .unwrap() as u32;
+        let triangles: Vec<&[u32]> = mesh
+            .indices
+            .chunks(3)
+            .filter(|triangle| triangle[0] >= top && triangle[0] < top + 4)
+            .collect();
+        assert_eq!(triangles.len(), 2);
+        //The darkened corner is only part of one triangle.
+        let dark = mesh.vertices[top as usize..top as usize + 4]
+            .iter()
+            .position(|vertex| vertex.ao == 2)
+            .unwrap() as u32
+            + top;
+        assert_eq!(
+            triangles
+                .iter()
+                .filter(|triangle| triangle.contains(&dark))
+                .count(),
+            1
+        );
+        for triangle in &triangles {
+            let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[triangle[i] as usize]);
+            let normal = (b.position - a.position).cross(c.position - a.position);
+            assert!(normal.normalize().abs_diff_eq(a.normal, 1e-6));
+        }
+    }
+
+    #[test]
+    fn test_unloaded_chunk_is_empty() {
+        let world = create_test_world();
+        assert!(mesh_chunk(&world, IVec3::new(100, 0, 0)).is_empty());
+        assert!(mesh_chunk(&world, CHUNK).is_empty());
+    }
+}
@@ -1,51 +1,138 @@
-use crate::utility::sparse_spatial_octree::SparseSpatialOctree;
-use crate::world::chunk::Chunk;
+use crate::utility::SpatialOctree;
+use crate::utility::sparse_spatial_octree::{RadiusError, SparseSpatialOctree};
+use crate::world::block_registry::BlockRegistry;
+use crate::world::block_tick::BlockTicks;
+use crate::world::chunk::{CHUNK_SIDE_SIZE, Chunk};
+use crate::world::fluid::FluidState;
+use crate::world::heightmap::Heightmap;
+use crate::world::history::{DEFAULT_MEMORY_LIMIT, EditHistory};
+use crate::world::light::ChunkLight;
+use crate::world::seed::{DEFAULT_SEED, WorldSeed};
+use crate::world::terrain::Terrain;
 use glam::{IVec3, Vec3, ivec3};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
+pub(crate) mod biome;
+pub(crate) mod block_registry;
+pub(crate) mod block_tick;
 pub(crate) mod chunk;
+pub(crate) mod decoration;
+pub(crate) mod edit;
+pub(crate) mod fluid;
+pub(crate) mod gravity;
+pub(crate) mod heightmap;
+pub(crate) mod history;
+pub(crate) mod light;
+pub(crate) mod mesh_export;
+pub(crate) mod mesher;
+pub(crate) mod raycast;
+pub(crate) mod schematic;
+pub(crate) mod seed;
+pub(crate) mod sparse_voxel_dag;
+pub(crate) mod structures;
+pub(crate) mod terrain;
+pub(crate) mod tests;
+pub(crate) mod vox;
+
+pub(crate) const NEIGHBOUR_OFFSETS: [IVec3; 6] = [
+    IVec3::NEG_X,
+    IVec3::X,
+    IVec3::NEG_Y,
+    IVec3::Y,
+    IVec3::NEG_Z,
+    IVec3::Z,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockChange {
+    pub position: IVec3,
+    pub old: u8,
+    pub new: u8,
+}
 
 pub struct World {
     pub loaded_chunks: HashMap<IVec3, Option<Box<Chunk>>>,
+    light: HashMap<IVec3, Box<ChunkLight>>,
     visible_map: SparseSpatialOctree,
     last_map_center: IVec3,
     last_player_pos: IVec3,
+    dirty_chunks: HashSet<IVec3>,
+    //Only recorded once someone asks for them with record_block_changes.
+    block_changes: Option<Vec<BlockChange>>,
+    history: EditHistory,
+    registry: BlockRegistry,
+    fluids: FluidState,
+    block_ticks: BlockTicks,
+    terrain: Terrain,
+    //Simulation ticks run so far.
+    tick_count: u64,
 }
 
 impl World {
-    pub fn new(radius: i32) -> Self {
+    pub fn new(radius: i32) -> Result<Self, RadiusError> {
+        Self::with_seed(radius, DEFAULT_SEED)
+    }
+
+    pub fn with_seed(radius: i32, seed: WorldSeed) -> Result<Self, RadiusError> {
+        Self::with_terrain(radius, Terrain::new(seed))
+    }
+
+    //Generates the terrain from a heightmap instead of noise. Biomes, ores, trees and structures
+    //still come from the seed.
+    pub fn with_heightmap(
+        radius: i32,
+        seed: WorldSeed,
+        heightmap: Heightmap,
+    ) -> Result<Self, RadiusError> {
+        Self::with_terrain(radius, Terrain::with_heightmap(seed, heightmap))
+    }
+
+    fn with_terrain(radius: i32, terrain: Terrain) -> Result<Self, RadiusError> {
         let last_map_center = IVec3::ZERO;
         let last_player_pos = IVec3::ZERO;
         let loaded_chunks: HashMap<IVec3, Option<Box<Chunk>>> = HashMap::new();
-        let visible_map = SparseSpatialOctree::new(last_map_center, radius);
+        let visible_map = SparseSpatialOctree::new(last_map_center, radius)?;
         let mut chunk = Self {
             loaded_chunks,
+            light: HashMap::new(),
             visible_map,
             last_map_center,
             last_player_pos,
+            dirty_chunks: HashSet::new(),
+            block_changes: None,
+            history: EditHistory::new(DEFAULT_MEMORY_LIMIT),
+            registry: BlockRegistry::default(),
+            fluids: FluidState::default(),
+            block_ticks: BlockTicks::default(),
+            terrain,
+            tick_count: 0,
         };
         chunk.initialize_map(radius);
-        chunk
+        Ok(chunk)
     }
 
     #[inline]
     fn initialize_map(&mut self, radius: i32) {
+        let mut inserted = Vec::new();
         for x in -radius..radius + 1 {
             for y in -radius..radius + 1 {
                 for z in -radius..radius + 1 {
                     let key = ivec3(x, y, z);
-                    let chunk = self.load_chunk(key);
                     if self.visible_map.is_in_sphere(&key) {
+                        let chunk = self.load_chunk(key);
                         self.visible_map.add(key, false);
-                        self.loaded_chunks.insert(key, chunk);
+                        self.insert_chunk(key, chunk);
+                        inserted.push(key);
                     }
                 }
             }
         }
+        self.light_new_chunks(&inserted);
     }
 
+    //Keeps the loaded chunks centered on the chunk the player is in.
     pub fn on_player_moved(&mut self, pos: Vec3) {
-        let pos = pos.floor().as_ivec3();
+        let (pos, _) = Chunk::split_world_pos(pos.floor().as_ivec3());
         if self.last_player_pos == pos {
             return;
         };
@@ -57,42 +144,175 @@ impl World {
         if new_center == self.last_map_center {
             return;
         }
-        let old_center = self.last_map_center;
-        let delta_pos = new_center - old_center;
-        if delta_pos.x <= 1
-            && delta_pos.x > 0
-            && delta_pos.y <= 1
-            && delta_pos.y > 0
-            && delta_pos.z <= 1
-            && delta_pos.z > 0
-        {
-            return;
+        let recentered = self.visible_map.recenter(new_center);
+        let dropped = recentered.dropped.iter().copied().collect();
+        self.fluids.drop_chunks(&dropped);
+        self.block_ticks.suspend_chunks(&dropped, self.tick_count);
+        for world_pos in recentered.dropped {
+            self.loaded_chunks.remove(&world_pos);
+            self.light.remove(&world_pos);
+            self.dirty_chunks.remove(&world_pos);
+        }
+        for world_pos in &recentered.covered {
+            let chunk = self.load_chunk(*world_pos);
+            self.visible_map.add(*world_pos, false);
+            self.insert_chunk(*world_pos, chunk);
+            self.block_ticks.resume_chunk(*world_pos, self.tick_count);
+        }
+        self.light_new_chunks(&recentered.covered);
+        self.last_map_center = new_center;
+    }
+
+    pub fn seed(&self) -> WorldSeed {
+        self.terrain.seed()
+    }
+
+    pub fn registry(&self) -> &BlockRegistry {
+        &self.registry
+    }
+
+    pub fn registry_mut(&mut self) -> &mut BlockRegistry {
+        &mut self.registry
+    }
+
+    fn load_chunk(&mut self, pos: IVec3) -> Option<Box<Chunk>> {
+        Chunk::new(pos, &self.terrain)
+    }
+
+    //Returns None if the chunk containing `world_pos` is not loaded.
+    pub fn get_block(&self, world_pos: IVec3) -> Option<u8> {
+        let (chunk_pos, local_pos) = Chunk::split_world_pos(world_pos);
+        match self.loaded_chunks.get(&chunk_pos)? {
+            Some(chunk) => Some(chunk.get_block(local_pos)),
+            None => Some(0),
+        }
+    }
+
+    //Returns the previous block, or None without changing anything if the chunk containing
+    //`world_pos` is not loaded or the edit history refused the change. Changes are recorded in
+    //the edit history.
+    pub fn set_block(&mut self, world_pos: IVec3, block: u8) -> Option<u8> {
+        let (old, created_chunk) = self.write_block(world_pos, block)?;
+        if old != block {
+            let change = BlockChange {
+                position: world_pos,
+                old,
+                new: block,
+            };
+            self.begin_transaction();
+            self.history.record(change, created_chunk);
+            self.blocks_edited(&[world_pos]);
+            if self.commit_transaction().is_err() {
+                return None;
+            }
         }
-        self.visible_map = self.visible_map.copy_base(new_center);
-        let mut new_chunk_positions = Vec::new();
-        self.loaded_chunks.retain(|key, value| {
-            let local_pos = key - old_center;
-            if self.visible_map.is_in_sphere(&local_pos) {
-                self.visible_map.add(*key, false);
-                true
-            } else {
-                let local_add_pos = -IVec3::new(local_pos.x - 1, local_pos.y - 1, local_pos.z - 1);
-                new_chunk_positions.push(local_add_pos);
-                false
+        Some(old)
+    }
+
+    //Updates the light, fluids and falling blocks around blocks changed by an edit, undo or redo.
+    fn blocks_edited(&mut self, positions: &[IVec3]) {
+        self.relight(positions);
+        self.fluids_edited(positions);
+        self.schedule_falls(positions);
+    }
+
+    pub fn tick_count(&self) -> u64 {
+        self.tick_count
+    }
+
+    //Advances the block simulation by one tick.
+    pub fn tick(&mut self) {
+        self.tick_count += 1;
+        self.run_block_ticks();
+    }
+
+    //set_block without the history and the updates around the change, also used by undo and redo.
+    //Returns the previous block and whether an all-air chunk had to be allocated.
+    fn write_block(&mut self, world_pos: IVec3, block: u8) -> Option<(u8, bool)> {
+        let (chunk_pos, local_pos) = Chunk::split_world_pos(world_pos);
+        let entry = self.loaded_chunks.get_mut(&chunk_pos)?;
+        let mut created_chunk = false;
+        let old = match entry {
+            Some(chunk) => chunk.set_block(local_pos, block),
+            None if block == 0 => return Some((0, false)),
+            None => {
+                created_chunk = true;
+                entry
+                    .insert(Chunk::empty(chunk_pos))
+                    .set_block(local_pos, block)
             }
+        };
+        if old == block {
+            return Some((old, created_chunk));
+        }
+        self.mark_dirty(chunk_pos, local_pos, local_pos);
+        self.push_block_change(BlockChange {
+            position: world_pos,
+            old,
+            new: block,
         });
-        for local_pos in new_chunk_positions {
-            let world_pos = new_center + local_pos;
-            let chunk = self.load_chunk(world_pos);
-            if chunk.is_some() {
-                self.visible_map.add(local_pos, true);
+        Some((old, created_chunk))
+    }
+
+    //Marks the chunk dirty, along with the loaded neighbours whose border the changed local box
+    //[local_min, local_max] touches. Diagonal neighbours count too, since meshing samples light
+    //around face corners.
+    fn mark_dirty(&mut self, chunk_pos: IVec3, local_min: IVec3, local_max: IVec3) {
+        self.dirty_chunks.insert(chunk_pos);
+        let touches_low = local_min.cmpeq(IVec3::ZERO);
+        let touches_high = local_max.cmpeq(IVec3::splat(CHUNK_SIDE_SIZE - 1));
+        for x in -1..=1 {
+            for y in -1..=1 {
+                for z in -1..=1 {
+                    let offset = ivec3(x, y, z);
+                    let touches = offset.cmpeq(IVec3::ZERO)
+                        | offset.cmplt(IVec3::ZERO) & touches_low
+                        | offset.cmpgt(IVec3::ZERO) & touches_high;
+                    if offset != IVec3::ZERO
+                        && touches.all()
+                        && self.loaded_chunks.contains_key(&(chunk_pos + offset))
+                    {
+                        self.dirty_chunks.insert(chunk_pos + offset);
+                    }
+                }
             }
-            self.loaded_chunks.insert(world_pos, chunk);
         }
-        self.last_map_center = new_center;
     }
 
-    fn load_chunk(&mut self, pos: IVec3) -> Option<Box<Chunk>> {
-        Chunk::new(pos)
+    pub fn is_dirty(&self, chunk_pos: IVec3) -> bool {
+        self.dirty_chunks.contains(&chunk_pos)
+    }
+
+    //Chunks that need to be remeshed since the last call.
+    pub fn take_dirty_chunks(&mut self) -> Vec<IVec3> {
+        self.dirty_chunks.drain().collect()
+    }
+
+    //Starts or stops recording block changes. Nothing drains them on its own, so whoever turns
+    //recording on has to call take_block_changes regularly.
+    pub fn record_block_changes(&mut self, enabled: bool) {
+        if enabled != self.block_changes.is_some() {
+            self.block_changes = enabled.then(Vec::new);
+        }
+    }
+
+    //Block changes in the order they were made since the last call.
+    pub fn take_block_changes(&mut self) -> Vec<BlockChange> {
+        self.block_changes
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    fn push_block_change(&mut self, change: BlockChange) {
+        if let Some(changes) = &mut self.block_changes {
+            changes.push(change);
+        }
+    }
+
+    fn insert_chunk(&mut self, pos: IVec3, chunk: Option<Box<Chunk>>) {
+        self.loaded_chunks.insert(pos, chunk);
+        //The mesher samples all 26 neighbours, so a new chunk touches every one of them.
+        self.mark_dirty(pos, IVec3::ZERO, IVec3::splat(CHUNK_SIDE_SIZE - 1));
     }
 }
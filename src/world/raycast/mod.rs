@@ -0,0 +1,70 @@
+mod tests;
+
+use crate::world::World;
+use glam::{IVec3, Vec3};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RaycastHit {
+    pub position: IVec3,
+    pub normal: IVec3,
+    pub block: u8,
+    pub distance: f32,
+}
+
+impl World {
+    //Walks the voxels along the ray (Amanatides and Woo) and stops at the first solid block.
+    //Unloaded chunks stop the ray without a hit. The normal is zero if the ray starts inside a
+    //solid block.
+    pub fn raycast(&self, origin: Vec3, direction: Vec3, max_distance: f32) -> Option<RaycastHit> {
+        let direction = direction.normalize_or_zero();
+        if direction == Vec3::ZERO {
+            return None;
+        }
+        let mut position = origin.floor().as_ivec3();
+        let mut step = IVec3::ZERO;
+        let mut t_max = Vec3::INFINITY;
+        let mut t_delta = Vec3::INFINITY;
+        for axis in 0..3 {
+            if direction[axis] > 0.0 {
+                step[axis] = 1;
+                t_max[axis] = (position[axis] as f32 + 1.0 - origin[axis]) / direction[axis];
+            } else if direction[axis] < 0.0 {
+                step[axis] = -1;
+                t_max[axis] = (position[axis] as f32 - origin[axis]) / direction[axis];
+            } else {
+                continue;
+            }
+            t_delta[axis] = 1.0 / direction[axis].abs();
+        }
+        let mut normal = IVec3::ZERO;
+        let mut distance = 0.0;
+        loop {
+            match self.get_block(position)? {
+                0 => {}
+                block => {
+                    return Some(RaycastHit {
+                        position,
+                        normal,
+                        block,
+                        distance,
+                    });
+                }
+            }
+            let axis = if t_max.x < t_max.y {
+                if t_max.x < t_max.z { 0 } else { 2 }
+            } else if t_max.y < t_max.z {
+                1
+            } else {
+                2
+            };
+            distance = t_max[axis];
+            if distance > max_distance {
+                return None;
+            }
+            position[axis] += step[axis];
+            t_max[axis] += t_delta[axis];
+            normal = IVec3::ZERO;
+            normal[axis] = -step[axis];
+        }
+    }
+}
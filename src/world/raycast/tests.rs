@@ -0,0 +1,122 @@
+#[cfg(test)]
+mod tests {
+    use crate::world::block_registry::{STONE, WOOD};
+    use crate::world::tests::tests::create_test_world;
+    use glam::{IVec3, Vec3, ivec3};
+
+    #[test]
+    fn test_axis_aligned_rays() {
+        let mut world = create_test_world();
+        world.set_block(ivec3(5, 5, 80), STONE);
+        world.set_block(ivec3(2, 5, 75), WOOD);
+
+        let hit = world
+            .raycast(Vec3::new(5.5, 5.5, 70.5), Vec3::Z, 20.0)
+            .unwrap();
+        assert_eq!(hit.position, ivec3(5, 5, 80));
+        assert_eq!(hit.normal, -IVec3::Z);
+        assert_eq!(hit.block, STONE);
+        assert!((hit.distance - 9.5).abs() < 1e-4);
+
+        //The direction does not have to be normalized.
+        let hit = world
+            .raycast(Vec3::new(10.5, 5.5, 75.5), Vec3::new(-3.0, 0.0, 0.0), 20.0)
+            .unwrap();
+        assert_eq!(hit.position, ivec3(2, 5, 75));
+        assert_eq!(hit.normal, IVec3::X);
+        assert_eq!(hit.block, WOOD);
+        assert!((hit.distance - 7.5).abs() < 1e-4);
+
+        assert_eq!(
+            world.raycast(Vec3::new(5.5, 5.5, 70.5), Vec3::ZERO, 20.0),
+            None
+        );
+    }
+
+    #[test]
+    fn test_diagonal_ray() {
+        let mut world = create_test_world();
+        for y in -2..10 {
+            world.set_block(ivec3(4, y, 70), STONE);
+        }
+        let hit = world
+            .raycast(Vec3::new(0.5, 0.3, 70.5), Vec3::new(1.0, 1.0, 0.0), 20.0)
+            .unwrap();
+        assert_eq!(hit.position, ivec3(4, 3, 70));
+        assert_eq!(hit.normal, -IVec3::X);
+        assert!((hit.distance - 3.5 * 2f32.sqrt()).abs() < 1e-4);
+
+        //Going up and away from the wall misses it.
+        assert_eq!(
+            world.raycast(Vec3::new(0.5, 0.3, 70.5), Vec3::new(-1.0, 1.0, 1.0), 20.0),
+            None
+        );
+    }
+
+    #[test]
+    fn test_ray_across_chunk_borders() {
+        let mut world = create_test_world();
+        world.set_block(ivec3(35, 5, 75), STONE);
+        world.set_block(ivec3(5, 5, 100), WOOD);
+
+        let hit = world
+            .raycast(Vec3::new(20.5, 5.5, 75.5), Vec3::X, 20.0)
+            .unwrap();
+        assert_eq!(hit.position, ivec3(35, 5, 75));
+        assert_eq!(hit.normal, -IVec3::X);
+        assert!((hit.distance - 14.5).abs() < 1e-4);
+
+        let hit = world
+            .raycast(Vec3::new(5.5, 5.5, 90.5), Vec3::Z, 20.0)
+            .unwrap();
+        assert_eq!(hit.position, ivec3(5, 5, 100));
+        assert_eq!(hit.normal, -IVec3::Z);
+    }
+
+    #[test]
+    fn test_ray_at_negative_coordinates() {
+        let mut world = create_test_world();
+        world.set_block(ivec3(-40, -3, 75), STONE);
+        world.set_block(ivec3(-7, -9, 80), STONE);
+
+        let hit = world
+            .raycast(Vec3::new(-30.5, -2.5, 75.5), -Vec3::X, 20.0)
+            .unwrap();
+        assert_eq!(hit.position, ivec3(-40, -3, 75));
+        assert_eq!(hit.normal, IVec3::X);
+        assert!((hit.distance - 8.5).abs() < 1e-4);
+
+        let hit = world
+            .raycast(Vec3::new(-6.5, -0.5, 80.5), -Vec3::Y, 20.0)
+            .unwrap();
+        assert_eq!(hit.position, ivec3(-7, -9, 80));
+        assert_eq!(hit.normal, IVec3::Y);
+        assert!((hit.distance - 7.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_ray_starting_inside_a_block() {
+        let mut world = create_test_world();
+        world.set_block(ivec3(5, 5, 80), STONE);
+        let hit = world
+            .raycast(Vec3::new(5.25, 5.5, 80.75), Vec3::new(1.0, -2.0, 0.5), 20.0)
+            .unwrap();
+        assert_eq!(hit.position, ivec3(5, 5, 80));
+        assert_eq!(hit.normal, IVec3::ZERO);
+        assert_eq!(hit.distance, 0.0);
+    }
+
+    #[test]
+    fn test_max_distance() {
+        let mut world = create_test_world();
+        world.set_block(ivec3(5, 5, 80), STONE);
+        let origin = Vec3::new(5.5, 5.5, 70.5);
+        assert_eq!(world.raycast(origin, Vec3::Z, 9.4), None);
+        assert!(world.raycast(origin, Vec3::Z, 9.5).is_some());
+        //Unloaded chunks end the ray like the distance does.
+        assert_eq!(
+            world.raycast(Vec3::new(0.5, 0.5, 120.5), Vec3::X, 10_000.0),
+            None
+        );
+    }
+}
@@ -0,0 +1,230 @@
+mod tests;
+
+use crate::world::World;
+use crate::world::edit::Clipboard;
+use crate::world::history::HistoryError;
+use glam::IVec3;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"VXSC";
+const FORMAT_VERSION: u8 = 1;
+const MAX_SIDE: i32 = u16::MAX as i32;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SchematicError {
+    InvalidMagic,
+    UnsupportedVersion(u8),
+    Truncated,
+    InvalidSize(IVec3),
+    EmptyPalette,
+    PaletteIndexOutOfRange(usize),
+    InvalidUtf8,
+    TrailingBytes(usize),
+}
+
+impl Display for SchematicError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchematicError::InvalidMagic => write!(f, "Data is not a schematic"),
+            SchematicError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported schematic format version {}", version)
+            }
+            SchematicError::Truncated => write!(f, "Schematic data ended unexpectedly"),
+            SchematicError::InvalidSize(size) => write!(f, "Invalid schematic size {}", size),
+            SchematicError::EmptyPalette => write!(f, "Schematic palette is empty"),
+            SchematicError::PaletteIndexOutOfRange(index) => {
+                write!(f, "Schematic palette index {} is out of range", index)
+            }
+            SchematicError::InvalidUtf8 => write!(f, "Schematic metadata is not valid UTF-8"),
+            SchematicError::TrailingBytes(count) => {
+                write!(f, "Schematic data has {} trailing bytes", count)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SchematicError {}
+
+//A reusable structure: its blocks, with air meaning "leave the world as it is" when placed, and
+//free-form metadata such as the name or author.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schematic {
+    pub blocks: Clipboard,
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl Schematic {
+    pub fn new(blocks: Clipboard) -> Self {
+        Self {
+            blocks,
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    pub fn size(&self) -> IVec3 {
+        self.blocks.size()
+    }
+
+    //Layout, little endian: magic, version byte, size as three u16s, palette length as u16 and
+    //the palette block ids, voxel palette indices packed LSB first with the fewest bits that fit
+    //the palette, then the metadata count as u16 and each key and value as a u16 length followed
+    //by UTF-8 bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let size = self.size();
+        assert!(
+            size.cmple(IVec3::splat(MAX_SIDE)).all(),
+            "Schematic is too large to serialize"
+        );
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(FORMAT_VERSION);
+        for side in size.to_array() {
+            bytes.extend_from_slice(&(side as u16).to_le_bytes());
+        }
+
+        let mut palette: Vec<u8> = self.blocks.blocks().to_vec();
+        palette.sort_unstable();
+        palette.dedup();
+        bytes.extend_from_slice(&(palette.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&palette);
+        let mut palette_index = [0u8; 256];
+        for (index, block) in palette.iter().enumerate() {
+            palette_index[*block as usize] = index as u8;
+        }
+
+        let bits = bits_per_index(palette.len());
+        let mut packed = vec![0u8; (self.blocks.blocks().len() * bits).div_ceil(8)];
+        for (voxel, block) in self.blocks.blocks().iter().enumerate() {
+            let index = palette_index[*block as usize];
+            for bit in 0..bits {
+                if index >> bit & 1 != 0 {
+                    let position = voxel * bits + bit;
+                    packed[position / 8] |= 1 << (position % 8);
+                }
+            }
+        }
+        bytes.extend_from_slice(&packed);
+
+        bytes.extend_from_slice(&(self.metadata.len() as u16).to_le_bytes());
+        for (key, value) in &self.metadata {
+            for text in [key, value] {
+                assert!(
+                    text.len() <= u16::MAX as usize,
+                    "Schematic metadata is too long to serialize"
+                );
+                bytes.extend_from_slice(&(text.len() as u16).to_le_bytes());
+                bytes.extend_from_slice(text.as_bytes());
+            }
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SchematicError> {
+        let mut reader = Reader { bytes, cursor: 0 };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(SchematicError::InvalidMagic);
+        }
+        let version = reader.take(1)?[0];
+        if version != FORMAT_VERSION {
+            return Err(SchematicError::UnsupportedVersion(version));
+        }
+        let size = IVec3::new(
+            reader.read_u16()? as i32,
+            reader.read_u16()? as i32,
+            reader.read_u16()? as i32,
+        );
+        if size.cmpeq(IVec3::ZERO).any() {
+            return Err(SchematicError::InvalidSize(size));
+        }
+
+        let palette_len = reader.read_u16()? as usize;
+        if palette_len == 0 {
+            return Err(SchematicError::EmptyPalette);
+        }
+        let palette = reader.take(palette_len)?;
+        let bits = bits_per_index(palette_len);
+        let volume = size.x as usize * size.y as usize * size.z as usize;
+        let packed = reader.take((volume * bits).div_ceil(8))?;
+        let mut blocks = Vec::with_capacity(volume);
+        for voxel in 0..volume {
+            let mut index = 0;
+            for bit in 0..bits {
+                let position = voxel * bits + bit;
+                index |= ((packed[position / 8] >> (position % 8) & 1) as usize) << bit;
+            }
+            let block = palette
+                .get(index)
+                .ok_or(SchematicError::PaletteIndexOutOfRange(index))?;
+            blocks.push(*block);
+        }
+
+        let mut metadata = BTreeMap::new();
+        for _ in 0..reader.read_u16()? {
+            let key = reader.read_string()?;
+            let value = reader.read_string()?;
+            metadata.insert(key, value);
+        }
+        if reader.cursor != bytes.len() {
+            return Err(SchematicError::TrailingBytes(bytes.len() - reader.cursor));
+        }
+        let blocks =
+            Clipboard::from_blocks(size, blocks).ok_or(SchematicError::InvalidSize(size))?;
+        Ok(Self { blocks, metadata })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_bytes())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let bytes = std::fs::read(path)?;
+        Self::from_bytes(&bytes)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+    }
+}
+
+fn bits_per_index(palette_len: usize) -> usize {
+    (usize::BITS - (palette_len.max(2) - 1).leading_zeros()) as usize
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    cursor: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], SchematicError> {
+        let end = self.cursor + count;
+        let slice = self
+            .bytes
+            .get(self.cursor..end)
+            .ok_or(SchematicError::Truncated)?;
+        self.cursor = end;
+        Ok(slice)
+    }
+
+    fn read_u16(&mut self) -> Result<u16, SchematicError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn read_string(&mut self) -> Result<String, SchematicError> {
+        let len = self.read_u16()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| SchematicError::InvalidUtf8)
+    }
+}
+
+impl World {
+    //Places the schematic rotated counter-clockwise around z by `quarter_turns`, with the minimum
+    //corner of the rotated blocks at `position`. Air in the schematic keeps the world's blocks.
+    pub fn place_schematic(
+        &mut self,
+        schematic: &Schematic,
+        position: IVec3,
+        quarter_turns: i32,
+    ) -> Result<usize, HistoryError> {
+        let blocks = schematic.blocks.rotated(quarter_turns);
+        self.paste(&blocks, position, false)
+    }
+}
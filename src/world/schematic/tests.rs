@@ -0,0 +1,174 @@
+#[cfg(test)]
+mod tests {
+    use crate::world::World;
+    use crate::world::edit::{Clipboard, Region};
+    use crate::world::schematic::{Schematic, SchematicError};
+    use glam::{IVec3, ivec3};
+
+    fn test_schematic() -> Schematic {
+        let mut blocks = Clipboard::new(ivec3(3, 2, 4));
+        blocks.set(ivec3(0, 0, 0), 2);
+        blocks.set(ivec3(2, 1, 0), 7);
+        blocks.set(ivec3(1, 1, 3), 200);
+        blocks.set(ivec3(2, 0, 2), 2);
+        let mut schematic = Schematic::new(blocks);
+        schematic
+            .metadata
+            .insert("name".to_string(), "test".to_string());
+        schematic
+            .metadata
+            .insert("author".to_string(), "ünïcode".to_string());
+        schematic
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let schematic = test_schematic();
+        let bytes = schematic.to_bytes();
+        assert_eq!(&bytes[0..4], b"VXSC");
+        assert_eq!(Schematic::from_bytes(&bytes), Ok(schematic));
+    }
+
+    #[test]
+    fn test_packed_size() {
+        //Four palette entries fit in two bits, so 24 voxels take 6 bytes.
+        let schematic = Schematic::new(test_schematic().blocks);
+        let header = 4 + 1 + 3 * 2;
+        let palette = 2 + 4;
+        let metadata = 2;
+        assert_eq!(schematic.to_bytes().len(), header + palette + 6 + metadata);
+
+        //A single entry palette still uses one bit per voxel.
+        let air = Schematic::new(Clipboard::new(ivec3(4, 4, 1)));
+        assert_eq!(air.to_bytes().len(), header + 2 + 1 + 2 + metadata);
+        assert_eq!(Schematic::from_bytes(&air.to_bytes()), Ok(air));
+    }
+
+    #[test]
+    fn test_every_palette_size() {
+        for palette_len in 1..=256usize {
+            let mut blocks = Clipboard::new(ivec3(16, 16, 2));
+            for index in 0..palette_len {
+                blocks.set(ivec3(index as i32 % 16, index as i32 / 16, 1), index as u8);
+            }
+            let schematic = Schematic::new(blocks);
+            assert_eq!(Schematic::from_bytes(&schematic.to_bytes()), Ok(schematic));
+        }
+    }
+
+    #[test]
+    fn test_read_errors() {
+        let bytes = test_schematic().to_bytes();
+        for len in 0..bytes.len() {
+            assert!(Schematic::from_bytes(&bytes[..len]).is_err());
+        }
+        assert_eq!(
+            Schematic::from_bytes(&bytes[..10]),
+            Err(SchematicError::Truncated)
+        );
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert_eq!(
+            Schematic::from_bytes(&bad_magic),
+            Err(SchematicError::InvalidMagic)
+        );
+
+        let mut bad_version = bytes.clone();
+        bad_version[4] = 9;
+        assert_eq!(
+            Schematic::from_bytes(&bad_version),
+            Err(SchematicError::UnsupportedVersion(9))
+        );
+
+        let mut zero_size = bytes.clone();
+        zero_size[5..7].copy_from_slice(&[0, 0]);
+        assert_eq!(
+            Schematic::from_bytes(&zero_size),
+            Err(SchematicError::InvalidSize(ivec3(0, 2, 4)))
+        );
+
+        let mut trailing = bytes.clone();
+        trailing.extend_from_slice(&[1, 2]);
+        assert_eq!(
+            Schematic::from_bytes(&trailing),
+            Err(SchematicError::TrailingBytes(2))
+        );
+    }
+
+    #[test]
+    fn test_palette_errors() {
+        //Three palette entries need two bits, which can also encode index 3.
+        let mut bytes = b"VXSC\x01".to_vec();
+        bytes.extend_from_slice(&[1, 0, 1, 0, 1, 0]);
+        bytes.extend_from_slice(&[3, 0, 0, 4, 9]);
+        bytes.push(0b11);
+        bytes.extend_from_slice(&[0, 0]);
+        assert_eq!(
+            Schematic::from_bytes(&bytes),
+            Err(SchematicError::PaletteIndexOutOfRange(3))
+        );
+        bytes[16] = 0b10;
+        assert_eq!(
+            Schematic::from_bytes(&bytes)
+                .unwrap()
+                .blocks
+                .get(IVec3::ZERO),
+            9
+        );
+
+        let mut empty = b"VXSC\x01".to_vec();
+        empty.extend_from_slice(&[1, 0, 1, 0, 1, 0, 0, 0]);
+        assert_eq!(
+            Schematic::from_bytes(&empty),
+            Err(SchematicError::EmptyPalette)
+        );
+    }
+
+    #[test]
+    fn test_invalid_metadata() {
+        let mut schematic = Schematic::new(Clipboard::new(IVec3::ONE));
+        schematic
+            .metadata
+            .insert("key".to_string(), "value".to_string());
+        let mut bytes = schematic.to_bytes();
+        let len = bytes.len();
+        bytes[len - 1] = 0xff;
+        assert_eq!(
+            Schematic::from_bytes(&bytes),
+            Err(SchematicError::InvalidUtf8)
+        );
+    }
+
+    #[test]
+    fn test_save_load() {
+        let path = std::env::temp_dir().join(format!("vxl_schematic_{}.vxs", std::process::id()));
+        let schematic = test_schematic();
+        schematic.save(&path).unwrap();
+        let loaded = Schematic::load(&path);
+        std::fs::write(&path, b"nope").unwrap();
+        let invalid = Schematic::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap(), schematic);
+        assert_eq!(invalid.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_place_rotated() {
+        let mut world = World::new(4).unwrap();
+        let schematic = test_schematic();
+        let position = ivec3(30, 30, 60);
+        world.fill(&Region::new(position, position + 4), 1).unwrap();
+
+        assert_eq!(world.place_schematic(&schematic, position, 1).unwrap(), 4);
+        //Rotated a quarter turn the schematic is 2 x 3 and +x points along +y.
+        assert_eq!(world.get_block(position + ivec3(1, 0, 0)), Some(2));
+        assert_eq!(world.get_block(position + ivec3(0, 2, 0)), Some(7));
+        assert_eq!(world.get_block(position + ivec3(0, 1, 3)), Some(200));
+        assert_eq!(world.get_block(position + ivec3(1, 2, 2)), Some(2));
+        //Air in the schematic keeps what was there.
+        assert_eq!(world.get_block(position + ivec3(0, 0, 0)), Some(1));
+        assert_eq!(world.history().undo_count(), 2);
+    }
+}
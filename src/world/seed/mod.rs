@@ -0,0 +1,55 @@
+mod tests;
+
+use glam::IVec3;
+use std::fmt::{Display, Formatter};
+
+//Everything random about generating a world and ticking its blocks derives from its seed, so the
+//same seed always gives the same world.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WorldSeed(pub u64);
+
+pub const DEFAULT_SEED: WorldSeed = WorldSeed(1944);
+
+impl WorldSeed {
+    //Integers are taken as they are, any other text is hashed.
+    pub fn from_text(text: &str) -> Self {
+        if let Ok(seed) = text.parse::<u64>() {
+            return WorldSeed(seed);
+        }
+        if let Ok(seed) = text.parse::<i64>() {
+            return WorldSeed(seed as u64);
+        }
+        WorldSeed(fnv1a(text.as_bytes()))
+    }
+
+    //Seed of the random choices made at one position, a chunk, a column or a structure cell.
+    //Every use picks its own `salt` so they do not repeat each other's choices.
+    pub(crate) fn position_seed(self, pos: IVec3, salt: u64) -> u64 {
+        let x = pos.x as u32 as u64;
+        let y = pos.y as u32 as u64;
+        let z = pos.z as u32 as u64;
+        self.0
+            ^ (x << 32 | y).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ z.wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+            ^ salt.wrapping_mul(0x1656_67B1_9E37_79F9)
+    }
+
+    //The noise generators only take 32 bit seeds, fold the upper half in so it is not lost.
+    pub(crate) fn noise_seed(self) -> i32 {
+        (self.0 ^ self.0 >> 32) as i32
+    }
+}
+
+impl Display for WorldSeed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+//64 bit FNV-1a. Unlike the standard library hashers its output is fixed, so seeds hashed from
+//text stay the same across builds.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xCBF2_9CE4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01B3)
+    })
+}
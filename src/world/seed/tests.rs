@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod tests {
+    use crate::world::World;
+    use crate::world::chunk::Chunk;
+    use crate::world::seed::{DEFAULT_SEED, WorldSeed, fnv1a};
+    use crate::world::terrain::Terrain;
+    use glam::{IVec3, ivec3};
+
+    //Chunks from the surface down to the caves, ores and gold, across several biomes.
+    const GOLDEN_CHUNKS: [IVec3; 6] = [
+        ivec3(0, 0, 0),
+        ivec3(0, 0, -1),
+        ivec3(-3, 2, 0),
+        ivec3(5, -4, -1),
+        ivec3(1, 1, -3),
+        ivec3(40, -25, 0),
+    ];
+
+    fn chunk_hash(terrain: &Terrain, chunk_pos: IVec3) -> u64 {
+        Chunk::new(chunk_pos, terrain).map_or(0, |chunk| fnv1a(&chunk.texture))
+    }
+
+    #[test]
+    fn test_seed_from_text() {
+        assert_eq!(WorldSeed::from_text("1944"), WorldSeed(1944));
+        assert_eq!(WorldSeed::from_text("-1"), WorldSeed(u64::MAX));
+        assert_eq!(
+            WorldSeed::from_text("island"),
+            WorldSeed::from_text("island")
+        );
+        assert_ne!(
+            WorldSeed::from_text("island"),
+            WorldSeed::from_text("Island")
+        );
+        assert_eq!(WorldSeed::from_text(""), WorldSeed(0xCBF2_9CE4_8422_2325));
+        assert_eq!(WorldSeed(7).to_string(), "7");
+    }
+
+    #[test]
+    fn test_generation_only_depends_on_the_seed() {
+        let world = World::with_seed(2, WorldSeed(42)).unwrap();
+        assert_eq!(world.seed(), WorldSeed(42));
+        let terrain = Terrain::new(WorldSeed(42));
+        let other_terrain = Terrain::new(WorldSeed(43));
+        let mut differences = 0;
+        for chunk_pos in [ivec3(0, 0, 0), ivec3(0, 0, -1), ivec3(-1, 0, 0)] {
+            let chunk = Chunk::new(chunk_pos, &terrain).unwrap();
+            let loaded = world.loaded_chunks[&chunk_pos].as_ref().unwrap();
+            assert!(chunk.texture == loaded.texture);
+            let other = Chunk::new(chunk_pos, &other_terrain).unwrap();
+            if chunk.texture != other.texture {
+                differences += 1;
+            }
+        }
+        assert_eq!(differences, 3);
+        //Seeds that only differ in the upper bits still get their own terrain.
+        assert_ne!(
+            chunk_hash(&Terrain::new(WorldSeed(42)), ivec3(0, 0, 0)),
+            chunk_hash(&Terrain::new(WorldSeed(42 | 1 << 40)), ivec3(0, 0, 0))
+        );
+    }
+
+    //Generating the same chunks must keep giving the same voxels. Update the hashes only when a
+    //change to the generation output is intended.
+    #[test]
+    fn test_golden_chunk_hashes() {
+        let expected = [
+            (
+                DEFAULT_SEED,
+                [
+                    4846637666604230005,
+                    4149489321293308769,
+                    2665955802128318269,
+                    16199251073377595621,
+                    18138278654804093475,
+                    15108614450709109871,
+                ],
+            ),
+            (
+                WorldSeed::from_text("golden"),
+                [
+                    12793268911574253979,
+                    14923610844291393590,
+                    7742009950266136560,
+                    2265405363561774591,
+                    3646791213339382579,
+                    16752641090301439254,
+                ],
+            ),
+        ];
+        for (seed, hashes) in expected {
+            let terrain = Terrain::new(seed);
+            let actual = GOLDEN_CHUNKS.map(|chunk_pos| chunk_hash(&terrain, chunk_pos));
+            assert_eq!(actual, hashes, "seed {}", seed);
+        }
+    }
+}
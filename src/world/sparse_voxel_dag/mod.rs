@@ -0,0 +1,150 @@
+mod tests;
+
+use crate::world::chunk::{CHUNK_SIDE_SIZE, Chunk};
+use glam::{IVec3, Vec3};
+use std::collections::HashMap;
+
+//Child references either point into `nodes` or, with the high bit set, hold the block id of a
+//uniform region.
+const LEAF_FLAG: u32 = 1 << 31;
+const RAY_EPSILON: f32 = 1e-4;
+
+pub struct DagHit {
+    pub position: IVec3,
+    pub block: u8,
+    pub distance: f32,
+    pub normal: IVec3,
+}
+
+//Sparse voxel directed acyclic graph. Every chunk is an octree of depth log2(CHUNK_SIDE_SIZE)
+//whose identical subtrees are stored once and shared between all chunks.
+#[derive(Default)]
+pub struct SparseVoxelDag {
+    nodes: Vec<[u32; 8]>,
+    node_lookup: HashMap<[u32; 8], u32>,
+    roots: HashMap<IVec3, u32>,
+}
+
+impl SparseVoxelDag {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_chunk(&mut self, chunk: &Chunk) {
+        let root = self.build_recursive(&chunk.texture, IVec3::ZERO, CHUNK_SIDE_SIZE);
+        self.roots.insert(chunk.position, root);
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.roots.len()
+    }
+
+    //Bytes used by the compressed form, without the build time deduplication table.
+    pub fn compressed_size(&self) -> usize {
+        self.nodes.len() * size_of::<[u32; 8]>()
+            + self.roots.len() * (size_of::<IVec3>() + size_of::<u32>())
+    }
+
+    pub fn uncompressed_size(&self) -> usize {
+        self.roots.len() * (CHUNK_SIDE_SIZE * CHUNK_SIDE_SIZE * CHUNK_SIDE_SIZE) as usize
+    }
+
+    pub fn get(&self, world_pos: IVec3) -> u8 {
+        self.find_region(world_pos).0
+    }
+
+    pub fn raycast(&self, origin: Vec3, direction: Vec3, max_distance: f32) -> Option<DagHit> {
+        let direction = direction.normalize_or_zero();
+        if direction == Vec3::ZERO {
+            return None;
+        }
+        let mut distance = 0.0;
+        let mut normal = IVec3::ZERO;
+        while distance <= max_distance {
+            let point = origin + direction * (distance + RAY_EPSILON);
+            let position = point.floor().as_ivec3();
+            let (block, region_min, region_size) = self.find_region(position);
+            if block != 0 {
+                return Some(DagHit {
+                    position,
+                    block,
+                    distance,
+                    normal,
+                });
+            }
+            //Skip the whole uniform region the ray is in.
+            let region_min = region_min.as_vec3();
+            let region_max = region_min + region_size as f32;
+            let mut exit = f32::INFINITY;
+            for axis in 0..3 {
+                let boundary = if direction[axis] > 0.0 {
+                    region_max[axis]
+                } else if direction[axis] < 0.0 {
+                    region_min[axis]
+                } else {
+                    continue;
+                };
+                let axis_exit = (boundary - origin[axis]) / direction[axis];
+                if axis_exit < exit {
+                    exit = axis_exit;
+                    normal = IVec3::ZERO;
+                    normal[axis] = if direction[axis] > 0.0 { -1 } else { 1 };
+                }
+            }
+            distance = exit.max(distance + RAY_EPSILON);
+        }
+        None
+    }
+
+    //Returns the block of the uniform region containing `world_pos`, its world space minimum
+    //corner and its side length. Chunks that were never added are treated as air.
+    fn find_region(&self, world_pos: IVec3) -> (u8, IVec3, i32) {
+        let chunk_pos = world_pos.div_euclid(IVec3::splat(CHUNK_SIDE_SIZE));
+        let chunk_min = chunk_pos * CHUNK_SIDE_SIZE;
+        let Some(&root) = self.roots.get(&chunk_pos) else {
+            return (0, chunk_min, CHUNK_SIDE_SIZE);
+        };
+        let local = world_pos - chunk_min;
+        let mut reference = root;
+        let mut node_min = IVec3::ZERO;
+        let mut size = CHUNK_SIDE_SIZE;
+        while reference & LEAF_FLAG == 0 {
+            size /= 2;
+            let offset = (local - node_min).cmpge(IVec3::splat(size));
+            let index = offset.bitmask() as usize;
+            node_min += IVec3::select(offset, IVec3::splat(size), IVec3::ZERO);
+            reference = self.nodes[reference as usize][index];
+        }
+        ((reference & !LEAF_FLAG) as u8, chunk_min + node_min, size)
+    }
+
+    fn build_recursive(&mut self, texture: &[u8], min: IVec3, size: i32) -> u32 {
+        if size == 1 {
+            let index = min.x + min.y * CHUNK_SIDE_SIZE + min.z * CHUNK_SIDE_SIZE * CHUNK_SIDE_SIZE;
+            return LEAF_FLAG | texture[index as usize] as u32;
+        }
+        let half = size / 2;
+        let children: [u32; 8] = core::array::from_fn(|index| {
+            let offset = IVec3::new(
+                (index & 1) as i32,
+                ((index >> 1) & 1) as i32,
+                ((index >> 2) & 1) as i32,
+            );
+            self.build_recursive(texture, min + offset * half, half)
+        });
+        if children[0] & LEAF_FLAG != 0 && children.iter().all(|child| *child == children[0]) {
+            return children[0];
+        }
+        if let Some(&reference) = self.node_lookup.get(&children) {
+            return reference;
+        }
+        let reference = self.nodes.len() as u32;
+        self.nodes.push(children);
+        self.node_lookup.insert(children, reference);
+        reference
+    }
+}
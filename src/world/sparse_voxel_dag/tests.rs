@@ -0,0 +1,147 @@
+#[cfg(test)]
+mod tests {
+    use crate::world::chunk::{CHUNK_SIDE_SIZE, Chunk};
+    use crate::world::seed::DEFAULT_SEED;
+    use crate::world::sparse_voxel_dag::SparseVoxelDag;
+    use crate::world::terrain::Terrain;
+    use glam::{IVec3, Vec3};
+
+    fn texture_index(x: i32, y: i32, z: i32) -> usize {
+        (x + y * CHUNK_SIDE_SIZE + z * CHUNK_SIDE_SIZE * CHUNK_SIDE_SIZE) as usize
+    }
+
+    fn filled_chunk(position: IVec3, block: impl Fn(i32, i32, i32) -> u8) -> Chunk {
+        let mut texture = vec![0; (CHUNK_SIDE_SIZE * CHUNK_SIDE_SIZE * CHUNK_SIDE_SIZE) as usize];
+        for x in 0..CHUNK_SIDE_SIZE {
+            for y in 0..CHUNK_SIDE_SIZE {
+                for z in 0..CHUNK_SIDE_SIZE {
+                    texture[texture_index(x, y, z)] = block(x, y, z);
+                }
+            }
+        }
+        Chunk { texture, position }
+    }
+
+    #[test]
+    fn test_point_lookup_matches_texture() {
+        let chunk = filled_chunk(IVec3::new(-1, 2, 0), |x, y, z| {
+            if z < (x + y) / 4 {
+                1 + ((x * 7 + y * 3) % 3) as u8
+            } else {
+                0
+            }
+        });
+        let mut dag = SparseVoxelDag::new();
+        dag.add_chunk(&chunk);
+        let chunk_min = chunk.position * CHUNK_SIDE_SIZE;
+        for x in 0..CHUNK_SIDE_SIZE {
+            for y in 0..CHUNK_SIDE_SIZE {
+                for z in 0..CHUNK_SIDE_SIZE {
+                    assert_eq!(
+                        dag.get(chunk_min + IVec3::new(x, y, z)),
+                        chunk.texture[texture_index(x, y, z)]
+                    );
+                }
+            }
+        }
+        assert_eq!(dag.get(IVec3::new(1000, 0, 0)), 0);
+    }
+
+    #[test]
+    fn test_uniform_chunk_has_no_nodes() {
+        let mut dag = SparseVoxelDag::new();
+        dag.add_chunk(&filled_chunk(IVec3::ZERO, |_, _, _| 3));
+        assert_eq!(dag.node_count(), 0);
+        assert_eq!(dag.get(IVec3::new(31, 31, 31)), 3);
+    }
+
+    #[test]
+    fn test_identical_chunks_share_nodes() {
+        let block = |x: i32, y: i32, z: i32| ((x ^ y ^ z) & 1) as u8;
+        let mut dag = SparseVoxelDag::new();
+        dag.add_chunk(&filled_chunk(IVec3::ZERO, block));
+        let node_count = dag.node_count();
+        dag.add_chunk(&filled_chunk(IVec3::new(5, 0, 0), block));
+        assert_eq!(dag.node_count(), node_count);
+        assert_eq!(dag.chunk_count(), 2);
+        //A checkerboard collapses to one node per level.
+        assert_eq!(node_count, 5);
+    }
+
+    #[test]
+    fn test_raycast_hits_floor() {
+        let mut dag = SparseVoxelDag::new();
+        dag.add_chunk(&filled_chunk(IVec3::ZERO, |_, _, z| (z < 4) as u8));
+        let hit = dag
+            .raycast(
+                Vec3::new(10.5, 10.5, 20.0),
+                Vec3::new(0.0, 0.0, -1.0),
+                100.0,
+            )
+            .unwrap();
+        assert_eq!(hit.position, IVec3::new(10, 10, 3));
+        assert_eq!(hit.normal, IVec3::new(0, 0, 1));
+        assert!((hit.distance - 16.0).abs() < 1e-3);
+        assert!(
+            dag.raycast(Vec3::new(10.5, 10.5, 20.0), Vec3::new(0.0, 0.0, -1.0), 10.0)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_raycast_crosses_missing_chunks() {
+        let mut dag = SparseVoxelDag::new();
+        dag.add_chunk(&filled_chunk(IVec3::new(-3, 0, 0), |x, y, z| {
+            (x == 5 && y == 7 && z == 9) as u8 * 2
+        }));
+        let hit = dag
+            .raycast(Vec3::new(40.5, 7.5, 9.5), Vec3::new(-1.0, 0.0, 0.0), 200.0)
+            .unwrap();
+        assert_eq!(hit.position, IVec3::new(-96 + 5, 7, 9));
+        assert_eq!(hit.block, 2);
+        assert_eq!(hit.normal, IVec3::new(1, 0, 0));
+    }
+
+    #[test]
+    fn test_raycast_diagonal() {
+        let mut dag = SparseVoxelDag::new();
+        dag.add_chunk(&filled_chunk(IVec3::ZERO, |x, y, z| {
+            (x == 20 && y == 20 && z == 20) as u8
+        }));
+        let hit = dag.raycast(Vec3::splat(0.5), Vec3::ONE, 100.0).unwrap();
+        assert_eq!(hit.position, IVec3::splat(20));
+        assert!(
+            dag.raycast(Vec3::new(0.5, 1.5, 0.5), Vec3::ONE, 100.0)
+                .is_none()
+        );
+    }
+
+    //Generates a thousand chunks, run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_default_terrain_compression() {
+        let mut dag = SparseVoxelDag::new();
+        let terrain = Terrain::new(DEFAULT_SEED);
+        let mut empty_chunks = 0;
+        for x in -16..16 {
+            for y in -16..16 {
+                for z in -1..2 {
+                    match Chunk::new(IVec3::new(x, y, z), &terrain) {
+                        Some(chunk) => dag.add_chunk(&chunk),
+                        None => empty_chunks += 1,
+                    }
+                }
+            }
+        }
+        println!(
+            "chunks: {} ({} empty skipped), nodes: {}, raw: {} bytes, dag: {} bytes, ratio: {:.1}x",
+            dag.chunk_count(),
+            empty_chunks,
+            dag.node_count(),
+            dag.uncompressed_size(),
+            dag.compressed_size(),
+            dag.uncompressed_size() as f64 / dag.compressed_size() as f64
+        );
+        assert!(dag.compressed_size() < dag.uncompressed_size());
+    }
+}
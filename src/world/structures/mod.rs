@@ -0,0 +1,186 @@
+mod tests;
+
+use crate::world::block_registry::{LEAVES, STONE, WOOD};
+use crate::world::chunk::{CHUNK_SIDE_SIZE, Chunk};
+use crate::world::edit::Clipboard;
+use crate::world::schematic::Schematic;
+use crate::world::terrain::{LOWEST_SURFACE, Terrain};
+use glam::{IVec2, IVec3, ivec2, ivec3};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::LazyLock;
+
+//Every CELL_SIZE x CELL_SIZE column of the world holds at most one structure.
+const CELL_SIZE: i32 = 24;
+//Keeps structure cells apart from the decoration derived from the same seed.
+const STRUCTURE_SALT: u64 = 102;
+
+struct Structure {
+    //The schematic in each of the four rotations around z.
+    rotations: [Clipboard; 4],
+    //How far the bottom layer is sunk into the ground.
+    depth: i32,
+}
+
+impl Structure {
+    fn new(schematic: Schematic, depth: i32) -> Self {
+        Self {
+            rotations: [0, 1, 2, 3].map(|turns| schematic.blocks.rotated(turns)),
+            depth,
+        }
+    }
+}
+
+//Structures biomes can decorate with, in the order of STRUCTURES.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StructureKind {
+    Tree,
+    Ruin,
+}
+
+static STRUCTURES: LazyLock<Vec<Structure>> =
+    LazyLock::new(|| vec![Structure::new(tree(), 0), Structure::new(ruin(), 1)]);
+
+//The blocks of a structure in one of its four rotations around z.
+pub(crate) fn structure_blocks(kind: StructureKind, rotation: usize) -> &'static Clipboard {
+    &STRUCTURES[kind as usize].rotations[rotation]
+}
+
+//Largest horizontal and vertical size of any structure, bounding which cells can reach a chunk.
+static MAX_SIZE: LazyLock<IVec3> = LazyLock::new(|| {
+    STRUCTURES
+        .iter()
+        .map(|structure| structure.rotations[0].size())
+        .fold(IVec3::ZERO, |max, size| {
+            max.max(ivec3(size.x.max(size.y), size.x.max(size.y), size.z))
+        })
+});
+
+pub(crate) struct Placement {
+    pub(crate) min: IVec3,
+    pub(crate) blocks: &'static Clipboard,
+}
+
+//The placement of a cell only depends on the cell position, so every chunk a structure overlaps
+//stamps the same blocks no matter which one is generated first. The biome at the anchor decides
+//how likely the cell holds a structure and which one.
+pub(crate) fn placement(terrain: &Terrain, cell: IVec2) -> Option<Placement> {
+    let mut rng =
+        StdRng::seed_from_u64(terrain.seed().position_seed(cell.extend(0), STRUCTURE_SALT));
+    let anchor = cell * CELL_SIZE
+        + ivec2(
+            rng.random_range(0..CELL_SIZE),
+            rng.random_range(0..CELL_SIZE),
+        );
+    let decoration = terrain.biome(anchor.x, anchor.y).decoration();
+    if !rng.random_bool(decoration.chance) {
+        return None;
+    }
+    let total_weight: u32 = decoration.structures.iter().map(|(_, weight)| weight).sum();
+    let mut pick = rng.random_range(0..total_weight);
+    let (kind, _) = decoration
+        .structures
+        .iter()
+        .find(|(_, weight)| {
+            if pick < *weight {
+                return true;
+            }
+            pick -= weight;
+            false
+        })
+        .unwrap();
+    let structure = &STRUCTURES[*kind as usize];
+    let blocks = &structure.rotations[rng.random_range(0..4)];
+    let ground = terrain.surface_height(anchor.x, anchor.y);
+    let size = blocks.size();
+    let min = ivec3(
+        anchor.x - size.x / 2,
+        anchor.y - size.y / 2,
+        ground + 1 - structure.depth,
+    );
+    Some(Placement { min, blocks })
+}
+
+//Stamps the parts of all structures that overlap the chunk into it. Returns whether any block was
+//placed.
+pub(crate) fn place_structures(terrain: &Terrain, chunk: &mut Chunk) -> bool {
+    let chunk_min = chunk.position * CHUNK_SIDE_SIZE;
+    let chunk_max = chunk_min + CHUNK_SIDE_SIZE - 1;
+    let max_size = *MAX_SIZE;
+    if chunk_max.z < LOWEST_SURFACE || chunk_min.z > terrain.max_surface_height() + max_size.z {
+        return false;
+    }
+    let cell_min = (chunk_min.truncate() - max_size.truncate()).div_euclid(IVec2::splat(CELL_SIZE));
+    let cell_max = (chunk_max.truncate() + max_size.truncate()).div_euclid(IVec2::splat(CELL_SIZE));
+    let mut placed = false;
+    for cell_y in cell_min.y..=cell_max.y {
+        for cell_x in cell_min.x..=cell_max.x {
+            let Some(placement) = placement(terrain, ivec2(cell_x, cell_y)) else {
+                continue;
+            };
+            let min = placement.min.max(chunk_min);
+            let max = (placement.min + placement.blocks.size() - 1).min(chunk_max);
+            for z in min.z..=max.z {
+                for y in min.y..=max.y {
+                    for x in min.x..=max.x {
+                        let world_pos = ivec3(x, y, z);
+                        let block = placement.blocks.get(world_pos - placement.min);
+                        if block != 0 {
+                            chunk.set_block(world_pos - chunk_min, block);
+                            placed = true;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    placed
+}
+
+fn tree() -> Schematic {
+    let mut blocks = Clipboard::new(ivec3(5, 5, 7));
+    let crown_center = IVec3::new(2, 2, 5);
+    for z in 3..7 {
+        for y in 0..5 {
+            for x in 0..5 {
+                let position = ivec3(x, y, z);
+                if (position - crown_center).length_squared() <= 5 {
+                    blocks.set(position, LEAVES);
+                }
+            }
+        }
+    }
+    for z in 0..5 {
+        blocks.set(ivec3(2, 2, z), WOOD);
+    }
+    let mut schematic = Schematic::new(blocks);
+    schematic
+        .metadata
+        .insert("name".to_string(), "tree".to_string());
+    schematic
+}
+
+//A stone floor with crumbling walls of uneven height and a doorway.
+fn ruin() -> Schematic {
+    let side = 7;
+    let mut blocks = Clipboard::new(ivec3(side, side, 4));
+    for y in 0..side {
+        for x in 0..side {
+            blocks.set(ivec3(x, y, 0), STONE);
+            let is_wall = x == 0 || y == 0 || x == side - 1 || y == side - 1;
+            let is_door = y == 0 && x == side / 2;
+            if !is_wall || is_door {
+                continue;
+            }
+            let height = 1 + (x * 7 + y * 3) % 3;
+            for z in 1..=height {
+                blocks.set(ivec3(x, y, z), STONE);
+            }
+        }
+    }
+    let mut schematic = Schematic::new(blocks);
+    schematic
+        .metadata
+        .insert("name".to_string(), "ruin".to_string());
+    schematic
+}
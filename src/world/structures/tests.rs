@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod tests {
+    use crate::world::World;
+    use crate::world::chunk::Chunk;
+    use crate::world::seed::DEFAULT_SEED;
+    use crate::world::structures::{CELL_SIZE, placement};
+    use crate::world::terrain::Terrain;
+    use glam::{IVec3, ivec2, ivec3};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_generation_is_deterministic() {
+        let terrain = Terrain::new(DEFAULT_SEED);
+        for position in [ivec3(0, 0, 0), ivec3(-3, 2, 0), ivec3(1, -1, 1)] {
+            let first = Chunk::new(position, &terrain).map(|chunk| chunk.texture);
+            let second = Chunk::new(position, &terrain).map(|chunk| chunk.texture);
+            assert!(first == second);
+        }
+    }
+
+    #[test]
+    fn test_structures_are_complete_across_chunks() {
+        let terrain = Terrain::new(DEFAULT_SEED);
+        let mut chunks = HashMap::new();
+        //Generates the chunks one by one instead of loading a world large enough to hold all cells.
+        let mut get_block = |world_pos: IVec3| {
+            let (chunk_pos, local_pos) = Chunk::split_world_pos(world_pos);
+            chunks
+                .entry(chunk_pos)
+                .or_insert_with(|| Chunk::new(chunk_pos, &terrain))
+                .as_ref()
+                .map_or(0, |chunk| chunk.texture[Chunk::texture_index(local_pos)])
+        };
+        let cells: Vec<_> = (-8..8)
+            .flat_map(|y| (-8..8).map(move |x| ivec2(x, y)))
+            .filter_map(|cell| placement(&terrain, cell))
+            .collect();
+        let bounds = |index: usize| {
+            let min = cells[index].min;
+            (min, min + cells[index].blocks.size() - 1)
+        };
+        let mut checked = 0;
+        let mut crossing_border = 0;
+        for index in 0..cells.len() {
+            let (min, max) = bounds(index);
+            //Overlapping structures overwrite each other, only check the ones standing alone.
+            let overlaps = (0..cells.len()).any(|other| {
+                let (other_min, other_max) = bounds(other);
+                other != index && min.cmple(other_max).all() && other_min.cmple(max).all()
+            });
+            if overlaps {
+                continue;
+            }
+            let blocks = cells[index].blocks;
+            for z in 0..blocks.size().z {
+                for y in 0..blocks.size().y {
+                    for x in 0..blocks.size().x {
+                        let block = blocks.get(ivec3(x, y, z));
+                        if block != 0 {
+                            assert_eq!(get_block(min + ivec3(x, y, z)), block);
+                        }
+                    }
+                }
+            }
+            checked += 1;
+            let (chunk_min, _) = Chunk::split_world_pos(min);
+            let (chunk_max, _) = Chunk::split_world_pos(max);
+            if chunk_min != chunk_max {
+                crossing_border += 1;
+            }
+        }
+        assert!(checked > 4, "{}", checked);
+        assert!(crossing_border > 0);
+    }
+
+    #[test]
+    fn test_structures_stand_on_the_ground() {
+        let world = World::new(4).unwrap();
+        let terrain = Terrain::new(DEFAULT_SEED);
+        let mut found = 0;
+        for cell_x in -2..2 {
+            for cell_y in -2..2 {
+                let Some(placement) = placement(&terrain, ivec2(cell_x, cell_y)) else {
+                    continue;
+                };
+                //The centre column is the one the ground height was taken from.
+                let size = placement.blocks.size();
+                let center = placement.min + ivec3(size.x / 2, size.y / 2, 0);
+                let below = world.get_block(center - IVec3::Z).unwrap();
+                assert_ne!(below, 0);
+                assert!(center.x.div_euclid(CELL_SIZE) == cell_x);
+                found += 1;
+            }
+        }
+        assert!(found > 0);
+    }
+}
@@ -0,0 +1,365 @@
+mod tests;
+
+use crate::world::biome::Biome;
+use crate::world::block_registry::STONE;
+use crate::world::chunk::{CHUNK_SIDE_SIZE, Chunk, MAX_HEIGHT, MIN_HEIGHT};
+use crate::world::heightmap::Heightmap;
+use crate::world::seed::WorldSeed;
+use fastnoise_lite::{FastNoiseLite, NoiseType};
+use glam::{IVec2, IVec3, ivec3};
+
+//Density is sampled every DENSITY_CELL voxels along each axis and interpolated in between, which
+//keeps the 3D noise affordable and smooths the surface.
+const DENSITY_CELL: i32 = 4;
+const CELLS: i32 = CHUNK_SIDE_SIZE / DENSITY_CELL;
+const LATTICE_SIDE: i32 = CELLS + 1;
+//Temperature and humidity change over hundreds of voxels, so biomes are wide.
+const CLIMATE_FREQUENCY: f32 = 0.004;
+//Layers of the biome's subsurface block under its surface block.
+const SUBSURFACE_DEPTH: i32 = 3;
+//Voxels of height the 3D noise is worth. The larger, the more overhangs, arches and cliffs.
+const OVERHANG_DEPTH: f32 = 8.0;
+//Worm caves are tunnels where two noises are both closer to 0 than WORM_RADIUS. They can break
+//through the surface.
+const WORM_RADIUS: f32 = 0.08;
+//Cheese caves are chambers where their noise is above CHEESE_THRESHOLD, deeper than CHEESE_TOP.
+const CHEESE_THRESHOLD: f32 = 0.6;
+const CHEESE_TOP: i32 = -16;
+//Scales cave noise to density so caves have sharp walls.
+const CAVE_SHARPNESS: f32 = 10.0;
+//Ground is searched no deeper than this, for example below cave mouths.
+pub(crate) const LOWEST_SURFACE: i32 = MIN_HEIGHT - 16;
+
+//Generates terrain from a density function: voxels are solid where the density is positive. A
+//height bias makes the density fall with z around a base height the biomes give each column, 3D
+//noise on top of it bends the surface into overhangs, and caves are carved out of it. Nothing is
+//solid above MAX_HEIGHT, and without caves everything below MIN_HEIGHT is. With a heightmap the
+//columns are solid up to the heights it gives instead, which can go above MAX_HEIGHT, and the
+//biomes only pick surface blocks.
+pub(crate) struct Terrain {
+    seed: WorldSeed,
+    heightmap: Option<Heightmap>,
+    temperature: FastNoiseLite,
+    humidity: FastNoiseLite,
+    height: FastNoiseLite,
+    overhang: FastNoiseLite,
+    worm_a: FastNoiseLite,
+    worm_b: FastNoiseLite,
+    cheese: FastNoiseLite,
+}
+
+impl Terrain {
+    pub(crate) fn new(seed: WorldSeed) -> Self {
+        let noise_seed = seed.noise_seed();
+        Self {
+            seed,
+            heightmap: None,
+            temperature: noise(noise_seed.wrapping_add(5), CLIMATE_FREQUENCY),
+            humidity: noise(noise_seed.wrapping_add(6), CLIMATE_FREQUENCY),
+            height: noise(noise_seed, 0.05),
+            overhang: noise(noise_seed.wrapping_add(1), 0.04),
+            worm_a: noise(noise_seed.wrapping_add(2), 0.02),
+            worm_b: noise(noise_seed.wrapping_add(3), 0.02),
+            cheese: noise(noise_seed.wrapping_add(4), 0.03),
+        }
+    }
+
+    pub(crate) fn with_heightmap(seed: WorldSeed, heightmap: Heightmap) -> Self {
+        Self {
+            heightmap: Some(heightmap),
+            ..Self::new(seed)
+        }
+    }
+
+    //The seed structures and decorations placed on the terrain derive from too.
+    pub(crate) fn seed(&self) -> WorldSeed {
+        self.seed
+    }
+
+    //Temperature and humidity of the world column (x, y), both from 0 to 1.
+    fn climate(&self, x: i32, y: i32) -> (f32, f32) {
+        let (x, y) = (x as f32, y as f32);
+        (
+            (self.temperature.get_noise_2d(x, y) + 1.0) / 2.0,
+            (self.humidity.get_noise_2d(x, y) + 1.0) / 2.0,
+        )
+    }
+
+    pub(crate) fn biome(&self, x: i32, y: i32) -> Biome {
+        let (temperature, humidity) = self.climate(x, y);
+        Biome::from_climate(temperature, humidity)
+    }
+
+    //Height the surface of the world column (x, y) is biased towards.
+    pub(crate) fn base_height(&self, x: i32, y: i32) -> f32 {
+        let (temperature, humidity) = self.climate(x, y);
+        let height = (self.height.get_noise_2d(x as f32, y as f32) + 1.0) / 2.0;
+        Biome::blended_height(temperature, humidity, height)
+    }
+
+    //Density at a lattice point, whose coordinates are multiples of DENSITY_CELL, given the
+    //base_height of its column.
+    fn sample(&self, lattice_pos: IVec3, base_height: f32) -> f32 {
+        //Interpolation between lattice points above MAX_HEIGHT - DENSITY_CELL stays negative, so
+        //nothing is solid above MAX_HEIGHT.
+        if lattice_pos.z > MAX_HEIGHT - DENSITY_CELL {
+            return -1.0;
+        }
+        let pos = lattice_pos.as_vec3();
+        let mut density = if lattice_pos.z < MIN_HEIGHT {
+            1.0
+        } else {
+            (base_height - pos.z) / OVERHANG_DEPTH + self.overhang.get_noise_3d(pos.x, pos.y, pos.z)
+        };
+        let worm = self
+            .worm_a
+            .get_noise_3d(pos.x, pos.y, pos.z)
+            .abs()
+            .max(self.worm_b.get_noise_3d(pos.x, pos.y, pos.z).abs());
+        density = density.min((worm - WORM_RADIUS) * CAVE_SHARPNESS);
+        if lattice_pos.z < CHEESE_TOP {
+            let cheese = self.cheese.get_noise_3d(pos.x, pos.y, pos.z);
+            density = density.min((CHEESE_THRESHOLD - cheese) * CAVE_SHARPNESS);
+        }
+        density
+    }
+
+    //Density at any voxel, interpolated from the lattice points around it exactly like fill does.
+    pub(crate) fn density(&self, world_pos: IVec3) -> f32 {
+        if let Some(heightmap) = &self.heightmap {
+            return if world_pos.z <= heightmap.height(world_pos.x, world_pos.y) {
+                1.0
+            } else {
+                -1.0
+            };
+        }
+        let cell_min = world_pos.div_euclid(IVec3::splat(DENSITY_CELL)) * DENSITY_CELL;
+        let corners = CORNERS.map(|corner| {
+            let lattice_pos = cell_min + corner * DENSITY_CELL;
+            self.sample(lattice_pos, self.base_height(lattice_pos.x, lattice_pos.y))
+        });
+        interpolate(&corners, world_pos - cell_min)
+    }
+
+    //The highest solid voxel of the world column (x, y), or LOWEST_SURFACE if there is none above
+    //it.
+    pub(crate) fn surface_height(&self, x: i32, y: i32) -> i32 {
+        if let Some(heightmap) = &self.heightmap {
+            return heightmap.height(x, y);
+        }
+        let column = IVec2::new(x, y);
+        let cell_min = column.div_euclid(IVec2::splat(DENSITY_CELL)) * DENSITY_CELL;
+        let corner_columns = [0, 1, 2, 3]
+            .map(|corner| cell_min + IVec2::new(corner & 1, corner >> 1) * DENSITY_CELL);
+        let base_heights = corner_columns.map(|corner| self.base_height(corner.x, corner.y));
+        let layer = |z: i32| {
+            [0, 1, 2, 3]
+                .map(|corner| self.sample(corner_columns[corner].extend(z), base_heights[corner]))
+        };
+        let mut upper_z = MAX_HEIGHT.div_euclid(DENSITY_CELL) * DENSITY_CELL + DENSITY_CELL;
+        let mut upper = layer(upper_z);
+        while upper_z > LOWEST_SURFACE {
+            let lower_z = upper_z - DENSITY_CELL;
+            let lower = layer(lower_z);
+            let corners = [
+                lower[0], lower[1], lower[2], lower[3], upper[0], upper[1], upper[2], upper[3],
+            ];
+            for z in (lower_z..upper_z).rev() {
+                let offset = (column - cell_min).extend(z - lower_z);
+                if z >= LOWEST_SURFACE && interpolate(&corners, offset) > 0.0 {
+                    return z;
+                }
+            }
+            upper_z = lower_z;
+            upper = lower;
+        }
+        LOWEST_SURFACE
+    }
+
+    //Nothing is solid above this height, MAX_HEIGHT unless a heightmap goes higher.
+    pub(crate) fn max_surface_height(&self) -> i32 {
+        match &self.heightmap {
+            Some(heightmap) => heightmap.max_height(),
+            None => MAX_HEIGHT,
+        }
+    }
+
+    //Writes the terrain of the chunk at `chunk_pos` into its texture. Returns whether any voxel
+    //is solid.
+    pub(crate) fn fill(&self, chunk_pos: IVec3, texture: &mut [u8]) -> bool {
+        let chunk_min = chunk_pos * CHUNK_SIDE_SIZE;
+        if chunk_min.z > self.max_surface_height() {
+            return false;
+        }
+        if let Some(heightmap) = &self.heightmap {
+            return self.fill_heightmap(heightmap, chunk_min, texture);
+        }
+        let mut base_heights = vec![0.0; (LATTICE_SIDE * LATTICE_SIDE) as usize];
+        //Lattice points below MIN_HEIGHT do not depend on the base height.
+        if chunk_min.z + CHUNK_SIDE_SIZE + DENSITY_CELL >= MIN_HEIGHT {
+            for y in 0..LATTICE_SIDE {
+                for x in 0..LATTICE_SIDE {
+                    let column = chunk_min.truncate() + IVec2::new(x, y) * DENSITY_CELL;
+                    base_heights[(x + y * LATTICE_SIDE) as usize] =
+                        self.base_height(column.x, column.y);
+                }
+            }
+        }
+        //One more layer of cells above the chunk tells how deep its top voxels are under the
+        //surface.
+        let mut lattice =
+            Vec::with_capacity((LATTICE_SIDE * LATTICE_SIDE * (LATTICE_SIDE + 1)) as usize);
+        for z in 0..LATTICE_SIDE + 1 {
+            for y in 0..LATTICE_SIDE {
+                for x in 0..LATTICE_SIDE {
+                    let base_height = base_heights[(x + y * LATTICE_SIDE) as usize];
+                    lattice
+                        .push(self.sample(chunk_min + ivec3(x, y, z) * DENSITY_CELL, base_height));
+                }
+            }
+        }
+        let chunk_lattice = &lattice[..(LATTICE_SIDE * LATTICE_SIDE * LATTICE_SIDE) as usize];
+        if chunk_lattice.iter().all(|density| *density <= 0.0) {
+            return false;
+        }
+        let lattice_index = |pos: IVec3| {
+            (pos.x + pos.y * LATTICE_SIDE + pos.z * LATTICE_SIDE * LATTICE_SIDE) as usize
+        };
+        let height = CHUNK_SIDE_SIZE + DENSITY_CELL;
+        let solid_index = |pos: IVec3| {
+            (pos.x + pos.y * CHUNK_SIDE_SIZE + pos.z * CHUNK_SIDE_SIZE * CHUNK_SIDE_SIZE) as usize
+        };
+        let mut solid = vec![false; (CHUNK_SIDE_SIZE * CHUNK_SIDE_SIZE * height) as usize];
+        for cell_z in 0..CELLS + 1 {
+            for cell_y in 0..CELLS {
+                for cell_x in 0..CELLS {
+                    let cell = ivec3(cell_x, cell_y, cell_z);
+                    let corners = CORNERS.map(|corner| lattice[lattice_index(cell + corner)]);
+                    //Interpolating only mixes the corners, so cells with corners on one side of 0
+                    //are all air or all solid.
+                    if corners.iter().all(|density| *density <= 0.0) {
+                        continue;
+                    }
+                    let all_solid = corners.iter().all(|density| *density > 0.0);
+                    for z in 0..DENSITY_CELL {
+                        for y in 0..DENSITY_CELL {
+                            for x in 0..DENSITY_CELL {
+                                let offset = ivec3(x, y, z);
+                                if all_solid || interpolate(&corners, offset) > 0.0 {
+                                    solid[solid_index(cell * DENSITY_CELL + offset)] = true;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut not_empty = false;
+        for y in 0..CHUNK_SIDE_SIZE {
+            for x in 0..CHUNK_SIDE_SIZE {
+                //Looked up on the first voxel that needs it, deep columns never do.
+                let mut biome = None;
+                //Solid voxels right above the current one.
+                let mut depth = 0;
+                for z in (0..height).rev() {
+                    let local_pos = ivec3(x, y, z);
+                    if !solid[solid_index(local_pos)] {
+                        depth = 0;
+                        continue;
+                    }
+                    if z < CHUNK_SIDE_SIZE {
+                        //Deep cave floors stay bare stone.
+                        let block = if chunk_min.z + z < LOWEST_SURFACE || depth > SUBSURFACE_DEPTH
+                        {
+                            STONE
+                        } else {
+                            let biome = *biome.get_or_insert_with(|| {
+                                self.biome(chunk_min.x + x, chunk_min.y + y)
+                            });
+                            if depth == 0 {
+                                biome.surface_block()
+                            } else {
+                                biome.subsurface_block()
+                            }
+                        };
+                        texture[Chunk::texture_index(local_pos)] = block;
+                        not_empty = true;
+                    }
+                    depth += 1;
+                }
+            }
+        }
+        not_empty
+    }
+
+    //Fills every column up to its height, with the painted block or the biome's surface block on
+    //top, then the biome's subsurface block and stone.
+    fn fill_heightmap(&self, heightmap: &Heightmap, chunk_min: IVec3, texture: &mut [u8]) -> bool {
+        let mut not_empty = false;
+        for y in 0..CHUNK_SIDE_SIZE {
+            for x in 0..CHUNK_SIDE_SIZE {
+                let (column_x, column_y) = (chunk_min.x + x, chunk_min.y + y);
+                let top = heightmap.height(column_x, column_y) - chunk_min.z;
+                if top < 0 {
+                    continue;
+                }
+                let mut biome = None;
+                for z in 0..=top.min(CHUNK_SIDE_SIZE - 1) {
+                    let depth = top - z;
+                    let block = if depth > SUBSURFACE_DEPTH {
+                        STONE
+                    } else {
+                        let biome = *biome.get_or_insert_with(|| self.biome(column_x, column_y));
+                        if depth > 0 {
+                            biome.subsurface_block()
+                        } else {
+                            heightmap
+                                .material(column_x, column_y)
+                                .unwrap_or(biome.surface_block())
+                        }
+                    };
+                    texture[Chunk::texture_index(ivec3(x, y, z))] = block;
+                }
+                not_empty = true;
+            }
+        }
+        not_empty
+    }
+}
+
+//Corners of a lattice cell, x first, then y, then z.
+const CORNERS: [IVec3; 8] = [
+    ivec3(0, 0, 0),
+    ivec3(1, 0, 0),
+    ivec3(0, 1, 0),
+    ivec3(1, 1, 0),
+    ivec3(0, 0, 1),
+    ivec3(1, 0, 1),
+    ivec3(0, 1, 1),
+    ivec3(1, 1, 1),
+];
+
+//Trilinear interpolation of the CORNERS densities of a cell at `offset` voxels from its minimum.
+fn interpolate(corners: &[f32; 8], offset: IVec3) -> f32 {
+    let t = offset.as_vec3() / DENSITY_CELL as f32;
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+    let y0 = lerp(
+        lerp(corners[0], corners[1], t.x),
+        lerp(corners[2], corners[3], t.x),
+        t.y,
+    );
+    let y1 = lerp(
+        lerp(corners[4], corners[5], t.x),
+        lerp(corners[6], corners[7], t.x),
+        t.y,
+    );
+    lerp(y0, y1, t.z)
+}
+
+fn noise(seed: i32, frequency: f32) -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(seed);
+    noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+    noise.set_frequency(Some(frequency));
+    noise
+}
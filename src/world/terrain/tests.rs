@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod tests {
+    use crate::world::World;
+    use crate::world::chunk::{CHUNK_SIDE_SIZE, Chunk, MAX_HEIGHT, MIN_HEIGHT};
+    use crate::world::seed::DEFAULT_SEED;
+    use crate::world::terrain::Terrain;
+    use glam::ivec3;
+
+    #[test]
+    fn test_fill_matches_density() {
+        let terrain = Terrain::new(DEFAULT_SEED);
+        //Too deep for structures, so only the density decides.
+        for chunk_pos in [ivec3(0, 0, -2), ivec3(-1, 2, -3)] {
+            let chunk = Chunk::new(chunk_pos, &terrain).unwrap();
+            let chunk_min = chunk_pos * CHUNK_SIDE_SIZE;
+            for z in (0..CHUNK_SIDE_SIZE).step_by(3) {
+                for y in (0..CHUNK_SIDE_SIZE).step_by(5) {
+                    for x in 0..CHUNK_SIDE_SIZE {
+                        let local_pos = ivec3(x, y, z);
+                        let solid = terrain.density(chunk_min + local_pos) > 0.0;
+                        assert_eq!(chunk.get_block(local_pos) != 0, solid);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty_chunks_above_the_terrain() {
+        let terrain = Terrain::new(DEFAULT_SEED);
+        assert!(Chunk::new(ivec3(0, 0, 2), &terrain).is_none());
+        assert!(Chunk::new(ivec3(-5, 3, 4), &terrain).is_none());
+    }
+
+    #[test]
+    fn test_surface_height() {
+        let terrain = Terrain::new(DEFAULT_SEED);
+        for (x, y) in [(0, 0), (17, -40), (-63, 5), (100, 100)] {
+            let surface = terrain.surface_height(x, y);
+            assert!(surface <= MAX_HEIGHT);
+            assert!(terrain.density(ivec3(x, y, surface)) > 0.0);
+            for z in surface + 1..=MAX_HEIGHT + 4 {
+                assert!(terrain.density(ivec3(x, y, z)) <= 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_caves_and_overhangs() {
+        let world = World::new(4).unwrap();
+        let mut overhangs = 0;
+        let mut cave_voxels = 0;
+        for x in -64..64 {
+            for y in -64..64 {
+                let solid = |z: i32| world.get_block(ivec3(x, y, z)).is_some_and(|b| b != 0);
+                //Air under solid ground above the lowest base height.
+                if (MIN_HEIGHT + 1..MAX_HEIGHT).any(|z| solid(z) && !solid(z - 1)) {
+                    overhangs += 1;
+                }
+                cave_voxels += (-64..0).filter(|z| !solid(*z)).count();
+            }
+        }
+        assert!(overhangs > 0);
+        assert!(cave_voxels > 1000);
+    }
+}
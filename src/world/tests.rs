@@ -0,0 +1,178 @@
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::world::block_registry::STONE;
+    use crate::world::edit::Region;
+    use crate::world::{BlockChange, World};
+    use glam::{IVec3, ivec3};
+
+    //Shared by the tests of every module that needs a world.
+    pub(crate) fn create_test_world() -> World {
+        let mut world = World::new(4).unwrap();
+        world.take_dirty_chunks();
+        world
+    }
+
+    //Adds a stone floor from (0, 0) to (30, 30) with its top at z = 65, high above the generated
+    //terrain.
+    pub(crate) fn create_test_world_with_floor() -> World {
+        let mut world = create_test_world();
+        world
+            .fill(&Region::new(ivec3(0, 0, 64), ivec3(30, 30, 64)), STONE)
+            .unwrap();
+        world.take_dirty_chunks();
+        world
+    }
+
+    fn sorted(mut positions: Vec<IVec3>) -> Vec<IVec3> {
+        positions.sort_by_key(|p| (p.x, p.y, p.z));
+        positions
+    }
+
+    #[test]
+    fn test_get_block_generated_terrain() {
+        let world = create_test_world();
+        //Below MIN_HEIGHT only caves are open, and nothing is solid above MAX_HEIGHT. Deep ground is
+        //stone whatever the biome.
+        assert_eq!(world.get_block(IVec3::new(3, -7, -1)), Some(STONE));
+        assert_eq!(world.get_block(IVec3::new(3, -7, 40)), Some(0));
+        assert_eq!(world.get_block(IVec3::new(1000, 0, 0)), None);
+    }
+
+    #[test]
+    fn test_set_block_negative_coordinates() {
+        let mut world = create_test_world();
+        let position = IVec3::new(-1, -33, -5);
+        assert_eq!(world.set_block(position, 0), Some(STONE));
+        assert_eq!(world.get_block(position), Some(0));
+        assert_eq!(world.get_block(IVec3::new(-2, -33, -5)), Some(STONE));
+        let chunk = world.loaded_chunks[&IVec3::new(-1, -2, -1)]
+            .as_ref()
+            .unwrap();
+        assert_eq!(chunk.get_block(IVec3::new(31, 31, 27)), 0);
+    }
+
+    #[test]
+    fn test_set_block_creates_air_chunk() {
+        let mut world = create_test_world();
+        let position = IVec3::new(5, 5, 40);
+        assert!(world.loaded_chunks[&IVec3::new(0, 0, 1)].is_none());
+
+        assert_eq!(world.set_block(position, 0), Some(0));
+        assert!(world.loaded_chunks[&IVec3::new(0, 0, 1)].is_none());
+        assert!(world.take_dirty_chunks().is_empty());
+
+        assert_eq!(world.set_block(position, 2), Some(0));
+        assert!(world.loaded_chunks[&IVec3::new(0, 0, 1)].is_some());
+        assert_eq!(world.get_block(position), Some(2));
+    }
+
+    #[test]
+    fn test_set_block_unloaded() {
+        let mut world = create_test_world();
+        world.record_block_changes(true);
+        assert_eq!(world.set_block(IVec3::new(0, 0, 1000), 1), None);
+        assert!(world.take_block_changes().is_empty());
+    }
+
+    #[test]
+    fn test_dirty_tracking() {
+        let mut world = create_test_world();
+        world.set_block(IVec3::new(5, 5, 40), 1);
+        //Its shadow reaches down to the terrain in the chunk below.
+        assert_eq!(
+            sorted(world.take_dirty_chunks()),
+            vec![IVec3::new(0, 0, 0), IVec3::new(0, 0, 1)]
+        );
+
+        //A corner voxel touches seven neighbouring chunks.
+        world.set_block(IVec3::new(0, 31, 32), 1);
+        assert_eq!(
+            sorted(world.take_dirty_chunks()),
+            vec![
+                IVec3::new(-1, 0, 0),
+                IVec3::new(-1, 0, 1),
+                IVec3::new(-1, 1, 0),
+                IVec3::new(-1, 1, 1),
+                IVec3::new(0, 0, 0),
+                IVec3::new(0, 0, 1),
+                IVec3::new(0, 1, 0),
+                IVec3::new(0, 1, 1),
+            ]
+        );
+
+        //Setting the same block again changes nothing.
+        world.set_block(IVec3::new(0, 31, 32), 1);
+        assert!(world.take_dirty_chunks().is_empty());
+    }
+
+    #[test]
+    fn test_block_change_events() {
+        let mut world = create_test_world();
+        world.record_block_changes(true);
+        world.set_block(IVec3::new(5, 5, 40), 3);
+        world.set_block(IVec3::new(5, 5, 40), 4);
+        world.set_block(IVec3::new(5, 5, 40), 4);
+        assert_eq!(
+            world.take_block_changes(),
+            vec![
+                BlockChange {
+                    position: IVec3::new(5, 5, 40),
+                    old: 0,
+                    new: 3
+                },
+                BlockChange {
+                    position: IVec3::new(5, 5, 40),
+                    old: 3,
+                    new: 4
+                },
+            ]
+        );
+        assert!(world.take_block_changes().is_empty());
+
+        //Once recording stops, changes are no longer kept around.
+        world.record_block_changes(false);
+        world.set_block(IVec3::new(5, 5, 40), 5);
+        assert!(world.block_changes.is_none());
+        assert!(world.take_block_changes().is_empty());
+    }
+
+    #[test]
+    fn test_streaming_marks_new_chunks_dirty() {
+        let mut world = create_test_world();
+        world.update_map_position(IVec3::new(1, 0, 0));
+        let dirty = world.take_dirty_chunks();
+        assert!(dirty.contains(&IVec3::new(5, 0, 0)));
+        assert!(dirty.contains(&IVec3::new(4, 0, 0)));
+        assert!(!dirty.contains(&IVec3::new(-3, 0, 0)));
+        assert!(!world.loaded_chunks.contains_key(&IVec3::new(-3, 0, 0)));
+
+        //Chunks that were already loaded are remeshed when a new chunk appears on any of their
+        //26 sides, including the ones that only touch new chunks at an edge or a corner.
+        let old: Vec<IVec3> = world.loaded_chunks.keys().copied().collect();
+        world.update_map_position(IVec3::new(1, 1, 0));
+        let dirty = world.take_dirty_chunks();
+        let new: Vec<IVec3> = world
+            .loaded_chunks
+            .keys()
+            .copied()
+            .filter(|pos| !old.contains(pos))
+            .collect();
+        let touching = |chunk_pos: IVec3, faces_only: bool| {
+            new.iter().any(|pos| {
+                let distance = (*pos - chunk_pos).abs();
+                distance.max_element() == 1 && (!faces_only || distance.element_sum() == 1)
+            })
+        };
+        let kept = old
+            .iter()
+            .filter(|pos| world.loaded_chunks.contains_key(pos));
+        let mut diagonal_only = 0;
+        for chunk_pos in kept.filter(|pos| touching(**pos, false)) {
+            assert!(dirty.contains(chunk_pos), "{}", chunk_pos);
+            if !touching(*chunk_pos, true) {
+                diagonal_only += 1;
+            }
+        }
+        assert!(diagonal_only > 0);
+    }
+}
@@ -0,0 +1,696 @@
+mod tests;
+
+use crate::world::World;
+use crate::world::block_registry::{AIR, BlockRegistry};
+use crate::world::edit::{Clipboard, Region};
+use crate::world::history::HistoryError;
+use glam::{IVec3, Mat3, Vec3, ivec3};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"VOX ";
+const WRITE_VERSION: i32 = 150;
+const SUPPORTED_VERSIONS: [i32; 2] = [150, 200];
+//Models in a .vox file are at most 256 voxels along each axis.
+const MAX_MODEL_SIDE: i32 = 256;
+//Keeps translations summed along the scene graph far from overflowing.
+const MAX_TRANSLATION: i32 = 1 << 20;
+//Largest scene that is flattened into a clipboard, 128 MiB of blocks.
+const MAX_SCENE_VOLUME: i64 = 1 << 27;
+const UNREGISTERED_COLOR: [u8; 4] = [128, 128, 128, 255];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum VoxError {
+    InvalidMagic,
+    UnsupportedVersion(i32),
+    Truncated,
+    MissingMain,
+    VoxelsWithoutSize,
+    InvalidSize(IVec3),
+    VoxelOutOfBounds(IVec3),
+    InvalidNode(i32),
+    InvalidAttribute(String),
+    InvalidUtf8,
+    SceneTooLarge(IVec3),
+    History(HistoryError),
+}
+
+impl Display for VoxError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VoxError::InvalidMagic => write!(f, "Data is not a .vox file"),
+            VoxError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported .vox version {}", version)
+            }
+            VoxError::Truncated => write!(f, ".vox data ended unexpectedly"),
+            VoxError::MissingMain => write!(f, ".vox data has no MAIN chunk"),
+            VoxError::VoxelsWithoutSize => write!(f, "XYZI chunk without a preceding SIZE chunk"),
+            VoxError::InvalidSize(size) => write!(f, "Invalid .vox model size {}", size),
+            VoxError::VoxelOutOfBounds(position) => {
+                write!(f, "Voxel {} is outside of its model", position)
+            }
+            VoxError::InvalidNode(id) => write!(f, "Invalid .vox scene node {}", id),
+            VoxError::InvalidAttribute(value) => {
+                write!(f, "Invalid .vox node attribute {}", value)
+            }
+            VoxError::InvalidUtf8 => write!(f, ".vox string is not valid UTF-8"),
+            VoxError::SceneTooLarge(size) => write!(f, ".vox scene of size {} is too large", size),
+            VoxError::History(err) => write!(f, "Could not import .vox scene: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for VoxError {}
+
+impl From<HistoryError> for VoxError {
+    fn from(err: HistoryError) -> Self {
+        VoxError::History(err)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoxModel {
+    pub size: IVec3,
+    //Position inside the model and palette index, 1 to 255.
+    pub voxels: Vec<(IVec3, u8)>,
+}
+
+//A model placed in the scene. A voxel at `v` ends up at rotation * (v - size / 2) + translation,
+//with the pivot rounded down like MagicaVoxel does.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VoxInstance {
+    pub model: usize,
+    pub rotation: Mat3,
+    pub translation: IVec3,
+}
+
+impl VoxInstance {
+    fn transform(&self, model: &VoxModel, position: IVec3) -> IVec3 {
+        let pivot = model.size / 2;
+        (self.rotation * (position - pivot).as_vec3())
+            .round()
+            .as_ivec3()
+            + self.translation
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoxFile {
+    pub models: Vec<VoxModel>,
+    pub instances: Vec<VoxInstance>,
+    //Indexed by palette index, entry 0 is unused.
+    pub palette: [[u8; 4]; 256],
+}
+
+impl VoxFile {
+    pub fn parse(bytes: &[u8]) -> Result<Self, VoxError> {
+        let mut reader = Reader::new(bytes);
+        if reader.take(4)? != MAGIC {
+            return Err(VoxError::InvalidMagic);
+        }
+        let version = reader.read_i32()?;
+        if !SUPPORTED_VERSIONS.contains(&version) {
+            return Err(VoxError::UnsupportedVersion(version));
+        }
+        let (id, _, mut children) = reader.read_chunk()?;
+        if id != b"MAIN" {
+            return Err(VoxError::MissingMain);
+        }
+
+        let mut models = Vec::new();
+        let mut size = None;
+        let mut palette = default_palette();
+        let mut nodes = HashMap::new();
+        while !children.is_empty() {
+            let (id, mut content, _) = children.read_chunk()?;
+            match id {
+                b"SIZE" => {
+                    let model_size = content.read_ivec3()?;
+                    if model_size.cmple(IVec3::ZERO).any()
+                        || model_size.cmpgt(IVec3::splat(MAX_MODEL_SIDE)).any()
+                    {
+                        return Err(VoxError::InvalidSize(model_size));
+                    }
+                    size = Some(model_size);
+                }
+                b"XYZI" => {
+                    let size = size.take().ok_or(VoxError::VoxelsWithoutSize)?;
+                    let count = content.read_i32()?.max(0) as usize;
+                    let mut voxels = Vec::new();
+                    for _ in 0..count {
+                        let voxel = content.take(4)?;
+                        let position = ivec3(voxel[0] as i32, voxel[1] as i32, voxel[2] as i32);
+                        if position.cmpge(size).any() {
+                            return Err(VoxError::VoxelOutOfBounds(position));
+                        }
+                        if voxel[3] != 0 {
+                            voxels.push((position, voxel[3]));
+                        }
+                    }
+                    models.push(VoxModel { size, voxels });
+                }
+                b"RGBA" => {
+                    //Entry i holds palette index i + 1, the last entry is unused.
+                    for entry in palette.iter_mut().skip(1) {
+                        *entry = content.take(4)?.try_into().unwrap();
+                    }
+                }
+                b"nTRN" => {
+                    let id = content.read_i32()?;
+                    content.read_dict()?;
+                    let child = content.read_i32()?;
+                    //Reserved id and layer id.
+                    content.read_i32()?;
+                    content.read_i32()?;
+                    let frame_count = content.read_i32()?;
+                    let frame = if frame_count > 0 {
+                        content.read_dict()?
+                    } else {
+                        HashMap::new()
+                    };
+                    let rotation = match frame.get("_r") {
+                        Some(value) => decode_rotation(parse_attribute(value)?)?,
+                        None => Mat3::IDENTITY,
+                    };
+                    let translation = match frame.get("_t") {
+                        Some(value) => parse_translation(value)?,
+                        None => IVec3::ZERO,
+                    };
+                    nodes.insert(id, Node::Transform(child, rotation, translation));
+                }
+                b"nGRP" => {
+                    let id = content.read_i32()?;
+                    content.read_dict()?;
+                    let count = content.read_i32()?.max(0);
+                    let children = (0..count)
+                        .map(|_| content.read_i32())
+                        .collect::<Result<_, _>>()?;
+                    nodes.insert(id, Node::Group(children));
+                }
+                b"nSHP" => {
+                    let id = content.read_i32()?;
+                    content.read_dict()?;
+                    if content.read_i32()? < 1 {
+                        return Err(VoxError::InvalidNode(id));
+                    }
+                    let model = content.read_i32()?;
+                    nodes.insert(id, Node::Shape(model));
+                }
+                //PACK, materials, layers, cameras and notes do not affect the voxels.
+                _ => {}
+            }
+        }
+
+        let mut instances = Vec::new();
+        if nodes.is_empty() {
+            //Files without a scene graph put every model at the origin.
+            for (index, model) in models.iter().enumerate() {
+                instances.push(VoxInstance {
+                    model: index,
+                    rotation: Mat3::IDENTITY,
+                    translation: model.size / 2,
+                });
+            }
+        } else {
+            collect_instances(
+                &nodes,
+                &models,
+                0,
+                Mat3::IDENTITY,
+                IVec3::ZERO,
+                0,
+                &mut instances,
+            )?;
+        }
+        Ok(Self {
+            models,
+            instances,
+            palette,
+        })
+    }
+
+    //Writes one SIZE and XYZI pair per model, a scene graph with the instances under a single
+    //group and the palette.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut children = Vec::new();
+        for model in &self.models {
+            let mut size = Vec::new();
+            write_ivec3(&mut size, model.size);
+            write_chunk(&mut children, b"SIZE", &size);
+            let mut voxels = Vec::new();
+            voxels.extend_from_slice(&(model.voxels.len() as i32).to_le_bytes());
+            for (position, index) in &model.voxels {
+                voxels.extend_from_slice(&[
+                    position.x as u8,
+                    position.y as u8,
+                    position.z as u8,
+                    *index,
+                ]);
+            }
+            write_chunk(&mut children, b"XYZI", &voxels);
+        }
+
+        let mut root = Vec::new();
+        write_transform(&mut root, 0, 1, Mat3::IDENTITY, IVec3::ZERO);
+        write_chunk(&mut children, b"nTRN", &root);
+        let mut group = Vec::new();
+        group.extend_from_slice(&1i32.to_le_bytes());
+        write_dict(&mut group, &[]);
+        group.extend_from_slice(&(self.instances.len() as i32).to_le_bytes());
+        for index in 0..self.instances.len() {
+            group.extend_from_slice(&(2 + 2 * index as i32).to_le_bytes());
+        }
+        write_chunk(&mut children, b"nGRP", &group);
+        for (index, instance) in self.instances.iter().enumerate() {
+            let transform_id = 2 + 2 * index as i32;
+            let mut transform = Vec::new();
+            write_transform(
+                &mut transform,
+                transform_id,
+                transform_id + 1,
+                instance.rotation,
+                instance.translation,
+            );
+            write_chunk(&mut children, b"nTRN", &transform);
+            let mut shape = Vec::new();
+            shape.extend_from_slice(&(transform_id + 1).to_le_bytes());
+            write_dict(&mut shape, &[]);
+            shape.extend_from_slice(&1i32.to_le_bytes());
+            shape.extend_from_slice(&(instance.model as i32).to_le_bytes());
+            write_dict(&mut shape, &[]);
+            write_chunk(&mut children, b"nSHP", &shape);
+        }
+
+        let mut palette = Vec::with_capacity(256 * 4);
+        for index in 1..=256 {
+            palette.extend_from_slice(&self.palette[index % 256]);
+        }
+        write_chunk(&mut children, b"RGBA", &palette);
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&WRITE_VERSION.to_le_bytes());
+        bytes.extend_from_slice(b"MAIN");
+        bytes.extend_from_slice(&0i32.to_le_bytes());
+        bytes.extend_from_slice(&(children.len() as i32).to_le_bytes());
+        bytes.extend_from_slice(&children);
+        bytes
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let bytes = std::fs::read(path)?;
+        Self::parse(&bytes).map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_bytes())
+    }
+
+    //Block id for every palette index. An index whose color is exactly the registry color of the
+    //block with the same id maps to that block, which keeps exported files stable. Any other index
+    //maps to the block with the closest color.
+    pub fn block_mapping(&self, registry: &BlockRegistry) -> [u8; 256] {
+        let mut mapping = [AIR; 256];
+        for (index, color) in self.palette.iter().enumerate().skip(1) {
+            mapping[index] = match registry.get(index as u8) {
+                Some(block) if block.color == *color => index as u8,
+                _ => registry.closest_color(*color).unwrap_or(AIR),
+            };
+        }
+        mapping
+    }
+
+    //All instances flattened into blocks, with the minimum corner of the scene at the origin.
+    //Returns None if the scene has no voxels.
+    pub fn to_clipboard(&self, registry: &BlockRegistry) -> Result<Option<Clipboard>, VoxError> {
+        let mapping = self.block_mapping(registry);
+        let mut blocks = Vec::new();
+        for instance in &self.instances {
+            if !translation_in_bounds(instance.translation) {
+                return Err(VoxError::SceneTooLarge(instance.translation));
+            }
+            let model = &self.models[instance.model];
+            for (position, index) in &model.voxels {
+                blocks.push((
+                    instance.transform(model, *position),
+                    mapping[*index as usize],
+                ));
+            }
+        }
+        let Some(min) = blocks
+            .iter()
+            .map(|(position, _)| *position)
+            .reduce(IVec3::min)
+        else {
+            return Ok(None);
+        };
+        let max = blocks
+            .iter()
+            .map(|(position, _)| *position)
+            .reduce(IVec3::max)
+            .unwrap();
+        let size = max - min + 1;
+        if size.as_i64vec3().element_product() > MAX_SCENE_VOLUME {
+            return Err(VoxError::SceneTooLarge(size));
+        }
+        let mut clipboard = Clipboard::new(size);
+        for (position, block) in blocks {
+            clipboard.set(position - min, block);
+        }
+        Ok(Some(clipboard))
+    }
+
+    //Palette index i holds block id i, so importing the file with the same registry gives back the
+    //same blocks. Clipboards larger than a .vox model are split into several models.
+    pub fn from_clipboard(clipboard: &Clipboard, registry: &BlockRegistry) -> Self {
+        let mut palette = [UNREGISTERED_COLOR; 256];
+        palette[0] = [0; 4];
+        for (index, color) in palette.iter_mut().enumerate().skip(1) {
+            if let Some(block) = registry.get(index as u8) {
+                *color = block.color;
+            }
+        }
+        let mut models = Vec::new();
+        let mut instances = Vec::new();
+        let size = clipboard.size();
+        let tiles = (size + MAX_MODEL_SIDE - 1) / MAX_MODEL_SIDE;
+        for tile_z in 0..tiles.z {
+            for tile_y in 0..tiles.y {
+                for tile_x in 0..tiles.x {
+                    let origin = ivec3(tile_x, tile_y, tile_z) * MAX_MODEL_SIDE;
+                    let model_size = (size - origin).min(IVec3::splat(MAX_MODEL_SIDE));
+                    let mut voxels = Vec::new();
+                    for z in 0..model_size.z {
+                        for y in 0..model_size.y {
+                            for x in 0..model_size.x {
+                                let position = ivec3(x, y, z);
+                                let block = clipboard.get(origin + position);
+                                if block != AIR {
+                                    voxels.push((position, block));
+                                }
+                            }
+                        }
+                    }
+                    if voxels.is_empty() {
+                        continue;
+                    }
+                    instances.push(VoxInstance {
+                        model: models.len(),
+                        rotation: Mat3::IDENTITY,
+                        translation: origin + model_size / 2,
+                    });
+                    models.push(VoxModel {
+                        size: model_size,
+                        voxels,
+                    });
+                }
+            }
+        }
+        Self {
+            models,
+            instances,
+            palette,
+        }
+    }
+}
+
+impl World {
+    //Places the scene with its minimum corner at `position` as one undo step. Empty voxels keep the
+    //world's blocks.
+    pub fn import_vox(&mut self, vox: &VoxFile, position: IVec3) -> Result<usize, VoxError> {
+        match vox.to_clipboard(self.registry())? {
+            Some(clipboard) => Ok(self.paste(&clipboard, position, false)?),
+            None => Ok(0),
+        }
+    }
+
+    pub fn export_vox(&self, region: &Region) -> VoxFile {
+        VoxFile::from_clipboard(&self.copy_region(region), self.registry())
+    }
+}
+
+enum Node {
+    Transform(i32, Mat3, IVec3),
+    Group(Vec<i32>),
+    Shape(i32),
+}
+
+fn collect_instances(
+    nodes: &HashMap<i32, Node>,
+    models: &[VoxModel],
+    id: i32,
+    rotation: Mat3,
+    translation: IVec3,
+    depth: usize,
+    instances: &mut Vec<VoxInstance>,
+) -> Result<(), VoxError> {
+    //A well formed graph is a tree, this only guards against cycles.
+    if depth > nodes.len() {
+        return Err(VoxError::InvalidNode(id));
+    }
+    match nodes.get(&id).ok_or(VoxError::InvalidNode(id))? {
+        Node::Transform(child, local_rotation, local_translation) => {
+            let child_rotation = rotation * *local_rotation;
+            let child_translation =
+                (rotation * local_translation.as_vec3()).round().as_ivec3() + translation;
+            if !translation_in_bounds(child_translation) {
+                return Err(VoxError::InvalidNode(id));
+            }
+            collect_instances(
+                nodes,
+                models,
+                *child,
+                child_rotation,
+                child_translation,
+                depth + 1,
+                instances,
+            )?;
+        }
+        Node::Group(children) => {
+            for child in children {
+                collect_instances(
+                    nodes,
+                    models,
+                    *child,
+                    rotation,
+                    translation,
+                    depth + 1,
+                    instances,
+                )?;
+            }
+        }
+        Node::Shape(model) => {
+            if *model < 0 || *model as usize >= models.len() {
+                return Err(VoxError::InvalidNode(id));
+            }
+            instances.push(VoxInstance {
+                model: *model as usize,
+                rotation,
+                translation,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn parse_attribute(value: &str) -> Result<u8, VoxError> {
+    value
+        .parse()
+        .map_err(|_| VoxError::InvalidAttribute(value.to_string()))
+}
+
+fn parse_translation(value: &str) -> Result<IVec3, VoxError> {
+    let parts: Vec<i32> = value
+        .split_whitespace()
+        .map(|part| part.parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| VoxError::InvalidAttribute(value.to_string()))?;
+    match parts[..] {
+        [x, y, z] if translation_in_bounds(ivec3(x, y, z)) => Ok(ivec3(x, y, z)),
+        _ => Err(VoxError::InvalidAttribute(value.to_string())),
+    }
+}
+
+fn translation_in_bounds(translation: IVec3) -> bool {
+    translation.cmpge(IVec3::splat(-MAX_TRANSLATION)).all()
+        && translation.cmple(IVec3::splat(MAX_TRANSLATION)).all()
+}
+
+//Rotations are stored as a byte: bits 0-1 and 2-3 are the column of the non-zero entry in the
+//first and second row, bits 4-6 are the signs of the three rows.
+fn decode_rotation(value: u8) -> Result<Mat3, VoxError> {
+    let first = (value & 3) as usize;
+    let second = (value >> 2 & 3) as usize;
+    if first > 2 || second > 2 || first == second {
+        return Err(VoxError::InvalidAttribute(value.to_string()));
+    }
+    let third = 3 - first - second;
+    let mut rows = [Vec3::ZERO; 3];
+    for (row, column) in [first, second, third].into_iter().enumerate() {
+        rows[row][column] = if value >> (4 + row) & 1 != 0 {
+            -1.0
+        } else {
+            1.0
+        };
+    }
+    Ok(Mat3::from_cols(rows[0], rows[1], rows[2]).transpose())
+}
+
+fn encode_rotation(rotation: Mat3) -> u8 {
+    let rows = rotation.transpose().to_cols_array_2d();
+    let mut value = 0;
+    for (row, entries) in rows.iter().enumerate() {
+        let column = entries
+            .iter()
+            .position(|entry| *entry != 0.0)
+            .expect("Rotation rows must have a non-zero entry");
+        if row < 2 {
+            value |= (column as u8) << (row * 2);
+        }
+        if entries[column] < 0.0 {
+            value |= 1 << (4 + row);
+        }
+    }
+    value
+}
+
+fn write_chunk(bytes: &mut Vec<u8>, id: &[u8; 4], content: &[u8]) {
+    bytes.extend_from_slice(id);
+    bytes.extend_from_slice(&(content.len() as i32).to_le_bytes());
+    bytes.extend_from_slice(&0i32.to_le_bytes());
+    bytes.extend_from_slice(content);
+}
+
+fn write_ivec3(bytes: &mut Vec<u8>, value: IVec3) {
+    for component in value.to_array() {
+        bytes.extend_from_slice(&component.to_le_bytes());
+    }
+}
+
+fn write_dict(bytes: &mut Vec<u8>, entries: &[(&str, String)]) {
+    bytes.extend_from_slice(&(entries.len() as i32).to_le_bytes());
+    for (key, value) in entries {
+        for text in [*key, value.as_str()] {
+            bytes.extend_from_slice(&(text.len() as i32).to_le_bytes());
+            bytes.extend_from_slice(text.as_bytes());
+        }
+    }
+}
+
+fn write_transform(bytes: &mut Vec<u8>, id: i32, child: i32, rotation: Mat3, translation: IVec3) {
+    bytes.extend_from_slice(&id.to_le_bytes());
+    write_dict(bytes, &[]);
+    bytes.extend_from_slice(&child.to_le_bytes());
+    //Reserved id, layer id and frame count.
+    bytes.extend_from_slice(&(-1i32).to_le_bytes());
+    bytes.extend_from_slice(&0i32.to_le_bytes());
+    bytes.extend_from_slice(&1i32.to_le_bytes());
+    let mut frame = Vec::new();
+    if rotation != Mat3::IDENTITY {
+        frame.push(("_r", encode_rotation(rotation).to_string()));
+    }
+    if translation != IVec3::ZERO {
+        frame.push((
+            "_t",
+            format!("{} {} {}", translation.x, translation.y, translation.z),
+        ));
+    }
+    write_dict(bytes, &frame);
+}
+
+//MagicaVoxel's palette for files without an RGBA chunk: the 6x6x6 color cube without black,
+//counting down from white, followed by ramps of red, green, blue and gray.
+fn default_palette() -> [[u8; 4]; 256] {
+    let mut palette = [[0; 4]; 256];
+    let mut index = 1;
+    for r in (0..6).rev() {
+        for g in (0..6).rev() {
+            for b in (0..6).rev() {
+                if r + g + b == 0 {
+                    continue;
+                }
+                palette[index] = [r * 0x33, g * 0x33, b * 0x33, 0xff];
+                index += 1;
+            }
+        }
+    }
+    let ramp = [0xee, 0xdd, 0xbb, 0xaa, 0x88, 0x77, 0x55, 0x44, 0x22, 0x11];
+    for channel in 0..4 {
+        for value in ramp {
+            palette[index] = match channel {
+                3 => [value, value, value, 0xff],
+                _ => {
+                    let mut color = [0, 0, 0, 0xff];
+                    color[channel] = value;
+                    color
+                }
+            };
+            index += 1;
+        }
+    }
+    palette
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    cursor: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], VoxError> {
+        let end = self.cursor.checked_add(count).ok_or(VoxError::Truncated)?;
+        let slice = self
+            .bytes
+            .get(self.cursor..end)
+            .ok_or(VoxError::Truncated)?;
+        self.cursor = end;
+        Ok(slice)
+    }
+
+    fn read_i32(&mut self) -> Result<i32, VoxError> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn read_ivec3(&mut self) -> Result<IVec3, VoxError> {
+        Ok(ivec3(self.read_i32()?, self.read_i32()?, self.read_i32()?))
+    }
+
+    fn read_size(&mut self) -> Result<usize, VoxError> {
+        usize::try_from(self.read_i32()?).map_err(|_| VoxError::Truncated)
+    }
+
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, cursor: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.cursor >= self.bytes.len()
+    }
+
+    //Chunk id, content and children.
+    fn read_chunk(&mut self) -> Result<(&'a [u8], Reader<'a>, Reader<'a>), VoxError> {
+        let id = self.take(4)?;
+        let content_size = self.read_size()?;
+        let children_size = self.read_size()?;
+        let content = Reader::new(self.take(content_size)?);
+        let children = Reader::new(self.take(children_size)?);
+        Ok((id, content, children))
+    }
+
+    fn read_string(&mut self) -> Result<String, VoxError> {
+        let len = self.read_size()?;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| VoxError::InvalidUtf8)
+    }
+
+    fn read_dict(&mut self) -> Result<HashMap<String, String>, VoxError> {
+        let count = self.read_size()?;
+        let mut dict = HashMap::new();
+        for _ in 0..count {
+            let key = self.read_string()?;
+            let value = self.read_string()?;
+            dict.insert(key, value);
+        }
+        Ok(dict)
+    }
+}
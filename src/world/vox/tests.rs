@@ -0,0 +1,210 @@
+#[cfg(test)]
+mod tests {
+    use crate::world::block_registry::{BlockRegistry, GRASS, RED_LAMP, SAND, STONE};
+    use crate::world::edit::{Clipboard, Region};
+    use crate::world::tests::tests::create_test_world;
+    use crate::world::vox::{VoxError, VoxFile, VoxInstance, parse_translation};
+    use glam::{IVec3, Mat3, ivec3};
+
+    const SINGLE: &[u8] = include_bytes!("fixtures/single.vox");
+    const SCENE: &[u8] = include_bytes!("fixtures/scene.vox");
+
+    #[test]
+    fn test_parse_single_model() {
+        let vox = VoxFile::parse(SINGLE).unwrap();
+        assert_eq!(vox.models.len(), 1);
+        assert_eq!(vox.models[0].size, ivec3(3, 2, 4));
+        assert_eq!(vox.models[0].voxels[1], (ivec3(2, 1, 0), 121));
+        //Without an RGBA chunk the default palette is used.
+        assert_eq!(vox.palette[1], [0xff, 0xff, 0xff, 0xff]);
+        assert_eq!(vox.palette[2], [0xff, 0xff, 0xcc, 0xff]);
+        assert_eq!(vox.palette[216], [0xee, 0, 0, 0xff]);
+        assert_eq!(vox.palette[255], [0x11, 0x11, 0x11, 0xff]);
+
+        let clipboard = vox
+            .to_clipboard(&BlockRegistry::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(clipboard.size(), ivec3(3, 2, 4));
+        assert_ne!(clipboard.get(ivec3(1, 1, 3)), 0);
+        assert_eq!(clipboard.get(ivec3(1, 1, 2)), 0);
+    }
+
+    #[test]
+    fn test_parse_scene() {
+        let vox = VoxFile::parse(SCENE).unwrap();
+        assert_eq!(vox.models.len(), 2);
+        assert_eq!(vox.instances.len(), 2);
+        assert_eq!(vox.instances[0].translation, ivec3(10, 0, 0));
+        assert_eq!(vox.instances[1].translation, ivec3(0, 5, -2));
+        assert_eq!(
+            vox.instances[1].rotation * IVec3::X.as_vec3(),
+            IVec3::Y.as_vec3()
+        );
+        assert_eq!(vox.palette[1], [92, 158, 71, 255]);
+
+        let mapping = vox.block_mapping(&BlockRegistry::default());
+        //Same color as the block with the same id.
+        assert_eq!(mapping[1], GRASS);
+        //Closest colors.
+        assert_eq!(mapping[2], RED_LAMP);
+        assert_eq!(mapping[3], STONE);
+        assert_eq!(mapping[4], SAND);
+
+        let clipboard = vox
+            .to_clipboard(&BlockRegistry::default())
+            .unwrap()
+            .unwrap();
+        //Voxels end up at (9, -1, -1), (10, 0, 0) and (0, 4..=6, -2).
+        assert_eq!(clipboard.size(), ivec3(11, 8, 3));
+        let min = ivec3(0, -1, -2);
+        assert_eq!(clipboard.get(ivec3(9, -1, -1) - min), GRASS);
+        assert_eq!(clipboard.get(ivec3(10, 0, 0) - min), RED_LAMP);
+        assert_eq!(clipboard.get(ivec3(0, 4, -2) - min), STONE);
+        assert_eq!(clipboard.get(ivec3(0, 5, -2) - min), STONE);
+        assert_eq!(clipboard.get(ivec3(0, 6, -2) - min), SAND);
+        let solid = clipboard
+            .blocks()
+            .iter()
+            .filter(|block| **block != 0)
+            .count();
+        assert_eq!(solid, 5);
+    }
+
+    #[test]
+    fn test_file_round_trip() {
+        for fixture in [SINGLE, SCENE] {
+            let vox = VoxFile::parse(fixture).unwrap();
+            let bytes = vox.to_bytes();
+            assert_eq!(VoxFile::parse(&bytes), Ok(vox));
+            //Writing is deterministic.
+            assert_eq!(VoxFile::parse(&bytes).unwrap().to_bytes(), bytes);
+        }
+    }
+
+    #[test]
+    fn test_save_load() {
+        let path = std::env::temp_dir().join(format!("vxl_vox_{}.vox", std::process::id()));
+        let vox = VoxFile::parse(SCENE).unwrap();
+        vox.save(&path).unwrap();
+        let loaded = VoxFile::load(&path);
+        std::fs::write(&path, b"VOX ").unwrap();
+        let invalid = VoxFile::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap(), vox);
+        assert_eq!(invalid.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_world_round_trip() {
+        let mut world = create_test_world();
+        let vox = VoxFile::parse(SCENE).unwrap();
+        let position = ivec3(20, 20, 50);
+        assert_eq!(world.import_vox(&vox, position).unwrap(), 5);
+        assert_eq!(world.get_block(position + ivec3(9, 0, 1)), Some(GRASS));
+
+        let region = Region::new(position, position + ivec3(10, 7, 2));
+        let exported = world.export_vox(&region);
+        assert_eq!(exported.models.len(), 1);
+        assert_eq!(exported.palette[STONE as usize], [128, 128, 133, 255]);
+        let reparsed = VoxFile::parse(&exported.to_bytes()).unwrap();
+        assert_eq!(
+            reparsed.to_clipboard(world.registry()),
+            vox.to_clipboard(world.registry())
+        );
+    }
+
+    #[test]
+    fn test_export_splits_large_regions() {
+        let mut clipboard = Clipboard::new(ivec3(300, 2, 1));
+        clipboard.set(ivec3(0, 0, 0), STONE);
+        clipboard.set(ivec3(299, 1, 0), 77);
+        let registry = BlockRegistry::default();
+        let vox = VoxFile::from_clipboard(&clipboard, &registry);
+        assert_eq!(vox.models.len(), 2);
+        assert_eq!(vox.models[1].size, ivec3(44, 2, 1));
+        //Unregistered ids keep their own palette index.
+        assert_eq!(vox.models[1].voxels, vec![(ivec3(43, 1, 0), 77)]);
+
+        let reparsed = VoxFile::parse(&vox.to_bytes()).unwrap();
+        let mut registry = BlockRegistry::default();
+        registry
+            .register(77, "marker", [128, 128, 128, 255])
+            .unwrap();
+        assert_eq!(reparsed.to_clipboard(&registry), Ok(Some(clipboard)));
+    }
+
+    #[test]
+    fn test_rotation_encoding() {
+        let vox = VoxFile::parse(SCENE).unwrap();
+        let mut rotated = vox.clone();
+        //Mirror x and swap y with z.
+        rotated.instances[0].rotation =
+            Mat3::from_cols(-IVec3::X.as_vec3(), IVec3::Z.as_vec3(), IVec3::Y.as_vec3());
+        assert_eq!(VoxFile::parse(&rotated.to_bytes()), Ok(rotated));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(VoxFile::parse(b"VOX"), Err(VoxError::Truncated));
+        assert_eq!(
+            VoxFile::parse(b"RIFF\x96\x00\x00\x00"),
+            Err(VoxError::InvalidMagic)
+        );
+        assert_eq!(
+            VoxFile::parse(b"VOX \x01\x00\x00\x00"),
+            Err(VoxError::UnsupportedVersion(1))
+        );
+        let mut not_main = SINGLE.to_vec();
+        not_main[8..12].copy_from_slice(b"MAIM");
+        assert_eq!(VoxFile::parse(&not_main), Err(VoxError::MissingMain));
+        for len in 0..SCENE.len() {
+            assert!(VoxFile::parse(&SCENE[..len]).is_err());
+        }
+
+        //The single model fixture has SIZE right after the MAIN header, then XYZI.
+        let mut out_of_bounds = SINGLE.to_vec();
+        out_of_bounds[60] = 3;
+        assert_eq!(
+            VoxFile::parse(&out_of_bounds),
+            Err(VoxError::VoxelOutOfBounds(ivec3(3, 0, 0)))
+        );
+        let mut zero_size = SINGLE.to_vec();
+        zero_size[32..36].copy_from_slice(&0i32.to_le_bytes());
+        assert_eq!(
+            VoxFile::parse(&zero_size),
+            Err(VoxError::InvalidSize(ivec3(0, 2, 4)))
+        );
+    }
+
+    #[test]
+    fn test_scene_bounds() {
+        let registry = BlockRegistry::default();
+        let mut vox = VoxFile::parse(SINGLE).unwrap();
+        vox.instances[0].translation = ivec3(0, i32::MIN, 0);
+        assert_eq!(
+            vox.to_clipboard(&registry),
+            Err(VoxError::SceneTooLarge(ivec3(0, i32::MIN, 0)))
+        );
+        //Two small models far apart would need a huge clipboard.
+        let mut far = vox.instances[0];
+        far.translation = ivec3(1000, 1000, 1000);
+        vox.instances = vec![
+            far,
+            VoxInstance {
+                translation: IVec3::ZERO,
+                ..far
+            },
+        ];
+        assert!(matches!(
+            vox.to_clipboard(&registry),
+            Err(VoxError::SceneTooLarge(_))
+        ));
+        assert!(matches!(
+            parse_translation("0 2000000 0"),
+            Err(VoxError::InvalidAttribute(_))
+        ));
+        assert_eq!(parse_translation("-3 0 7"), Ok(ivec3(-3, 0, 7)));
+    }
+}